@@ -0,0 +1,46 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use json_diff::{DiffCache, diff};
+use serde_json::{Value, json};
+use std::hint::black_box;
+
+/// A large base document and candidates that each change one record.
+fn documents() -> (Value, Vec<Value>) {
+    let records: serde_json::Map<String, Value> = (0..1_000)
+        .map(|i| {
+            let record = json!({"id": i, "tags": ["a", "b"], "meta": {"owner": "team", "rev": 1}});
+            (format!("r{i}"), record)
+        })
+        .collect();
+    let base = Value::Object(records);
+    let candidates = (0..50)
+        .map(|i| {
+            let mut candidate = base.clone();
+            candidate[format!("r{}", i * 20)]["meta"]["rev"] = json!(2);
+            candidate
+        })
+        .collect();
+    (base, candidates)
+}
+
+fn bench_cache(c: &mut Criterion) {
+    let (base, candidates) = documents();
+
+    c.bench_function("diff x50 (uncached)", |b| {
+        b.iter(|| {
+            for candidate in &candidates {
+                black_box(diff(black_box(&base), candidate));
+            }
+        })
+    });
+    let cache = DiffCache::new(&base);
+    c.bench_function("diff x50 (DiffCache)", |b| {
+        b.iter(|| {
+            for candidate in &candidates {
+                black_box(cache.diff(black_box(candidate)));
+            }
+        })
+    });
+}
+
+criterion_group!(benches, bench_cache);
+criterion_main!(benches);
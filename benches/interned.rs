@@ -0,0 +1,38 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use json_diff::{diff, diff_interned};
+use serde_json::{Value, json};
+use std::hint::black_box;
+
+/// A wide document of deeply nested records, each with the same long key names.
+fn document(version: u64) -> Value {
+    let records: serde_json::Map<String, Value> = (0..2_000)
+        .map(|i| {
+            let record = json!({
+                "configuration": {
+                    "networking": {
+                        "interfaces": {
+                            "primary": {"address": format!("10.0.{}.{}", i / 256, i % 256), "version": version}
+                        }
+                    }
+                }
+            });
+            (format!("record_{i}"), record)
+        })
+        .collect();
+    Value::Object(records)
+}
+
+fn bench_paths(c: &mut Criterion) {
+    let before = document(1);
+    let after = document(2);
+
+    c.bench_function("diff (string paths)", |b| {
+        b.iter(|| diff(black_box(&before), black_box(&after)))
+    });
+    c.bench_function("diff_interned (segment paths)", |b| {
+        b.iter(|| diff_interned(black_box(&before), black_box(&after)))
+    });
+}
+
+criterion_group!(benches, bench_paths);
+criterion_main!(benches);
@@ -0,0 +1,34 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use json_diff::{diff, diff_parallel};
+use serde_json::{Value, json};
+use std::hint::black_box;
+
+/// A 10k-key object and a copy with every tenth record changed.
+fn documents() -> (Value, Value) {
+    let records: serde_json::Map<String, Value> = (0..10_000)
+        .map(|i| {
+            let record = json!({"id": i, "tags": ["a", "b"], "meta": {"owner": "team", "rev": 1}});
+            (format!("r{i}"), record)
+        })
+        .collect();
+    let before = Value::Object(records);
+    let mut after = before.clone();
+    for i in (0..10_000).step_by(10) {
+        after[format!("r{i}")]["meta"]["rev"] = json!(2);
+    }
+    (before, after)
+}
+
+fn bench_parallel(c: &mut Criterion) {
+    let (before, after) = documents();
+
+    c.bench_function("diff 10k keys (serial)", |b| {
+        b.iter(|| black_box(diff(black_box(&before), black_box(&after))))
+    });
+    c.bench_function("diff 10k keys (diff_parallel)", |b| {
+        b.iter(|| black_box(diff_parallel(black_box(&before), black_box(&after))))
+    });
+}
+
+criterion_group!(benches, bench_parallel);
+criterion_main!(benches);
@@ -0,0 +1,1804 @@
+//! Applying and reverting deltas.
+
+use crate::path::{
+    get_path, get_segments, get_segments_mut, natural_cmp, parse_from_end, parse_index,
+    parse_position, resolve_position,
+};
+use crate::shape::fits;
+use crate::{
+    ApplyOptions, ArrayOp, Change, Delta, DotCodec, JsonDiffError, PathCodec, number, reorder,
+    trace,
+};
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+
+/// Final path segment addressing the end of an array, as in RFC 6902.
+const APPEND: &str = "-";
+
+/// Apply a `Delta` to an original JSON value, returning a new `Value`.
+///
+/// Changes are applied in four passes:
+///
+/// 1. every change that edits in place (`Modify`, `Set`, relocations and `Add`s of object
+///    keys), in path order, so parents are created before their children;
+/// 2. removals of object keys, deepest path first;
+/// 3. removals of array elements (and `RemoveRange`s), highest index first;
+/// 4. insertions of array elements (`+N` segments, and `InsertRange`s), lowest index first.
+///
+/// A removal therefore never runs before a change beneath it: a delta that modifies
+/// `a.b` and removes `a` (as merged deltas can) ends with `a` gone, instead of the modify
+/// recreating it. Removing both `a.b` and `a` also works under `try_apply`, since `a.b`
+/// goes first. The array passes keep element indices in a delta meaningful: removals
+/// refer to positions in the old array and insertions to positions in the new one (see
+/// the crate docs).
+///
+/// `apply` is lenient: missing intermediate objects are created and changes that cannot
+/// be applied are skipped. Use `try_apply` to get an error instead.
+///
+/// No delta makes `apply` (or any other apply/revert function) panic, so deltas read from
+/// untrusted storage are safe to apply. Pathological inputs are handled as follows:
+///
+/// - the empty path refers to the whole document (removing it leaves `null`);
+/// - empty segments, as in `a..b`, `.a` or `a.`, name the object key `""` (`try_apply`
+///   and the other strict functions reject them with `JsonDiffError::InvalidPath`);
+/// - a path that continues through a scalar (or through an array with a non-index
+///   segment) replaces that value with an object, and missing intermediate objects are
+///   created, except for a `Remove`, which is skipped without creating anything;
+/// - an insertion past the end of an array appends, while removing or replacing an
+///   element that does not exist is skipped (as is removing `-` from an empty array);
+/// - a negative index `-N` in a hand-written delta addresses the `N`th element from the
+///   end of the array as it is when the change is applied (`-1` is the last element, and
+///   an `Add` at `-1` inserts before it); one reaching before the start is skipped;
+/// - a `Move` or `Copy` whose source is missing is skipped;
+/// - a `NumberDelta` whose target is missing or not a number is skipped, as is one whose
+///   sum would not be a finite number;
+/// - an `ArrayOps` script whose target is not an array is skipped, as is each of its
+///   steps that addresses no element (an `Insert` past the end appends).
+/// - a `Reorder` whose target is missing or not an object is skipped;
+/// - a `RemoveRange` running past the end of its array removes the elements that exist,
+///   and an `InsertRange` past the end appends; either is skipped if its parent is not
+///   an array.
+pub fn apply(original: &Value, delta: &Delta) -> Value {
+    apply_checked(original, delta).0
+}
+
+/// Apply a `Delta` and report whether the result differs from `original`.
+///
+/// The flag is tracked while applying (a change counts only if it actually altered the
+/// tree), so no extra full-document comparison is made. An empty delta, or one whose
+/// changes all write values already present, yields `false`.
+pub fn apply_checked(original: &Value, delta: &Delta) -> (Value, bool) {
+    let mut result = original.clone();
+    // Lenient mode never fails.
+    let changed = apply_changes(&mut result, delta, None, false, &DotCodec).unwrap_or(true);
+    (result, changed)
+}
+
+/// Apply a `Delta` leniently, borrowing `original` instead of returning a copy when
+/// nothing changes.
+///
+/// An empty delta returns `Cow::Borrowed` without cloning anything, which makes this
+/// cheap in polling loops that mostly see no changes. Otherwise the delta is applied as
+/// by [`apply_checked`], and the copy is discarded in favour of `original` if no change
+/// altered it.
+///
+/// ```rust
+/// use std::borrow::Cow;
+/// use serde_json::json;
+/// use json_diff::{apply_cow, diff, Delta};
+///
+/// let doc = json!({"a": 1});
+/// assert!(matches!(apply_cow(&doc, &Delta::new()), Cow::Borrowed(_)));
+/// let delta = diff(&doc, &json!({"a": 2}));
+/// assert_eq!(apply_cow(&doc, &delta).into_owned(), json!({"a": 2}));
+/// ```
+pub fn apply_cow<'a>(original: &'a Value, delta: &Delta) -> Cow<'a, Value> {
+    if delta.is_empty() {
+        return Cow::Borrowed(original);
+    }
+    match apply_checked(original, delta) {
+        (result, true) => Cow::Owned(result),
+        (_, false) => Cow::Borrowed(original),
+    }
+}
+
+/// Apply a `Delta` leniently and also return the delta that undoes it.
+///
+/// The undo delta records the value each change actually replaced, captured as the change
+/// is applied, rather than the values recorded in `delta`. It therefore restores
+/// `original` exactly even when the base has drifted: a `Modify` whose `old` no longer
+/// matches is undone back to the value that was really there, a change that was skipped
+/// leaves nothing to undo, and an `Add` that overwrote an existing key is undone by
+/// restoring that key instead of removing it. Changes to array elements, whose indices
+/// shift as elements come and go, are undone by restoring the whole array, and missing
+/// objects created on the way to a target are removed again. Invert a delta with
+/// [`Delta::invert`] only when the base is known to match.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{apply, apply_with_undo, DeltaBuilder};
+///
+/// // The delta expects `count` to be 1, but the document has moved on.
+/// let delta = DeltaBuilder::new().modify("count", json!(1), json!(5)).build();
+/// let original = json!({"count": 3});
+/// let (result, undo) = apply_with_undo(&original, &delta);
+/// assert_eq!(result, json!({"count": 5}));
+/// assert_eq!(apply(&result, &undo), original);
+/// ```
+pub fn apply_with_undo(original: &Value, delta: &Delta) -> (Value, Delta) {
+    let mut result = original.clone();
+    let mut undo = Delta::new();
+    for (path, change) in plan(original, delta, &DotCodec) {
+        let mut targets: Vec<_> = match change.endpoints() {
+            Some((from, to)) => vec![from, to],
+            None => vec![path],
+        }
+        .into_iter()
+        .map(|target| undo_target(&result, target, &DotCodec))
+        .collect();
+        targets.dedup();
+        // A value restored as a whole already restores everything under it.
+        let priors: Vec<_> = targets
+            .iter()
+            .filter(|target| {
+                !under_any(target, &DotCodec, |prefix| {
+                    undo.contains_key(prefix) || targets.iter().any(|other| other == prefix)
+                })
+            })
+            .map(|target| (target.clone(), lookup(&result, target, &DotCodec).cloned()))
+            .collect();
+        // Lenient mode never fails.
+        let _ = apply_change(&mut result, path, change, false, &DotCodec);
+        for (target, prior) in priors {
+            let now = lookup(&result, &target, &DotCodec);
+            record_restore(&mut undo, target, prior, now);
+        }
+    }
+    (result, undo)
+}
+
+/// The path whose value, captured before a change at `path`, is enough to undo it: the
+/// array holding an element `path` addresses, the first step `path` would have to create
+/// (or replace, for a scalar in the way), or `path` itself. Array steps are spelled as
+/// the plain index they resolve to, so each location has a single path.
+fn undo_target(root: &Value, path: &str, codec: &dyn PathCodec) -> String {
+    let segments = codec.decode(path);
+    let mut resolved = Vec::with_capacity(segments.len());
+    let mut current = root;
+    for (i, segment) in segments.iter().enumerate() {
+        let last = i + 1 == segments.len();
+        let next = match current {
+            Value::Object(obj) => obj.get(segment).map(|next| (segment.clone(), next)),
+            Value::Array(items) if !last => resolve_position(segment, items.len())
+                .and_then(|index| Some((index.to_string(), items.get(index)?))),
+            _ => None,
+        };
+        match next {
+            Some((step, next)) => {
+                resolved.push(step);
+                current = next;
+            }
+            None => {
+                if current.is_object() {
+                    resolved.push(segment.clone());
+                }
+                break;
+            }
+        }
+    }
+    codec.encode(&resolved)
+}
+
+/// Whether `recorded` holds for a proper prefix of `path`, the root included.
+fn under_any(path: &str, codec: &dyn PathCodec, recorded: impl Fn(&str) -> bool) -> bool {
+    let segments = codec.decode(path);
+    (0..segments.len()).any(|len| recorded(&codec.encode(&segments[..len])))
+}
+
+/// Record in `undo` the change that turns `now` at `path` back into `prior`. If `path` is
+/// already recorded, the value captured first is the one restored.
+fn record_restore(undo: &mut Delta, path: String, prior: Option<Value>, now: Option<&Value>) {
+    let prior = match undo.remove(&path) {
+        Some(Change::Add(v) | Change::Modify { new: v, .. }) => Some(v),
+        Some(_) => None,
+        None => prior,
+    };
+    let change = match (prior, now) {
+        (Some(v), Some(w)) if v != *w => Change::Modify {
+            old: w.clone(),
+            new: v,
+        },
+        (Some(v), None) => Change::Add(v),
+        (None, Some(w)) => Change::Remove(w.clone()),
+        _ => return,
+    };
+    undo.insert(path, change);
+}
+
+/// Apply changes leniently in exactly the order given, returning a new `Value`.
+///
+/// `apply` takes a `Delta`, whose map is keyed (and so iterated) by path, and orders its
+/// changes in passes by kind and path (see `apply`). `apply_ordered` performs no
+/// reordering, giving precise control where the order of parent/child or sibling
+/// changes matters: removing `a` and then adding `a.b` leaves `{"a": {"b": ..}}`,
+/// while the reverse order leaves no `a`. The same path may appear more than once.
+/// Array element segments are resolved against the array as the preceding changes left
+/// it. Changes that cannot be applied are skipped, as with `apply`.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{apply_ordered, Change};
+///
+/// let changes = [
+///     ("log.-".to_string(), Change::Add(json!("first"))),
+///     ("log.-".to_string(), Change::Add(json!("second"))),
+/// ];
+/// assert_eq!(apply_ordered(&json!({"log": []}), &changes), json!({"log": ["first", "second"]}));
+/// ```
+pub fn apply_ordered(original: &Value, changes: &[(String, Change)]) -> Value {
+    let mut result = original.clone();
+    for (path, change) in changes {
+        // Lenient mode never fails.
+        let _ = apply_change(&mut result, path, change, false, &DotCodec);
+    }
+    result
+}
+
+/// What `apply_report` did with each path of a delta.
+///
+/// Paths are listed in the order the changes were applied. A `Move` or `Rename` lists its
+/// `from` path as removed and its `to` path as created or overwritten.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ApplyReport {
+    /// Paths that did not exist and were written, including array element insertions.
+    pub created: Vec<String>,
+    /// Paths whose existing value was replaced.
+    pub overwritten: Vec<String>,
+    /// Paths whose value was removed.
+    pub removed: Vec<String>,
+    /// Paths whose change left the document as it was: `Unchanged`s, writes of a value
+    /// already present and changes `apply` skips (such as removing a missing key).
+    pub skipped: Vec<String>,
+}
+
+/// Apply a `Delta` like `apply`, reporting which paths were created, overwritten, removed
+/// or skipped.
+///
+/// Each target is inspected just before its change is applied, so the report reflects the
+/// document as the earlier changes left it.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{apply_report, DeltaBuilder};
+///
+/// let delta = DeltaBuilder::new()
+///     .add("owner", json!("ann"))
+///     .modify("status", json!("open"), json!("closed"))
+///     .build();
+/// let (_, report) = apply_report(&json!({"status": "open"}), &delta);
+/// assert_eq!(report.created, ["owner"]);
+/// assert_eq!(report.overwritten, ["status"]);
+/// ```
+pub fn apply_report(original: &Value, delta: &Delta) -> (Value, ApplyReport) {
+    let mut result = original.clone();
+    let mut report = ApplyReport::default();
+    for (path, change) in plan(&result, delta, &DotCodec) {
+        let (target, inserting) = match change {
+            Change::Move { to, .. } | Change::Rename { to, .. } | Change::Copy { to, .. } => {
+                (to.as_str(), true)
+            }
+            Change::Modify { .. }
+            | Change::Set(_)
+            | Change::NumberDelta { .. }
+            | Change::Opaque { .. }
+            | Change::Reorder { .. } => (path, false),
+            _ => (path, true),
+        };
+        let existed = occupied(&result, target, inserting);
+        // Lenient mode never fails.
+        if !apply_change(&mut result, path, change, false, &DotCodec).unwrap_or(true) {
+            report.skipped.push(path.to_string());
+            continue;
+        }
+        if let Change::Move { from, .. } | Change::Rename { from, .. } = change {
+            report.removed.push(from.clone());
+        }
+        let list = match change {
+            Change::Remove(_) | Change::RemoveRange(_) => &mut report.removed,
+            _ if existed => &mut report.overwritten,
+            _ => &mut report.created,
+        };
+        list.push(target.to_string());
+    }
+    (result, report)
+}
+
+/// Apply a `Delta` like `apply`, timing each change (`profiling` feature).
+///
+/// Returns the result and, for every change in the order it was applied, its path and the
+/// time spent applying it, including navigating to its target. Sorting the timings shows
+/// which paths or kinds of change dominate on a large document, such as deeply nested
+/// paths that are expensive to reach.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{apply, apply_profiled, diff};
+/// use std::time::Duration;
+///
+/// let before = json!({"a": {"b": {"c": 1}}, "d": 1});
+/// let delta = diff(&before, &json!({"a": {"b": {"c": 2}}, "d": 2}));
+/// let (result, timings) = apply_profiled(&before, &delta);
+/// assert_eq!(result, apply(&before, &delta));
+/// let paths: Vec<&str> = timings.iter().map(|(path, _)| path.as_str()).collect();
+/// assert_eq!(paths, ["a.b.c", "d"]);
+/// let total: Duration = timings.iter().map(|(_, took)| *took).sum();
+/// let slowest = timings.iter().max_by_key(|(_, took)| *took).unwrap();
+/// assert!(slowest.1 <= total);
+/// ```
+#[cfg(feature = "profiling")]
+pub fn apply_profiled(
+    original: &Value,
+    delta: &Delta,
+) -> (Value, Vec<(String, std::time::Duration)>) {
+    let mut result = original.clone();
+    let mut timings = Vec::with_capacity(delta.len());
+    for (path, change) in plan(&result, delta, &DotCodec) {
+        let start = std::time::Instant::now();
+        // Lenient mode never fails.
+        let _ = apply_change(&mut result, path, change, false, &DotCodec);
+        timings.push((path.to_string(), start.elapsed()));
+    }
+    (result, timings)
+}
+
+/// Apply only the `Add`s of `delta` that fill in a missing value, returning the result and
+/// the paths of all other changes, which were skipped.
+///
+/// An `Add` is applied when its target object key is absent or it inserts into an array,
+/// and the path leads through objects and arrays only: adding below a scalar, which
+/// `apply` would replace with an object, is skipped too. Every change that would replace,
+/// remove or relocate existing data (`Modify`, `Remove`, `Move`, ...) is skipped and
+/// reported, as are `Add`s to keys that already exist. `Unchanged` entries are neither
+/// applied nor reported. This layers defaults under a document without clobbering it.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{apply_non_destructive, DeltaBuilder};
+///
+/// let defaults = DeltaBuilder::new()
+///     .add("port", json!(80))
+///     .add("host", json!("localhost"))
+///     .build();
+/// let (config, skipped) = apply_non_destructive(&json!({"port": 8080}), &defaults);
+/// assert_eq!(config, json!({"port": 8080, "host": "localhost"}));
+/// assert_eq!(skipped, ["port"]);
+/// ```
+pub fn apply_non_destructive(original: &Value, delta: &Delta) -> (Value, Vec<String>) {
+    let mut result = original.clone();
+    let mut skipped = Vec::new();
+    for (path, change) in plan(&result, delta, &DotCodec) {
+        match change {
+            Change::Unchanged(_) => {}
+            Change::Add(_) if !occupied(&result, path, true) && fits(&result, path) => {
+                // Lenient mode never fails.
+                let _ = apply_change(&mut result, path, change, false, &DotCodec);
+            }
+            _ => skipped.push(path.to_string()),
+        }
+    }
+    (result, skipped)
+}
+
+/// Whether a write to `path` would replace an existing value. Inserting into an array
+/// never does, as it shifts the element at the index instead.
+fn occupied(root: &Value, path: &str, inserting: bool) -> bool {
+    if path.is_empty() {
+        return true;
+    }
+    let (parent, key) = path.rsplit_once('.').unwrap_or(("", path));
+    match get_path(root, parent) {
+        Some(Value::Object(obj)) => obj.contains_key(key),
+        Some(Value::Array(items)) => {
+            !inserting && resolve_position(key, items.len()).is_some_and(|i| i < items.len())
+        }
+        _ => false,
+    }
+}
+
+/// Apply a `Delta`, failing on the first change that does not fit the document.
+///
+/// Unlike `apply`, nothing is created implicitly: the parent of every target must exist,
+/// `Remove` and `Modify` targets must exist, and `Move`/`Copy` sources must exist
+/// (`JsonDiffError::PathNotFound` otherwise). Array indices must be in range
+/// (`JsonDiffError::IndexOutOfBounds` otherwise), and target paths must not contain empty
+/// segments (`JsonDiffError::InvalidPath` otherwise). A `NumberDelta` must find a number
+/// to add to (`JsonDiffError::StaleValue` otherwise).
+pub fn try_apply(original: &Value, delta: &Delta) -> Result<Value, JsonDiffError> {
+    try_apply_with(original, delta, &ApplyOptions::default())
+}
+
+/// Like `try_apply`, with additional checks configured by `options`.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{try_apply_with, ApplyOptions, DeltaBuilder, JsonDiffError};
+///
+/// let options = ApplyOptions { add_must_not_exist: true, ..Default::default() };
+/// let delta = DeltaBuilder::new().add("owner", json!("bob")).build();
+/// assert_eq!(
+///     try_apply_with(&json!({"owner": "ann"}), &delta, &options),
+///     Err(JsonDiffError::PathExists { path: "owner".to_string() })
+/// );
+/// ```
+pub fn try_apply_with(
+    original: &Value,
+    delta: &Delta,
+    options: &ApplyOptions,
+) -> Result<Value, JsonDiffError> {
+    let mut result = original.clone();
+    apply_changes(&mut result, delta, Some(options), true, &DotCodec)?;
+    Ok(result)
+}
+
+/// Apply a `Delta` leniently like `apply`, enforcing the checks of `options`.
+///
+/// Changes that do not fit the document are skipped and missing intermediate objects are
+/// created, as with `apply`, but a change that breaks one of the checks fails with its
+/// error instead. This is the entry point for `ApplyOptions::max_apply_depth`, which caps
+/// the nesting that creating intermediate objects can build.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{apply_with, ApplyOptions, DeltaBuilder, JsonDiffError};
+///
+/// let options = ApplyOptions { max_apply_depth: Some(2), ..Default::default() };
+/// let shallow = DeltaBuilder::new().add("a.b", json!(1)).build();
+/// assert_eq!(apply_with(&json!({}), &shallow, &options), Ok(json!({"a": {"b": 1}})));
+/// let deep = DeltaBuilder::new().add("a.b.c", json!(1)).build();
+/// assert!(matches!(
+///     apply_with(&json!({}), &deep, &options),
+///     Err(JsonDiffError::PathTooDeep { .. })
+/// ));
+/// ```
+pub fn apply_with(
+    original: &Value,
+    delta: &Delta,
+    options: &ApplyOptions,
+) -> Result<Value, JsonDiffError> {
+    let mut result = original.clone();
+    apply_changes(&mut result, delta, Some(options), false, &DotCodec)?;
+    Ok(result)
+}
+
+/// Apply a `Delta` leniently, editing `value` in place instead of cloning it.
+pub fn apply_in_place(value: &mut Value, delta: &Delta) {
+    // Lenient mode never fails.
+    let _ = apply_changes(value, delta, None, false, &DotCodec);
+}
+
+/// Apply a `Delta` in place with `try_apply`'s checks, all or nothing.
+///
+/// The changes are applied to a working copy that only replaces `value` once every change
+/// has succeeded, so on error `value` is left exactly as it was.
+pub fn apply_in_place_txn(value: &mut Value, delta: &Delta) -> Result<(), JsonDiffError> {
+    let mut working = value.clone();
+    apply_changes(
+        &mut working,
+        delta,
+        Some(&ApplyOptions::default()),
+        true,
+        &DotCodec,
+    )?;
+    *value = working;
+    Ok(())
+}
+
+/// Revert a `Delta` on a JSON value, returning the previous state.
+///
+/// Replays the inverse of each change in the reverse of the order `apply` uses. A `Copy`
+/// whose target no longer matches its source, and a `Set` (which has no old value), are
+/// left in place; use `try_revert` to get an error instead.
+///
+/// A value whose type changed (object ⇄ array, scalar ⇄ container, `null` ⇄ anything) is
+/// always recorded as one `Modify` holding both whole values, even where `DiffOptions`
+/// diff arrays element by element, so reverting restores the original type and value
+/// exactly. The exceptions are type changes a delta does not record in full: values made
+/// equal by `DiffOptions::coerce` or `numbers_value_equal` (`"1"` and `1`, `1` and `1.0`)
+/// produce no change at all, and a `Set` from `capture_old: false` is refused by
+/// `try_revert` with `JsonDiffError::Irreversible`.
+pub fn revert(original: &Value, delta: &Delta) -> Value {
+    let mut result = original.clone();
+    // Lenient mode never fails.
+    let _ = revert_changes(&mut result, delta, false, &DotCodec);
+    result
+}
+
+/// Revert a `Delta`, failing if a change cannot be cleanly undone.
+///
+/// Inverse changes are applied with `try_apply`'s checks. A `Copy` is undone by removing
+/// its target, which is only safe while the target still equals the source; otherwise
+/// `JsonDiffError::IrreversibleCopy` is returned. A `Set` yields `JsonDiffError::Irreversible`.
+pub fn try_revert(original: &Value, delta: &Delta) -> Result<Value, JsonDiffError> {
+    let mut result = original.clone();
+    revert_changes(&mut result, delta, true, &DotCodec)?;
+    Ok(result)
+}
+
+/// Apply a `Delta` whose paths are spelled with `codec`, as produced by
+/// [`diff_with_codec`](crate::diff_with_codec). Otherwise behaves like `apply`; changes at
+/// paths the codec does not accept (see `PathCodec::is_valid`) are skipped.
+pub fn apply_with_codec(original: &Value, delta: &Delta, codec: &dyn PathCodec) -> Value {
+    let mut result = original.clone();
+    // Lenient mode never fails.
+    let _ = apply_changes(&mut result, delta, None, false, codec);
+    result
+}
+
+/// Revert a `Delta` whose paths are spelled with `codec`. Otherwise behaves like `revert`.
+pub fn revert_with_codec(original: &Value, delta: &Delta, codec: &dyn PathCodec) -> Value {
+    let mut result = original.clone();
+    // Lenient mode never fails.
+    let _ = revert_changes(&mut result, delta, false, codec);
+    result
+}
+
+/// Apply every change of `delta` in plan order, enforcing the checks of `options` if
+/// given. `strict` applies with the checks of the strict functions; otherwise leniently.
+fn apply_changes(
+    root: &mut Value,
+    delta: &Delta,
+    options: Option<&ApplyOptions>,
+    strict: bool,
+    codec: &dyn PathCodec,
+) -> Result<bool, JsonDiffError> {
+    let mut changed = false;
+    for (path, change) in plan(root, delta, codec) {
+        if let Some(options) = options {
+            check_options(root, path, change, options, codec)?;
+        }
+        changed |= apply_change(root, path, change, strict, codec)?;
+    }
+    Ok(changed)
+}
+
+/// Enforce the `ApplyOptions` checks for one change before it is applied.
+fn check_options(
+    root: &Value,
+    path: &str,
+    change: &Change,
+    options: &ApplyOptions,
+    codec: &dyn PathCodec,
+) -> Result<(), JsonDiffError> {
+    if let Some(max_depth) = options.max_apply_depth {
+        let target = change.endpoints().map_or(path, |(_, to)| to);
+        if codec.decode(target).len() > max_depth {
+            return Err(JsonDiffError::PathTooDeep {
+                path: target.to_string(),
+                max_depth,
+            });
+        }
+    }
+    if options.add_must_not_exist
+        && matches!(change, Change::Add(_))
+        && occupied_key(root, path, codec)
+    {
+        return Err(JsonDiffError::PathExists {
+            path: path.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Whether `path` names an object key that is already present.
+fn occupied_key(root: &Value, path: &str, codec: &dyn PathCodec) -> bool {
+    let mut segments = codec.decode(path);
+    let Some(key) = segments.pop() else {
+        return false;
+    };
+    match get_segments(root, &segments) {
+        Some(Value::Object(obj)) => obj.contains_key(&key),
+        _ => false,
+    }
+}
+
+fn revert_changes(
+    root: &mut Value,
+    delta: &Delta,
+    strict: bool,
+    codec: &dyn PathCodec,
+) -> Result<(), JsonDiffError> {
+    for (path, change) in plan(root, delta, codec).into_iter().rev() {
+        match change {
+            Change::Copy { from, to } => {
+                let source = lookup(root, from, codec);
+                if source.is_some() && source == lookup(root, to, codec) {
+                    set_value(root, to, Edit::Remove, strict, codec)?;
+                } else if strict {
+                    return Err(JsonDiffError::IrreversibleCopy {
+                        from: from.clone(),
+                        to: to.clone(),
+                    });
+                }
+            }
+            Change::Unchanged(_) => {}
+            Change::Set(_) | Change::Opaque { .. } => {
+                if strict {
+                    return Err(JsonDiffError::Irreversible {
+                        path: path.to_string(),
+                    });
+                }
+            }
+            _ => {
+                apply_change(root, path, &change.clone().inverse(), strict, codec)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Order the changes of a delta for application to `root` (see `apply`).
+///
+/// A numeric last segment is only treated as an array element when its parent in `root` is
+/// not an object, so integer-keyed objects such as `{"0": .., "10": ..}` get key semantics.
+fn plan<'a>(root: &Value, delta: &'a Delta, codec: &dyn PathCodec) -> Vec<(&'a str, &'a Change)> {
+    application_order(delta, codec, |parent| {
+        matches!(get_segments(root, parent), Some(Value::Object(_)))
+    })
+}
+
+/// Order the changes of a delta for application, given whether the parent of a path (as
+/// segments) is an object.
+pub(crate) fn application_order<'a>(
+    delta: &'a Delta,
+    codec: &dyn PathCodec,
+    parent_is_object: impl Fn(&[String]) -> bool,
+) -> Vec<(&'a str, &'a Change)> {
+    let mut in_place = Vec::new();
+    let mut key_removals = Vec::new();
+    let mut removals_from_end = Vec::new();
+    let mut removals = Vec::new();
+    let mut insertions = Vec::new();
+    for (path, change) in delta {
+        let segments = codec.decode(path);
+        let (segment, parent) = segments
+            .split_last()
+            .map_or(("", &[][..]), |(last, parent)| (last.as_str(), parent));
+        let parent_is_object = !segments.is_empty() && parent_is_object(parent);
+        let bucket = match change {
+            Change::Remove(_) if parent_is_object => &mut key_removals,
+            _ if parent_is_object => &mut in_place,
+            Change::Remove(_) | Change::RemoveRange(_) if parse_index(segment).is_some() => {
+                &mut removals
+            }
+            Change::Remove(_) if parse_from_end(segment).is_some() => &mut removals_from_end,
+            Change::Remove(_) => &mut key_removals,
+            Change::Add(_) | Change::InsertRange(_) if parse_position(segment).is_some() => {
+                &mut insertions
+            }
+            _ => &mut in_place,
+        };
+        bucket.push((segments, path.as_str(), change));
+    }
+    key_removals.sort_by(|(a, path_a, _), (b, path_b, _)| {
+        b.len().cmp(&a.len()).then_with(|| path_a.cmp(path_b))
+    });
+    // `-N` counts from the end as it stands, so the element furthest from it goes first
+    // and the ones after it keep their positions.
+    removals_from_end.sort_by(|(a, ..), (b, ..)| {
+        let from_end = |segments: &[String]| segments.last().and_then(|s| parse_from_end(s));
+        natural_cmp(&a[..a.len() - 1], &b[..b.len() - 1])
+            .then_with(|| from_end(b).cmp(&from_end(a)))
+    });
+    removals.sort_by(|(a, ..), (b, ..)| natural_cmp(b, a));
+    insertions.sort_by(|(a, ..), (b, ..)| natural_cmp(a, b));
+    in_place
+        .into_iter()
+        .chain(key_removals)
+        .chain(removals_from_end)
+        .chain(removals)
+        .chain(insertions)
+        .map(|(_, path, change)| (path, change))
+        .collect()
+}
+
+/// Apply a single change, returning whether it altered `root`.
+fn apply_change(
+    root: &mut Value,
+    path: &str,
+    change: &Change,
+    strict: bool,
+    codec: &dyn PathCodec,
+) -> Result<bool, JsonDiffError> {
+    let result = perform_change(root, path, change, strict, codec);
+    trace::applied(path, change, &result);
+    result
+}
+
+fn perform_change(
+    root: &mut Value,
+    path: &str,
+    change: &Change,
+    strict: bool,
+    codec: &dyn PathCodec,
+) -> Result<bool, JsonDiffError> {
+    let edit = match change {
+        Change::Add(v) => Edit::Insert(v.clone()),
+        Change::Modify { new, .. } | Change::Set(new) => Edit::Replace(new.clone()),
+        Change::Remove(_) => Edit::Remove,
+        Change::Unchanged(_) => return Ok(false),
+        Change::Opaque { .. } if strict => {
+            return Err(JsonDiffError::Opaque {
+                path: path.to_string(),
+            });
+        }
+        Change::Opaque { .. } => return Ok(false),
+        Change::Reorder { keys, .. } => {
+            return match get_segments_mut(root, &codec.decode(path)) {
+                Some(Value::Object(obj)) => Ok(reorder::reorder_keys(obj, keys)),
+                Some(_) if strict => Err(JsonDiffError::StaleValue {
+                    path: path.to_string(),
+                }),
+                None if strict => Err(not_found(path)),
+                _ => Ok(false),
+            };
+        }
+        Change::RemoveRange(values) => {
+            return splice_range(root, path, values, false, strict, codec);
+        }
+        Change::InsertRange(values) => {
+            return splice_range(root, path, values, true, strict, codec);
+        }
+        Change::ArrayOps(ops) => {
+            let mut items = match lookup(root, path, codec) {
+                Some(Value::Array(items)) => items.clone(),
+                Some(_) if strict => {
+                    return Err(JsonDiffError::StaleValue {
+                        path: path.to_string(),
+                    });
+                }
+                None if strict => return Err(not_found(path)),
+                _ => return Ok(false),
+            };
+            let mut changed = false;
+            for op in ops {
+                changed |= perform_array_op(&mut items, path, op, strict, codec)?;
+            }
+            if !changed {
+                return Ok(false);
+            }
+            Edit::Replace(Value::Array(items))
+        }
+        Change::NumberDelta { by } => {
+            let sum = match source_value(root, path, strict, codec)? {
+                Some(Value::Number(current)) => number::add(&current, by),
+                _ => None,
+            };
+            match sum {
+                Some(sum) => Edit::Replace(sum.into()),
+                None if strict => {
+                    return Err(JsonDiffError::StaleValue {
+                        path: path.to_string(),
+                    });
+                }
+                None => return Ok(false),
+            }
+        }
+        Change::Move { from, to } | Change::Rename { from, to } => {
+            let Some(value) = source_value(root, from, strict, codec)? else {
+                return Ok(false);
+            };
+            if from == to {
+                return Ok(false);
+            }
+            set_value(root, from, Edit::Remove, strict, codec)?;
+            set_value(root, to, Edit::Insert(value), strict, codec)?;
+            return Ok(true);
+        }
+        Change::Copy { from, to } => {
+            let Some(value) = source_value(root, from, strict, codec)? else {
+                return Ok(false);
+            };
+            return set_value(root, to, Edit::Insert(value), strict, codec);
+        }
+    };
+    set_value(root, path, edit, strict, codec)
+}
+
+/// Remove or insert (when `inserting`) the run of array elements `values` starting at the
+/// element `path` addresses, in one splice.
+fn splice_range(
+    root: &mut Value,
+    path: &str,
+    values: &[Value],
+    inserting: bool,
+    strict: bool,
+    codec: &dyn PathCodec,
+) -> Result<bool, JsonDiffError> {
+    let mut segments = codec.decode(path);
+    let segment = segments.pop().unwrap_or_default();
+    let items = match get_segments_mut(root, &segments) {
+        Some(Value::Array(items)) => items,
+        _ if strict => return Err(not_found(path)),
+        _ => return Ok(false),
+    };
+    let len = items.len();
+    let Some(start) = resolve_position(&segment, len) else {
+        return if strict {
+            Err(not_found(path))
+        } else {
+            Ok(false)
+        };
+    };
+    let end = if inserting {
+        start
+    } else {
+        start + values.len()
+    };
+    if strict && end > len {
+        // The insertion point, or the last element to remove.
+        let index = if inserting { start } else { end - 1 };
+        return Err(JsonDiffError::IndexOutOfBounds {
+            path: path.to_string(),
+            index,
+            len,
+        });
+    }
+    let (start, end) = (start.min(len), end.min(len));
+    if inserting {
+        items.splice(start..start, values.iter().cloned());
+        Ok(!values.is_empty())
+    } else {
+        items.drain(start..end);
+        Ok(start < end)
+    }
+}
+
+/// Perform one step of a `Change::ArrayOps` script on `items`, the array at `path`.
+fn perform_array_op(
+    items: &mut Vec<Value>,
+    path: &str,
+    op: &ArrayOp,
+    strict: bool,
+    codec: &dyn PathCodec,
+) -> Result<bool, JsonDiffError> {
+    let (index, len) = (op.index(), items.len());
+    let in_range = match op {
+        ArrayOp::Insert { .. } => !strict || index <= len,
+        _ => index < len,
+    };
+    if !in_range {
+        return if strict {
+            Err(JsonDiffError::IndexOutOfBounds {
+                path: codec.join(path, &index.to_string()),
+                index,
+                len,
+            })
+        } else {
+            Ok(false)
+        };
+    }
+    match op {
+        ArrayOp::Insert { value, .. } => items.insert(index.min(len), value.clone()),
+        ArrayOp::Delete { .. } => {
+            items.remove(index);
+        }
+        ArrayOp::Update { change, .. } => {
+            return perform_change(&mut items[index], "", change, strict, codec);
+        }
+    }
+    Ok(true)
+}
+
+fn source_value(
+    root: &Value,
+    from: &str,
+    strict: bool,
+    codec: &dyn PathCodec,
+) -> Result<Option<Value>, JsonDiffError> {
+    match lookup(root, from, codec) {
+        Some(value) => Ok(Some(value.clone())),
+        None if strict => Err(not_found(from)),
+        None => Ok(None),
+    }
+}
+
+/// What `set_value` does at the target location. Object keys treat `Insert` and
+/// `Replace` alike; array elements distinguish inserting before an index from
+/// overwriting it.
+pub(crate) enum Edit {
+    Insert(Value),
+    Replace(Value),
+    Remove,
+}
+
+/// Perform `edit` at `path`, returning whether anything in `root` changed (including
+/// intermediate objects created on the way).
+///
+/// In strict mode every location the edit relies on must already exist; otherwise the
+/// edit is skipped (or intermediate objects are created) as `apply` documents.
+pub(crate) fn set_value(
+    root: &mut Value,
+    path: &str,
+    edit: Edit,
+    strict: bool,
+    codec: &dyn PathCodec,
+) -> Result<bool, JsonDiffError> {
+    if path.is_empty() {
+        return Ok(set_root(root, edit));
+    }
+    let parts = codec.decode(path);
+    let malformed = !codec.is_valid(path);
+    if malformed && !strict {
+        return Ok(false);
+    }
+    if strict && (malformed || parts.iter().any(String::is_empty)) {
+        return Err(JsonDiffError::InvalidPath {
+            path: path.to_string(),
+        });
+    }
+    let Some((key, parents)) = parts.split_last() else {
+        return Ok(false);
+    };
+    let mut current = root;
+    let mut changed = false;
+    // A removal never creates the parents it would remove from.
+    let existing_only = strict || matches!(edit, Edit::Remove);
+    // Navigate to the parent of the target
+    for (i, segment) in parents.iter().enumerate() {
+        let container = empty_container(&parts[i + 1]);
+        current = match descend(current, segment, container, existing_only, &mut changed) {
+            Some(next) => next,
+            None if strict => return Err(not_found(path)),
+            None => return Ok(false),
+        };
+    }
+    let missing = |edited: bool| {
+        if strict {
+            Err(not_found(path))
+        } else {
+            Ok(edited)
+        }
+    };
+    let edited = match current {
+        Value::Object(obj) => match edit {
+            Edit::Replace(_) if strict && !obj.contains_key(key) => return Err(not_found(path)),
+            Edit::Insert(v) | Edit::Replace(v) => {
+                if obj.get(key) == Some(&v) {
+                    false
+                } else {
+                    obj.insert(key.clone(), v);
+                    true
+                }
+            }
+            Edit::Remove => match reorder::remove_key(obj, key) {
+                Some(_) => true,
+                None => return missing(changed),
+            },
+        },
+        Value::Array(items) if key == APPEND => match edit {
+            Edit::Insert(v) => {
+                items.push(v);
+                true
+            }
+            Edit::Remove if !items.is_empty() => {
+                items.pop();
+                true
+            }
+            _ => return missing(changed),
+        },
+        Value::Array(items) => {
+            let len = items.len();
+            let out_of_bounds = |index| {
+                if strict {
+                    Err(JsonDiffError::IndexOutOfBounds {
+                        path: path.to_string(),
+                        index,
+                        len,
+                    })
+                } else {
+                    Ok(changed)
+                }
+            };
+            let index = match (resolve_position(key, len), parse_from_end(key)) {
+                (Some(index), _) => index,
+                (None, Some(back)) => return out_of_bounds(back),
+                (None, None) => return missing(changed),
+            };
+            let out_of_bounds = || out_of_bounds(index);
+            match edit {
+                Edit::Insert(_) if strict && index > len => return out_of_bounds(),
+                Edit::Insert(v) => {
+                    items.insert(index.min(len), v);
+                    true
+                }
+                Edit::Replace(v) => match items.get_mut(index) {
+                    Some(slot) if *slot != v => {
+                        *slot = v;
+                        true
+                    }
+                    Some(_) => false,
+                    None => return out_of_bounds(),
+                },
+                Edit::Remove => {
+                    if index < len {
+                        items.remove(index);
+                        true
+                    } else {
+                        return out_of_bounds();
+                    }
+                }
+            }
+        }
+        _ => return missing(changed),
+    };
+    Ok(changed || edited)
+}
+
+/// Perform `edit` on the whole document (the empty path). Removing the root leaves `null`.
+fn set_root(root: &mut Value, edit: Edit) -> bool {
+    let value = match edit {
+        Edit::Insert(v) | Edit::Replace(v) => v,
+        Edit::Remove => Value::Null,
+    };
+    if *root == value {
+        false
+    } else {
+        *root = value;
+        true
+    }
+}
+
+/// The container to create for a missing step whose child segment is `next`: an array
+/// when appending to it, an object otherwise.
+fn empty_container(next: &str) -> Value {
+    if next == APPEND {
+        Value::Array(Vec::new())
+    } else {
+        Value::Object(Map::new())
+    }
+}
+
+/// Step into `segment` of `current`, following existing object keys and array elements.
+/// Outside strict mode missing steps are created as `container` (replacing scalars and
+/// arrays that cannot be indexed by `segment`); in strict mode they yield `None`.
+fn descend<'a>(
+    current: &'a mut Value,
+    segment: &str,
+    container: Value,
+    strict: bool,
+    changed: &mut bool,
+) -> Option<&'a mut Value> {
+    let index = match &*current {
+        Value::Array(items) => resolve_position(segment, items.len()).filter(|&i| i < items.len()),
+        _ => None,
+    };
+    let exists = index.is_some() || current.get(segment).is_some();
+    if strict && !exists {
+        return None;
+    }
+    match (index, current) {
+        (Some(i), Value::Array(items)) => items.get_mut(i),
+        (_, Value::Object(obj)) => Some(obj.entry(segment).or_insert_with(|| {
+            *changed = true;
+            container
+        })),
+        (_, other) => {
+            *changed = true;
+            let mut obj = Map::new();
+            obj.insert(segment.to_string(), container);
+            *other = Value::Object(obj);
+            other.as_object_mut()?.get_mut(segment)
+        }
+    }
+}
+
+/// The value at `path` spelled with `codec`.
+fn lookup<'a>(root: &'a Value, path: &str, codec: &dyn PathCodec) -> Option<&'a Value> {
+    get_segments(root, &codec.decode(path))
+}
+
+fn not_found(path: &str) -> JsonDiffError {
+    JsonDiffError::PathNotFound {
+        path: path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use serde_json::json;
+
+    fn modify(old: Value, new: Value) -> Change {
+        Change::Modify { old, new }
+    }
+
+    #[test]
+    fn try_apply_matches_apply_for_valid_delta() {
+        let before = json!({"a": 1, "b": {"c": [1, 2]}});
+        let mut delta = Delta::new();
+        delta.insert("a".to_string(), modify(json!(1), json!(2)));
+        delta.insert("b.d".to_string(), Change::Add(json!(true)));
+        delta.insert("b.c.1".to_string(), Change::Remove(json!(2)));
+        delta.insert("b.c.+0".to_string(), Change::Add(json!(0)));
+
+        let applied = try_apply(&before, &delta);
+        assert_eq!(applied, Ok(json!({"a": 2, "b": {"c": [0, 1], "d": true}})));
+        assert_eq!(applied, Ok(apply(&before, &delta)));
+    }
+
+    #[test]
+    fn undo_restores_a_matching_base() {
+        let before = json!({"a": 1, "b": {"c": [1, 2, 3]}, "gone": true});
+        let after = json!({"a": 2, "b": {"c": [1, 3, 4], "d": null}});
+        let delta = crate::diff(&before, &after);
+
+        let (result, undo) = apply_with_undo(&before, &delta);
+        assert_eq!(result, after);
+        assert_eq!(apply(&result, &undo), before);
+        assert_eq!(
+            apply(&result, &undo),
+            apply(&result, &delta.invert(&result))
+        );
+
+        let (unchanged, undo) = apply_with_undo(&before, &Delta::new());
+        assert_eq!(unchanged, before);
+        assert!(undo.is_empty());
+    }
+
+    #[test]
+    fn ranges_splice_leniently_and_check_bounds_strictly() {
+        let doc = json!({"list": [0, 1, 2, 3], "obj": {"a": 1}});
+        let remove = Delta::from_iter([(
+            "list.2".to_string(),
+            Change::RemoveRange(vec![json!(2), json!(3), json!(4)]),
+        )]);
+        assert_eq!(apply(&doc, &remove)["list"], json!([0, 1]));
+        assert_eq!(
+            try_apply(&doc, &remove),
+            Err(JsonDiffError::IndexOutOfBounds {
+                path: "list.2".to_string(),
+                index: 4,
+                len: 4,
+            })
+        );
+
+        let insert = Delta::from_iter([(
+            "list.+9".to_string(),
+            Change::InsertRange(vec![json!("x"), json!("y")]),
+        )]);
+        assert_eq!(apply(&doc, &insert)["list"], json!([0, 1, 2, 3, "x", "y"]));
+        assert!(matches!(
+            try_apply(&doc, &insert),
+            Err(JsonDiffError::IndexOutOfBounds { index: 9, .. })
+        ));
+
+        let misplaced =
+            Delta::from_iter([("obj.0".to_string(), Change::InsertRange(vec![json!(1)]))]);
+        assert_eq!(apply(&doc, &misplaced), doc);
+        assert_eq!(
+            try_apply(&doc, &misplaced),
+            Err(JsonDiffError::PathNotFound {
+                path: "obj.0".to_string()
+            })
+        );
+    }
+
+    #[cfg(feature = "profiling")]
+    #[test]
+    fn profiling_times_every_change() {
+        let options = crate::DiffOptions {
+            set_arrays: vec!["list".to_string()],
+            ..Default::default()
+        };
+        let before = json!({"deep": {"a": {"b": {"c": 1}}}, "list": [1, 2, 3], "gone": true});
+        let after = json!({"deep": {"a": {"b": {"c": 2}}}, "list": [3, 4], "new": null});
+        let delta = crate::diff_with(&before, &after, &options);
+        let (result, timings) = apply_profiled(&before, &delta);
+        assert_eq!(result, apply(&before, &delta));
+        assert_eq!(result, after);
+        assert_eq!(timings.len(), delta.len());
+        let paths: Vec<&str> = timings.iter().map(|(path, _)| path.as_str()).collect();
+        let ordered: Vec<&str> = plan(&before, &delta, &DotCodec)
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(paths, ordered);
+    }
+
+    #[test]
+    fn apply_cow_borrows_when_nothing_changes() {
+        let doc = json!({"a": 1, "b": [1, 2]});
+        assert!(matches!(apply_cow(&doc, &Delta::new()), Cow::Borrowed(_)));
+        let no_op = crate::DeltaBuilder::new()
+            .modify("a", json!(0), json!(1))
+            .build();
+        assert!(matches!(apply_cow(&doc, &no_op), Cow::Borrowed(_)));
+
+        let delta = crate::diff(&doc, &json!({"a": 2, "b": [1, 2]}));
+        let applied = apply_cow(&doc, &delta);
+        assert!(matches!(applied, Cow::Owned(_)));
+        assert_eq!(applied.into_owned(), apply(&doc, &delta));
+    }
+
+    #[test]
+    fn lenient_removal_of_a_missing_nested_path_changes_nothing() {
+        let delta = crate::DeltaBuilder::new().remove("a.b", json!(1)).build();
+        for doc in [json!({}), json!({"a": 1}), json!({"a": [1]})] {
+            assert_eq!(apply_checked(&doc, &delta), (doc.clone(), false));
+            assert!(matches!(apply_cow(&doc, &delta), Cow::Borrowed(_)));
+            let (result, undo) = apply_with_undo(&doc, &delta);
+            assert_eq!(apply(&result, &undo), doc);
+        }
+    }
+
+    #[test]
+    fn non_destructive_apply_only_fills_gaps() {
+        let original = json!({
+            "name": "svc",
+            "limits": {"cpu": 2},
+            "tags": ["a"],
+            "level": 3,
+            "old": true
+        });
+        let delta = crate::DeltaBuilder::new()
+            .add("name", json!("other"))
+            .add("limits.memory", json!("1G"))
+            .add("limits.cpu", json!(4))
+            .add("owner", json!({"team": "core"}))
+            .add("tags.+1", json!("b"))
+            .add("level.deep", json!(1))
+            .modify("level", json!(3), json!(4))
+            .remove("old", json!(true))
+            .change("tags", Change::Unchanged(json!(["a"])))
+            .build();
+        let (result, skipped) = apply_non_destructive(&original, &delta);
+        assert_eq!(
+            result,
+            json!({
+                "name": "svc",
+                "limits": {"cpu": 2, "memory": "1G"},
+                "tags": ["a", "b"],
+                "level": 3,
+                "old": true,
+                "owner": {"team": "core"}
+            })
+        );
+        assert_eq!(
+            skipped,
+            ["level", "level.deep", "limits.cpu", "name", "old"]
+        );
+    }
+
+    #[test]
+    fn undo_restores_the_real_base_after_drift() {
+        let delta = crate::DeltaBuilder::new()
+            .modify("count", json!(1), json!(2))
+            .add("owner", json!("ann"))
+            .remove("legacy", json!("old"))
+            .modify("missing", json!(0), json!(1))
+            .build();
+        let drifted = json!({"count": 7, "owner": "bo", "legacy": "changed"});
+
+        let (result, undo) = apply_with_undo(&drifted, &delta);
+        assert_eq!(result, json!({"count": 2, "owner": "ann", "missing": 1}));
+        assert_eq!(apply(&result, &undo), drifted);
+        // Blindly inverting the delta restores the values it expected instead.
+        assert_ne!(apply(&result, &delta.invert(&result)), drifted);
+    }
+
+    #[test]
+    fn undo_records_only_the_paths_it_touched() {
+        let original = json!({
+            "big": (0..100).collect::<Vec<_>>(),
+            "tags": ["a", "b", "c"],
+            "rows": [{"n": 1}, {"n": 2}],
+            "src": {"k": 1},
+            "flag": true
+        });
+        let delta = crate::DeltaBuilder::new()
+            .remove("tags.0", json!("a"))
+            .add("tags.+2", json!("d"))
+            .modify("rows.1.n", json!(2), json!(3))
+            .add("deep.er.still", json!(1))
+            .add("flag.inner.x", json!(0))
+            .change(
+                "dst",
+                Change::Move {
+                    from: "src".to_string(),
+                    to: "dst".to_string(),
+                },
+            )
+            .build();
+        let (result, undo) = apply_with_undo(&original, &delta);
+        assert_eq!(result, apply(&original, &delta));
+        assert_eq!(
+            undo.keys().collect::<Vec<_>>(),
+            ["deep", "dst", "flag", "rows.1.n", "src", "tags"]
+        );
+        assert_eq!(apply(&result, &undo), original);
+    }
+
+    #[test]
+    fn apply_ordered_follows_the_given_order() {
+        let before = json!({"a": {"x": 1}});
+        let remove = ("a".to_string(), Change::Remove(json!({"x": 1})));
+        let add = ("a.b".to_string(), Change::Add(json!(2)));
+
+        assert_eq!(
+            apply_ordered(&before, &[remove.clone(), add.clone()]),
+            json!({"a": {"b": 2}})
+        );
+        assert_eq!(apply_ordered(&before, &[add, remove]), json!({}));
+
+        let first = ("items.+0".to_string(), Change::Add(json!("x")));
+        let second = ("items.+0".to_string(), Change::Add(json!("y")));
+        assert_eq!(
+            apply_ordered(&json!({"items": []}), &[first, second]),
+            json!({"items": ["y", "x"]})
+        );
+    }
+
+    #[test]
+    fn apply_report_classifies_each_path() {
+        let before = json!({"name": "a", "old": 1, "list": [1, 2], "same": true});
+        let mut delta = Delta::new();
+        delta.insert("name".to_string(), modify(json!("a"), json!("b")));
+        delta.insert("created.deep".to_string(), Change::Add(json!(1)));
+        delta.insert("old".to_string(), Change::Remove(json!(1)));
+        delta.insert("list.+0".to_string(), Change::Add(json!(0)));
+        delta.insert("same".to_string(), Change::Add(json!(true)));
+        delta.insert("missing".to_string(), Change::Remove(json!(null)));
+
+        let (applied, report) = apply_report(&before, &delta);
+        assert_eq!(applied, apply(&before, &delta));
+        assert_eq!(report.created, ["created.deep", "list.+0"]);
+        assert_eq!(report.overwritten, ["name"]);
+        assert_eq!(report.removed, ["old"]);
+        assert_eq!(report.skipped, ["same", "missing"]);
+    }
+
+    #[test]
+    fn apply_report_splits_moves() {
+        let mut delta = Delta::new();
+        delta.insert(
+            "b".to_string(),
+            Change::Move {
+                from: "a".to_string(),
+                to: "b".to_string(),
+            },
+        );
+        let (applied, report) = apply_report(&json!({"a": 1, "b": 2}), &delta);
+        assert_eq!(applied, json!({"b": 1}));
+        assert_eq!(report.removed, ["a"]);
+        assert_eq!(report.overwritten, ["b"]);
+    }
+
+    #[test]
+    fn parent_removal_runs_after_changes_beneath_it() {
+        // Merging "set a.b to 1" with "remove a" keeps both entries.
+        let before = json!({"a": {"b": 0, "c": {"d": 2}}, "keep": true});
+        let after = json!({"keep": true});
+        let mut delta = Delta::new();
+        delta.insert("a.b".to_string(), modify(json!(0), json!(1)));
+        delta.insert("a.c.d".to_string(), Change::Remove(json!(2)));
+        delta.insert("a".to_string(), Change::Remove(json!({"b": 1, "c": {}})));
+
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(try_apply(&before, &delta), Ok(after.clone()));
+        assert_eq!(revert(&after, &delta), before);
+    }
+
+    #[test]
+    fn integer_keyed_objects_use_key_semantics() {
+        let before = json!({"m": {"0": "a", "1": "b", "10": "c"}});
+        let after = json!({"m": {"0": "a", "2": "x"}});
+        let delta = crate::diff(&before, &after);
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(revert(&after, &delta), before);
+
+        // `m.10` is a key, so it is removed before its parent rather than after it.
+        let mut nested = Delta::new();
+        nested.insert("m".to_string(), Change::Remove(json!({})));
+        nested.insert("m.10".to_string(), Change::Remove(json!("c")));
+        assert_eq!(try_apply(&before, &nested), Ok(json!({})));
+
+        // A numeric `Add` on an object sets the key instead of inserting an element.
+        let mut add = Delta::new();
+        add.insert("m.1".to_string(), Change::Add(json!("B")));
+        assert_eq!(
+            apply(&before, &add),
+            json!({"m": {"0": "a", "1": "B", "10": "c"}})
+        );
+        let mut list = Delta::new();
+        list.insert("l.1".to_string(), Change::Add(json!("B")));
+        assert_eq!(
+            apply(&json!({"l": ["a", "b"]}), &list),
+            json!({"l": ["a", "B", "b"]})
+        );
+    }
+
+    #[test]
+    fn dash_segment_appends_and_pops() {
+        let mut append = Delta::new();
+        append.insert("items.-".to_string(), Change::Add(json!(3)));
+        let before = json!({"items": [1, 2]});
+        let after = apply(&before, &append);
+        assert_eq!(after, json!({"items": [1, 2, 3]}));
+        assert_eq!(revert(&after, &append), before);
+        assert_eq!(try_apply(&before, &append), Ok(after));
+
+        let mut pop = Delta::new();
+        pop.insert("items.-".to_string(), Change::Remove(json!(2)));
+        assert_eq!(apply(&before, &pop), json!({"items": [1]}));
+        assert_eq!(
+            try_apply(&json!({"items": []}), &pop),
+            Err(not_found("items.-"))
+        );
+    }
+
+    #[test]
+    fn dash_segment_creates_missing_arrays() {
+        let mut append = Delta::new();
+        append.insert("log.entries.-".to_string(), Change::Add(json!("first")));
+        assert_eq!(
+            apply(&json!({}), &append),
+            json!({"log": {"entries": ["first"]}})
+        );
+        assert_eq!(
+            try_apply(&json!({}), &append),
+            Err(not_found("log.entries.-"))
+        );
+        // On objects `-` is an ordinary key.
+        assert_eq!(
+            apply(&json!({"log": {}}), &append),
+            json!({"log": {"entries": ["first"]}})
+        );
+        let mut key = Delta::new();
+        key.insert("obj.-".to_string(), Change::Add(json!(1)));
+        assert_eq!(apply(&json!({"obj": {}}), &key), json!({"obj": {"-": 1}}));
+    }
+
+    #[test]
+    fn root_changes_replace_the_whole_document() {
+        for (before, after) in [
+            (json!(1), json!([1, 2])),
+            (json!([true]), json!({"a": 1})),
+            (json!(null), json!("x")),
+        ] {
+            let delta = crate::diff(&before, &after);
+            assert_eq!(apply(&before, &delta), after);
+            assert_eq!(revert(&after, &delta), before);
+        }
+    }
+
+    #[test]
+    fn try_apply_rejects_missing_targets() {
+        let before = json!({"a": 1});
+
+        let mut missing_parent = Delta::new();
+        missing_parent.insert("x.y".to_string(), Change::Add(json!(1)));
+        assert_eq!(try_apply(&before, &missing_parent), Err(not_found("x.y")));
+
+        let mut missing_remove = Delta::new();
+        missing_remove.insert("b".to_string(), Change::Remove(json!(1)));
+        assert_eq!(try_apply(&before, &missing_remove), Err(not_found("b")));
+
+        let mut missing_modify = Delta::new();
+        missing_modify.insert("b".to_string(), modify(json!(1), json!(2)));
+        assert_eq!(try_apply(&before, &missing_modify), Err(not_found("b")));
+    }
+
+    #[test]
+    fn try_apply_reports_out_of_range_indices() {
+        let before = json!({"items": [1, 2]});
+        let mut delta = Delta::new();
+        delta.insert("items.5".to_string(), Change::Remove(json!(0)));
+        assert_eq!(
+            try_apply(&before, &delta),
+            Err(JsonDiffError::IndexOutOfBounds {
+                path: "items.5".to_string(),
+                index: 5,
+                len: 2,
+            })
+        );
+        assert_eq!(apply(&before, &delta), before);
+    }
+
+    #[test]
+    fn negative_indices_count_from_the_end() {
+        let before = json!({"items": [1, 2, 3], "rows": [{"n": 1}, {"n": 2}]});
+
+        let mut set_last = Delta::new();
+        set_last.insert("items.-1".to_string(), modify(json!(3), json!(30)));
+        set_last.insert("rows.-2.n".to_string(), modify(json!(1), json!(10)));
+        let expected = json!({"items": [1, 2, 30], "rows": [{"n": 10}, {"n": 2}]});
+        assert_eq!(apply(&before, &set_last), expected);
+        assert_eq!(try_apply(&before, &set_last), Ok(expected));
+
+        let mut remove_last = Delta::new();
+        remove_last.insert("items.-1".to_string(), Change::Remove(json!(3)));
+        remove_last.insert("items.0".to_string(), Change::Remove(json!(1)));
+        let expected = json!({"items": [2], "rows": before["rows"]});
+        assert_eq!(apply(&before, &remove_last), expected);
+        assert_eq!(try_apply(&before, &remove_last), Ok(expected));
+
+        let mut insert = Delta::new();
+        insert.insert("items.-1".to_string(), Change::Add(json!(2.5)));
+        assert_eq!(apply(&before, &insert)["items"], json!([1, 2, 2.5, 3]));
+
+        // Removals from the end apply furthest first, so each `-N` means the same element.
+        let mut remove_tail = Delta::new();
+        remove_tail.insert("items.-1".to_string(), Change::Remove(json!(3)));
+        remove_tail.insert("items.-2".to_string(), Change::Remove(json!(2)));
+        let expected = json!({"items": [1], "rows": before["rows"]});
+        assert_eq!(apply(&before, &remove_tail), expected);
+        assert_eq!(try_apply(&before, &remove_tail), Ok(expected));
+    }
+
+    #[test]
+    fn negative_indices_out_of_range_are_errors_strictly() {
+        let before = json!({"items": [1, 2]});
+        for change in [Change::Remove(json!(0)), modify(json!(0), json!(1))] {
+            let mut delta = Delta::new();
+            delta.insert("items.-3".to_string(), change);
+            assert_eq!(
+                try_apply(&before, &delta),
+                Err(JsonDiffError::IndexOutOfBounds {
+                    path: "items.-3".to_string(),
+                    index: 3,
+                    len: 2,
+                })
+            );
+            assert_eq!(apply(&before, &delta), before);
+        }
+        // `-0` is not an index.
+        let mut delta = Delta::new();
+        delta.insert("items.-0".to_string(), Change::Remove(json!(1)));
+        assert_eq!(apply(&before, &delta), before);
+    }
+
+    #[test]
+    fn empty_segments_are_keys_leniently_and_errors_strictly() {
+        let before = json!({"a": {"": {"b": 1}}});
+        for path in ["a..b", ".a", "a."] {
+            let mut delta = Delta::new();
+            delta.insert(path.to_string(), Change::Add(json!(2)));
+            assert_eq!(
+                try_apply(&before, &delta),
+                Err(JsonDiffError::InvalidPath {
+                    path: path.to_string()
+                })
+            );
+        }
+
+        let mut delta = Delta::new();
+        delta.insert("a..b".to_string(), modify(json!(1), json!(2)));
+        assert_eq!(apply(&before, &delta), json!({"a": {"": {"b": 2}}}));
+
+        let mut trailing = Delta::new();
+        trailing.insert("x.".to_string(), Change::Add(json!(true)));
+        assert_eq!(apply(&json!({}), &trailing), json!({"x": {"": true}}));
+    }
+
+    #[test]
+    fn add_must_not_exist_rejects_occupied_keys() {
+        let options = ApplyOptions {
+            add_must_not_exist: true,
+            ..Default::default()
+        };
+        let before = json!({"user": {"name": "Ann"}, "tags": ["a"]});
+
+        let mut free = Delta::new();
+        free.insert("user.email".to_string(), Change::Add(json!("a@x")));
+        free.insert("tags.0".to_string(), Change::Add(json!("z")));
+        assert_eq!(
+            try_apply_with(&before, &free, &options),
+            Ok(json!({"user": {"name": "Ann", "email": "a@x"}, "tags": ["z", "a"]}))
+        );
+
+        let mut occupied = Delta::new();
+        occupied.insert("user.name".to_string(), Change::Add(json!("Bo")));
+        assert_eq!(
+            try_apply_with(&before, &occupied, &options),
+            Err(JsonDiffError::PathExists {
+                path: "user.name".to_string()
+            })
+        );
+        // The default keeps RFC 6902's replace semantics.
+        assert_eq!(
+            try_apply(&before, &occupied),
+            Ok(json!({"user": {"name": "Bo"}, "tags": ["a"]}))
+        );
+    }
+
+    #[test]
+    fn max_apply_depth_rejects_deep_paths() {
+        let options = ApplyOptions {
+            max_apply_depth: Some(3),
+            ..Default::default()
+        };
+        let mut shallow = Delta::new();
+        shallow.insert("a.b.c".to_string(), Change::Add(json!(1)));
+        assert_eq!(
+            try_apply_with(&json!({"a": {"b": {}}}), &shallow, &options),
+            Ok(json!({"a": {"b": {"c": 1}}}))
+        );
+
+        let deep_path = vec!["a"; 1_000].join(".");
+        let mut deep = Delta::new();
+        deep.insert(deep_path.clone(), Change::Add(json!(1)));
+        assert_eq!(
+            try_apply_with(&json!({}), &deep, &options),
+            Err(JsonDiffError::PathTooDeep {
+                path: deep_path,
+                max_depth: 3
+            })
+        );
+    }
+
+    #[test]
+    fn max_apply_depth_caps_created_intermediates() {
+        let options = ApplyOptions {
+            max_apply_depth: Some(3),
+            ..Default::default()
+        };
+        let mut shallow = Delta::new();
+        shallow.insert("a.b.c".to_string(), Change::Add(json!(1)));
+        // Strict application refuses the missing parents anyway; lenient creates them.
+        assert!(try_apply(&json!({}), &shallow).is_err());
+        assert_eq!(
+            apply_with(&json!({}), &shallow, &options),
+            Ok(json!({"a": {"b": {"c": 1}}}))
+        );
+
+        let deep_path = vec!["a"; 1_000].join(".");
+        let mut deep = Delta::new();
+        deep.insert(deep_path.clone(), Change::Add(json!(1)));
+        assert_eq!(
+            apply_with(&json!({}), &deep, &options),
+            Err(JsonDiffError::PathTooDeep {
+                path: deep_path.clone(),
+                max_depth: 3
+            })
+        );
+        assert_eq!(
+            apply_with(&json!({}), &deep, &ApplyOptions::default()),
+            Ok(apply(&json!({}), &deep))
+        );
+    }
+
+    #[test]
+    fn apply_in_place_edits_target() {
+        let mut value = json!({"a": 1});
+        let mut delta = Delta::new();
+        delta.insert("a".to_string(), modify(json!(1), json!(5)));
+        delta.insert("n.m".to_string(), Change::Add(json!("x")));
+
+        apply_in_place(&mut value, &delta);
+        assert_eq!(value, json!({"a": 5, "n": {"m": "x"}}));
+    }
+
+    #[test]
+    fn transactional_apply_commits_on_success() {
+        let mut value = json!({"a": 1, "b": 2});
+        let mut delta = Delta::new();
+        delta.insert("a".to_string(), modify(json!(1), json!(10)));
+        delta.insert("b".to_string(), Change::Remove(json!(2)));
+
+        assert_eq!(apply_in_place_txn(&mut value, &delta), Ok(()));
+        assert_eq!(value, json!({"a": 10}));
+    }
+
+    #[test]
+    fn transactional_apply_rolls_back_on_failure() {
+        let original = json!({"a": 1, "b": {"c": 2}});
+        let mut value = original.clone();
+        let mut delta = Delta::new();
+        // "a" and "b.c" apply first and succeed; "z.w" then fails.
+        delta.insert("a".to_string(), modify(json!(1), json!(10)));
+        delta.insert("b.c".to_string(), Change::Remove(json!(2)));
+        delta.insert("z.w".to_string(), modify(json!(0), json!(1)));
+
+        assert_eq!(
+            apply_in_place_txn(&mut value, &delta),
+            Err(not_found("z.w"))
+        );
+        assert_eq!(value, original);
+    }
+
+    fn arb_value() -> impl Strategy<Value = Value> {
+        arb_value_with_keys(arb_segment().boxed())
+    }
+
+    fn arb_value_with_keys(keys: BoxedStrategy<String>) -> impl Strategy<Value = Value> {
+        let leaf = prop_oneof![
+            Just(Value::Null),
+            any::<bool>().prop_map(Value::from),
+            any::<i64>().prop_map(Value::from),
+            (-1e6..1e6f64).prop_map(Value::from),
+            "[a-z]{0,3}".prop_map(Value::from),
+        ];
+        leaf.prop_recursive(4, 32, 4, move |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..4).prop_map(Value::from),
+                prop::collection::btree_map(keys.clone(), inner, 0..4)
+                    .prop_map(|map| Value::Object(map.into_iter().collect())),
+            ]
+        })
+    }
+
+    /// Object keys for round-trip tests. Keys containing the `.` separator, or empty
+    /// keys (which collide with their parent's path), cannot be addressed by a dot-path,
+    /// so they are left out.
+    fn arb_key() -> BoxedStrategy<String> {
+        prop_oneof!["[a-c]{1,2}", "[0-2]", Just("+1".to_string())].boxed()
+    }
+
+    /// Segments chosen to hit edge cases: empty keys, indices in and out of range,
+    /// insertion markers, and things that look like indices but are not.
+    fn arb_segment() -> impl Strategy<Value = String> {
+        prop::sample::select(vec![
+            "", "a", "b", "0", "1", "+0", "+7", "99", "-", "-1", "x y",
+        ])
+        .prop_map(str::to_string)
+    }
+
+    fn arb_path() -> impl Strategy<Value = String> {
+        prop::collection::vec(arb_segment(), 0..4).prop_map(|segments| segments.join("."))
+    }
+
+    fn arb_change() -> impl Strategy<Value = Change> {
+        prop_oneof![
+            arb_value().prop_map(Change::Add),
+            arb_value().prop_map(Change::Remove),
+            (arb_value(), arb_value()).prop_map(|(old, new)| Change::Modify { old, new }),
+            arb_value().prop_map(Change::Set),
+            arb_value().prop_map(Change::Unchanged),
+            (arb_path(), arb_path()).prop_map(|(from, to)| Change::Move { from, to }),
+            (arb_path(), arb_path()).prop_map(|(from, to)| Change::Copy { from, to }),
+            (arb_path(), arb_path()).prop_map(|(from, to)| Change::Rename { from, to }),
+        ]
+    }
+
+    fn arb_delta() -> impl Strategy<Value = Delta> {
+        prop::collection::vec((arb_path(), arb_change()), 0..6)
+            .prop_map(|entries| entries.into_iter().collect())
+    }
+
+    proptest! {
+        #[test]
+        fn arbitrary_deltas_never_panic(value in arb_value(), delta in arb_delta()) {
+            let _ = apply(&value, &delta);
+            let _ = apply_checked(&value, &delta);
+            let _ = try_apply(&value, &delta);
+            let _ = revert(&value, &delta);
+            let _ = try_revert(&value, &delta);
+
+            let mut in_place = value.clone();
+            apply_in_place(&mut in_place, &delta);
+            let mut txn = value.clone();
+            if apply_in_place_txn(&mut txn, &delta).is_err() {
+                prop_assert_eq!(txn, value);
+            }
+        }
+
+        #[test]
+        fn diff_then_apply_round_trips(
+            a in arb_value_with_keys(arb_key()),
+            b in arb_value_with_keys(arb_key()),
+        ) {
+            let delta = crate::diff(&a, &b);
+            prop_assert_eq!(&apply(&a, &delta), &b);
+            prop_assert_eq!(&revert(&b, &delta), &a);
+            prop_assert_eq!(&try_apply(&a, &delta), &Ok(b.clone()));
+            prop_assert_eq!(&try_revert(&b, &delta), &Ok(a.clone()));
+        }
+
+        #[test]
+        fn undo_restores_the_original(value in arb_value(), delta in arb_delta()) {
+            let (result, undo) = apply_with_undo(&value, &delta);
+            prop_assert_eq!(apply(&result, &undo), value);
+        }
+    }
+}
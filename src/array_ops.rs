@@ -0,0 +1,288 @@
+//! Ordered edit scripts for arrays, carried by `Change::ArrayOps`.
+
+use crate::{Change, DiffOptions};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// One step of a [`Change::ArrayOps`] script.
+///
+/// Steps are replayed in order, and each index refers to the array as the preceding steps
+/// left it, so an insertion shifts the elements after it for every later step. This is
+/// what makes a script unambiguous where index-keyed paths are not: "insert at 0, then
+/// update 1" updates the element that was at 0 before the insertion.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ArrayOp {
+    /// Insert `value` before the element at `index` (at the length, to append).
+    Insert { index: usize, value: Value },
+    /// Delete the element at `index`, which held `value`.
+    Delete { index: usize, value: Value },
+    /// Apply `change` to the element at `index`, as if the element were the whole document
+    /// (paths inside `change` are relative to the element).
+    Update { index: usize, change: Box<Change> },
+}
+
+impl ArrayOp {
+    /// The index this step addresses.
+    pub fn index(&self) -> usize {
+        match self {
+            ArrayOp::Insert { index, .. }
+            | ArrayOp::Delete { index, .. }
+            | ArrayOp::Update { index, .. } => *index,
+        }
+    }
+
+    /// The step that undoes this one: inserts and deletes swap, and an update's change is
+    /// inverted (see [`Change::inverse`]).
+    pub fn inverse(self) -> Self {
+        match self {
+            ArrayOp::Insert { index, value } => ArrayOp::Delete { index, value },
+            ArrayOp::Delete { index, value } => ArrayOp::Insert { index, value },
+            ArrayOp::Update { index, change } => ArrayOp::Update {
+                index,
+                change: Box::new(change.inverse()),
+            },
+        }
+    }
+}
+
+/// The script that undoes `ops`: each step inverted, in reverse order.
+pub(crate) fn invert_ops(ops: Vec<ArrayOp>) -> Vec<ArrayOp> {
+    ops.into_iter().rev().map(ArrayOp::inverse).collect()
+}
+
+/// Diff `a` into `b` as an edit script, keeping a longest common subsequence of equivalent
+/// elements in place.
+///
+/// Between two kept elements, removed and inserted elements are paired up by position as
+/// `Update`s of the whole element; the surplus on either side becomes `Delete`s or
+/// `Insert`s. Takes time and memory proportional to `a.len() * b.len()`.
+pub(crate) fn diff_ops(options: &DiffOptions, a: &[Value], b: &[Value]) -> Vec<ArrayOp> {
+    let (n, m) = (a.len(), b.len());
+    // `kept[i][j]`: length of the longest common subsequence of `a[i..]` and `b[j..]`.
+    let mut kept = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            kept[i][j] = if options.equivalent(&a[i], &b[j]) {
+                kept[i + 1][j + 1] + 1
+            } else {
+                kept[i + 1][j].max(kept[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j, mut index) = (0, 0, 0);
+    while i < n || j < m {
+        if i < n && j < m && options.equivalent(&a[i], &b[j]) {
+            i += 1;
+            j += 1;
+            index += 1;
+            continue;
+        }
+        let (gap_a, gap_b) = (i, j);
+        while i < n || j < m {
+            if i < n && j < m && options.equivalent(&a[i], &b[j]) {
+                break;
+            }
+            if j == m || (i < n && kept[i + 1][j] >= kept[i][j + 1]) {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        let (removed, inserted) = (&a[gap_a..i], &b[gap_b..j]);
+        let paired = removed.len().min(inserted.len());
+        for (old, new) in removed.iter().zip(inserted) {
+            if !options.equivalent(old, new)
+                && let Some(change) = options.capture_change(old, new)
+            {
+                ops.push(ArrayOp::Update {
+                    index,
+                    change: Box::new(change),
+                });
+            }
+            index += 1;
+        }
+        for old in &removed[paired..] {
+            ops.push(ArrayOp::Delete {
+                index,
+                value: options.capture(old),
+            });
+        }
+        for new in &inserted[paired..] {
+            ops.push(ArrayOp::Insert {
+                index,
+                value: options.capture(new),
+            });
+            index += 1;
+        }
+    }
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArrayStrategy, Delta, apply, diff_with, revert, try_apply, try_revert};
+    use serde_json::json;
+
+    fn ops_options() -> DiffOptions {
+        DiffOptions {
+            array_strategy: ArrayStrategy::Ops,
+            ..Default::default()
+        }
+    }
+
+    fn script(path: &str, ops: Vec<ArrayOp>) -> Delta {
+        let mut delta = Delta::new();
+        delta.insert(path.to_string(), Change::ArrayOps(ops));
+        delta
+    }
+
+    #[test]
+    fn steps_are_replayed_in_order() {
+        let before = json!({"list": ["a", "b"]});
+        // Under index-keyed paths, `list.+0` and `list.1` leave it unclear whether the
+        // update targets "b" or the element that ends up at 1 after the insertion.
+        let delta = script(
+            "list",
+            vec![
+                ArrayOp::Insert {
+                    index: 0,
+                    value: json!("z"),
+                },
+                ArrayOp::Update {
+                    index: 1,
+                    change: Box::new(Change::Modify {
+                        old: json!("a"),
+                        new: json!("A"),
+                    }),
+                },
+                ArrayOp::Insert {
+                    index: 3,
+                    value: json!("end"),
+                },
+                ArrayOp::Delete {
+                    index: 2,
+                    value: json!("b"),
+                },
+            ],
+        );
+        let after = json!({"list": ["z", "A", "end"]});
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(try_apply(&before, &delta), Ok(after.clone()));
+        assert_eq!(revert(&after, &delta), before);
+        assert_eq!(try_revert(&after, &delta), Ok(before));
+    }
+
+    #[test]
+    fn updates_apply_nested_changes_to_the_element() {
+        let before = json!([[1, 2], {"a": 1}, 10]);
+        let delta = script(
+            "",
+            vec![
+                ArrayOp::Update {
+                    index: 0,
+                    change: Box::new(Change::ArrayOps(vec![ArrayOp::Delete {
+                        index: 0,
+                        value: json!(1),
+                    }])),
+                },
+                ArrayOp::Update {
+                    index: 1,
+                    change: Box::new(Change::Rename {
+                        from: "a".to_string(),
+                        to: "b".to_string(),
+                    }),
+                },
+                ArrayOp::Update {
+                    index: 2,
+                    change: Box::new(Change::NumberDelta { by: 5.into() }),
+                },
+            ],
+        );
+        let after = json!([[2], {"b": 1}, 15]);
+        assert_eq!(try_apply(&before, &delta), Ok(after.clone()));
+        assert_eq!(try_revert(&after, &delta), Ok(before));
+    }
+
+    #[test]
+    fn out_of_range_steps_fail_strictly() {
+        let before = json!({"list": [1]});
+        let delta = script(
+            "list",
+            vec![ArrayOp::Delete {
+                index: 1,
+                value: json!(2),
+            }],
+        );
+        assert!(try_apply(&before, &delta).is_err());
+        assert_eq!(apply(&before, &delta), before);
+        assert!(try_apply(&json!({"list": {}}), &delta).is_err());
+    }
+
+    #[test]
+    fn diff_produces_minimal_scripts() {
+        let before = json!({"list": ["a", "b", "c", "d"], "same": [1]});
+        let after = json!({"list": ["x", "a", "c", "D", "e"], "same": [1]});
+        let delta = diff_with(&before, &after, &ops_options());
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["list"]);
+        assert_eq!(
+            delta["list"],
+            Change::ArrayOps(vec![
+                ArrayOp::Insert {
+                    index: 0,
+                    value: json!("x"),
+                },
+                ArrayOp::Delete {
+                    index: 2,
+                    value: json!("b"),
+                },
+                ArrayOp::Update {
+                    index: 3,
+                    change: Box::new(Change::Modify {
+                        old: json!("d"),
+                        new: json!("D"),
+                    }),
+                },
+                ArrayOp::Insert {
+                    index: 4,
+                    value: json!("e"),
+                },
+            ])
+        );
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(revert(&after, &delta), before);
+        assert_eq!(delta.invert(&after).apply(&after), before);
+    }
+
+    #[test]
+    fn merged_scripts_are_concatenated() {
+        let (a, b, c) = (json!([1, 2]), json!([0, 1, 2]), json!([0, 1, 3]));
+        let mut delta = diff_with(&a, &b, &ops_options());
+        delta.merge(diff_with(&b, &c, &ops_options())).unwrap();
+        assert!(matches!(&delta[""], Change::ArrayOps(ops) if ops.len() == 2));
+        assert_eq!(apply(&a, &delta), c);
+        assert_eq!(revert(&c, &delta), a);
+    }
+
+    #[test]
+    fn diffed_scripts_round_trip() {
+        let samples = [
+            json!([]),
+            json!([1]),
+            json!([1, 2, 3]),
+            json!([3, 2, 1]),
+            json!([1, 1, 2, 2]),
+            json!([{"a": 1}, [2], null, "s"]),
+            json!([2, 3, 4, 5, 6]),
+        ];
+        for before in &samples {
+            for after in &samples {
+                let delta = diff_with(before, after, &ops_options());
+                assert_eq!(apply(before, &delta), *after, "{before} -> {after}");
+                assert_eq!(revert(after, &delta), *before, "{before} <- {after}");
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Diffing and applying many documents in one call.
+
+use crate::{Delta, JsonDiffError, apply, diff};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use serde_json::Value;
+
+/// Diff each `(before, after)` pair, returning the deltas in the same order.
+///
+/// With the `rayon` feature the pairs are diffed in parallel.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{apply_batch, diff_batch};
+///
+/// let pairs = [(json!({"a": 1}), json!({"a": 2})), (json!([1]), json!([1]))];
+/// let deltas = diff_batch(&pairs);
+/// assert!(deltas[1].is_empty());
+///
+/// let bases: Vec<_> = pairs.iter().map(|(before, _)| before.clone()).collect();
+/// let applied = apply_batch(&bases, &deltas).unwrap();
+/// assert_eq!(applied[0], json!({"a": 2}));
+/// ```
+pub fn diff_batch(pairs: &[(Value, Value)]) -> Vec<Delta> {
+    #[cfg(feature = "rayon")]
+    let pairs = pairs.par_iter();
+    #[cfg(not(feature = "rayon"))]
+    let pairs = pairs.iter();
+    pairs.map(|(before, after)| diff(before, after)).collect()
+}
+
+/// Apply `deltas[i]` to `bases[i]` for every `i`, as `apply` does, returning the results
+/// in order.
+///
+/// Fails with `JsonDiffError::LengthMismatch` when the slices differ in length. With the
+/// `rayon` feature the documents are processed in parallel.
+pub fn apply_batch(bases: &[Value], deltas: &[Delta]) -> Result<Vec<Value>, JsonDiffError> {
+    if bases.len() != deltas.len() {
+        return Err(JsonDiffError::LengthMismatch {
+            bases: bases.len(),
+            deltas: deltas.len(),
+        });
+    }
+    #[cfg(feature = "rayon")]
+    let pairs = bases.par_iter().zip(deltas);
+    #[cfg(not(feature = "rayon"))]
+    let pairs = bases.iter().zip(deltas);
+    Ok(pairs.map(|(base, delta)| apply(base, delta)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::revert;
+    use serde_json::json;
+
+    #[test]
+    fn batch_deltas_round_trip() {
+        let pairs = vec![
+            (json!({"name": "a"}), json!({"name": "b", "n": 1})),
+            (json!([1, 2]), json!([2])),
+            (json!({"x": {"y": true}}), json!({"x": {"y": true}})),
+        ];
+        let deltas = diff_batch(&pairs);
+        assert_eq!(deltas.len(), pairs.len());
+
+        let (bases, afters): (Vec<Value>, Vec<Value>) = pairs.iter().cloned().unzip();
+        assert_eq!(apply_batch(&bases, &deltas), Ok(afters.clone()));
+        for ((after, delta), before) in afters.iter().zip(&deltas).zip(&bases) {
+            assert_eq!(&revert(after, delta), before);
+        }
+    }
+
+    #[test]
+    fn apply_batch_rejects_length_mismatch() {
+        assert_eq!(
+            apply_batch(&[json!(1), json!(2)], &[Delta::new()]),
+            Err(JsonDiffError::LengthMismatch {
+                bases: 2,
+                deltas: 1
+            })
+        );
+    }
+}
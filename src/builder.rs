@@ -0,0 +1,126 @@
+//! Fluent construction of deltas.
+
+use crate::{Change, Delta};
+use serde_json::{Number, Value};
+
+/// Builds a `Delta` with chainable calls instead of repeated `insert`s.
+///
+/// Later calls for the same path replace earlier ones.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{apply, DeltaBuilder};
+///
+/// let delta = DeltaBuilder::new()
+///     .modify("status", json!("pending"), json!("shipped"))
+///     .add("tracking.code", json!("BR123"))
+///     .remove("draft", json!(true))
+///     .build();
+///
+/// let before = json!({"status": "pending", "draft": true});
+/// assert_eq!(
+///     apply(&before, &delta),
+///     json!({"status": "shipped", "tracking": {"code": "BR123"}})
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DeltaBuilder {
+    delta: Delta,
+}
+
+impl DeltaBuilder {
+    /// Start an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that `value` was added at `path`.
+    pub fn add(self, path: impl Into<String>, value: Value) -> Self {
+        self.change(path, Change::Add(value))
+    }
+
+    /// Record that `value` was removed from `path`.
+    pub fn remove(self, path: impl Into<String>, value: Value) -> Self {
+        self.change(path, Change::Remove(value))
+    }
+
+    /// Record that the value at `path` changed from `old` to `new`.
+    pub fn modify(self, path: impl Into<String>, old: Value, new: Value) -> Self {
+        self.change(path, Change::Modify { old, new })
+    }
+
+    /// Record that the number at `path` was increased by `by` (a `Change::NumberDelta`).
+    pub fn increment(self, path: impl Into<String>, by: impl Into<Number>) -> Self {
+        self.change(path, Change::NumberDelta { by: by.into() })
+    }
+
+    /// Record an arbitrary `change` at `path`.
+    pub fn change(mut self, path: impl Into<String>, change: Change) -> Self {
+        self.delta.insert(path.into(), change);
+        self
+    }
+
+    /// Finish building and return the `Delta`.
+    pub fn build(self) -> Delta {
+        self.delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{apply, revert};
+    use serde_json::json;
+
+    #[test]
+    fn builds_multi_op_delta() {
+        let delta = DeltaBuilder::new()
+            .modify("user.age", json!(30), json!(31))
+            .add("user.email", json!("ann@example.com"))
+            .remove("legacy", json!({"id": 7}))
+            .change(
+                "owner",
+                Change::Move {
+                    from: "user".to_string(),
+                    to: "owner".to_string(),
+                },
+            )
+            .build();
+
+        let mut expected = Delta::new();
+        expected.insert(
+            "user.age".to_string(),
+            Change::Modify {
+                old: json!(30),
+                new: json!(31),
+            },
+        );
+        expected.insert(
+            "user.email".to_string(),
+            Change::Add(json!("ann@example.com")),
+        );
+        expected.insert("legacy".to_string(), Change::Remove(json!({"id": 7})));
+        expected.insert(
+            "owner".to_string(),
+            Change::Move {
+                from: "user".to_string(),
+                to: "owner".to_string(),
+            },
+        );
+        assert_eq!(delta, expected);
+    }
+
+    #[test]
+    fn built_delta_applies_and_reverts() {
+        let before = json!({"a": 1, "b": {"c": "x"}});
+        let after = json!({"a": 2, "b": {"d": "y"}});
+        let delta = DeltaBuilder::new()
+            .modify("a", json!(1), json!(2))
+            .remove("b.c", json!("x"))
+            .add("b.d", json!("y"))
+            .build();
+
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(revert(&after, &delta), before);
+    }
+}
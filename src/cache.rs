@@ -0,0 +1,199 @@
+//! Diffing many candidates against one base document.
+
+use crate::hash::{hash_entry, hash_value, object_hash};
+use crate::path::join_path;
+use crate::{Change, Delta, DiffOptions, DotCodec, compare};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// A base document with precomputed subtree hashes, for repeated diffs against it.
+///
+/// `DiffCache` hashes the base once. Each [`DiffCache::diff`] then hashes the candidate
+/// in a single pass and walks both documents together. An object whose hash differs from
+/// the base's is descended into directly, without first comparing the two in full. A
+/// subtree whose hash matches is compared once to confirm it is equal and then skipped,
+/// so a hash collision can never hide a change. The output equals
+/// `diff(base, candidate)`.
+///
+/// The cache owns a copy of the base, so it can never go stale; to diff against a new
+/// base, call [`DiffCache::rebuild`] (or create a new cache). `DiffCache` is `Send` and
+/// `Sync`, so one cache can serve many threads through a shared reference.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff, DiffCache};
+///
+/// let base = json!({"config": {"retries": 3, "hosts": ["a", "b"]}, "name": "svc"});
+/// let cache = DiffCache::new(&base);
+/// for candidate in [json!({"config": {"retries": 4, "hosts": ["a", "b"]}, "name": "svc"})] {
+///     assert_eq!(cache.diff(&candidate), diff(&base, &candidate));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DiffCache {
+    base: Value,
+    hashes: HashMap<String, u64>,
+}
+
+impl DiffCache {
+    /// Hash every object subtree of `base`.
+    pub fn new(base: &Value) -> Self {
+        let mut hashes = HashMap::new();
+        hash_base(base, String::new(), &mut hashes);
+        DiffCache {
+            base: base.clone(),
+            hashes,
+        }
+    }
+
+    /// Replace the base document, rehashing it.
+    pub fn rebuild(&mut self, base: &Value) {
+        *self = DiffCache::new(base);
+    }
+
+    /// The cached base document.
+    pub fn base(&self) -> &Value {
+        &self.base
+    }
+
+    /// Compute `diff(base, candidate)` using the cached hashes.
+    pub fn diff(&self, candidate: &Value) -> Delta {
+        let mut candidate_hashes = HashMap::new();
+        hash_candidate(candidate, &mut candidate_hashes);
+        let mut delta = Delta::new();
+        self.compare(
+            &mut delta,
+            &candidate_hashes,
+            String::new(),
+            &self.base,
+            candidate,
+        );
+        delta
+    }
+
+    fn compare(
+        &self,
+        delta: &mut Delta,
+        candidate_hashes: &HashMap<*const Value, u64>,
+        path: String,
+        a: &Value,
+        b: &Value,
+    ) {
+        let base_hash = self.hashes.get(&path);
+        let candidate_hash = candidate_hashes.get(&(b as *const Value));
+        let differs = matches!((base_hash, candidate_hash), (Some(x), Some(y)) if x != y);
+        match (a, b) {
+            // Equal hashes only suggest equality, so objects are confirmed before being
+            // skipped.
+            (Value::Object(obj_a), Value::Object(obj_b)) if differs || a != b => {
+                for (key, va) in obj_a {
+                    let child = join_path(&path, key);
+                    match obj_b.get(key) {
+                        Some(vb) => self.compare(delta, candidate_hashes, child, va, vb),
+                        None => {
+                            delta.insert(child, Change::Remove(va.clone()));
+                        }
+                    }
+                }
+                for (key, vb) in obj_b {
+                    if !obj_a.contains_key(key) {
+                        delta.insert(join_path(&path, key), Change::Add(vb.clone()));
+                    }
+                }
+            }
+            (Value::Object(_), Value::Object(_)) => {}
+            _ => compare(delta, &DiffOptions::default(), &DotCodec, path, a, b),
+        }
+    }
+}
+
+/// Record the hash of every object in `value` by path, returning the hash of `value`.
+fn hash_base(value: &Value, path: String, hashes: &mut HashMap<String, u64>) -> u64 {
+    let hash = match value {
+        Value::Object(obj) => combine_object(
+            obj.iter()
+                .map(|(key, child)| (key, hash_base(child, join_path(&path, key), hashes))),
+        ),
+        _ => hash_value(value),
+    };
+    if value.is_object() {
+        hashes.insert(path, hash);
+    }
+    hash
+}
+
+/// Record the hash of every object in `value` by address, returning the hash of `value`.
+fn hash_candidate(value: &Value, hashes: &mut HashMap<*const Value, u64>) -> u64 {
+    let hash = match value {
+        Value::Object(obj) => combine_object(
+            obj.iter()
+                .map(|(key, child)| (key, hash_candidate(child, hashes))),
+        ),
+        _ => hash_value(value),
+    };
+    if value.is_object() {
+        hashes.insert(value as *const Value, hash);
+    }
+    hash
+}
+
+/// Object hash from its entries' hashes, agreeing with `hash_value`.
+fn combine_object<'a>(entries: impl Iterator<Item = (&'a String, u64)>) -> u64 {
+    let sum = entries.fold(0u64, |sum, (key, child)| {
+        sum.wrapping_add(hash_entry(key, child))
+    });
+    object_hash(sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff;
+    use serde_json::json;
+
+    #[test]
+    fn cached_diff_matches_plain_diff() {
+        let base = json!({
+            "service": {"name": "api", "ports": [80, 443], "env": {"A": "1", "B": "2"}},
+            "replicas": 3,
+            "labels": {"team": "core"}
+        });
+        let cache = DiffCache::new(&base);
+        let candidates = [
+            base.clone(),
+            json!({"service": {"name": "api", "ports": [80], "env": {"A": "1", "B": "3"}}, "replicas": 3, "labels": {"team": "core"}}),
+            json!({"service": "gone", "replicas": 4}),
+            json!([1, 2]),
+            json!({"service": {"name": "api", "ports": [80, 443], "env": {"A": "1", "B": "2", "C": "x"}}, "replicas": 3, "labels": {}}),
+        ];
+        for candidate in &candidates {
+            assert_eq!(cache.diff(candidate), diff(&base, candidate), "{candidate}");
+        }
+        assert_eq!(cache.base(), &base);
+    }
+
+    #[test]
+    fn hash_matches_are_confirmed() {
+        let base = json!({"a": {"b": 1}});
+        let candidate = json!({"a": {"b": 2}});
+        let mut cache = DiffCache::new(&base);
+        // Pretend the base hashes like the candidate, as a collision would.
+        let mut candidate_hashes = HashMap::new();
+        hash_candidate(&candidate, &mut candidate_hashes);
+        cache.hashes.insert(
+            String::new(),
+            candidate_hashes[&(&candidate as *const Value)],
+        );
+        cache
+            .hashes
+            .insert("a".to_string(), hash_value(&candidate["a"]));
+        assert_eq!(cache.diff(&candidate), diff(&base, &candidate));
+    }
+
+    #[test]
+    fn rebuild_switches_the_base() {
+        let mut cache = DiffCache::new(&json!({"a": 1}));
+        cache.rebuild(&json!({"a": 2}));
+        assert!(cache.diff(&json!({"a": 2})).is_empty());
+    }
+}
@@ -0,0 +1,96 @@
+//! Classifying changes by shape for review tooling.
+
+use crate::Change;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::mem::discriminant;
+
+/// The kind of a change, derived from the shape of the values it carries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChangeCategory {
+    /// An object or array was added, removed or replaced as a whole, or a value was moved,
+    /// copied or renamed.
+    Structural,
+    /// A leaf value was added, removed or changed without changing its type.
+    Scalar,
+    /// The old and new values have different JSON types (e.g. string to number).
+    TypeChange,
+}
+
+/// Classify `change` by the shape of its values.
+///
+/// `Add`, `Remove`, `Set` and `Unchanged` are `Structural` when their value is an object
+/// or array and `Scalar` otherwise. A `Modify` is a `TypeChange` when `old` and `new`
+/// have different JSON types, and otherwise follows the same container rule. `Move`,
+/// `Copy` and `Rename` relocate whole subtrees and are always `Structural`, like an
+/// `ArrayOps` script, a `Reorder` of keys or an element range, while a `NumberDelta` is
+/// always `Scalar`, as is an `Opaque`, whose values are not known.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{categorize, Change, ChangeCategory};
+///
+/// let change = Change::Modify { old: json!("42"), new: json!(42) };
+/// assert_eq!(categorize(&change), ChangeCategory::TypeChange);
+/// ```
+pub fn categorize(change: &Change) -> ChangeCategory {
+    match change {
+        Change::Add(v) | Change::Remove(v) | Change::Set(v) | Change::Unchanged(v) => by_shape(v),
+        Change::Modify { old, new } if discriminant(old) != discriminant(new) => {
+            ChangeCategory::TypeChange
+        }
+        Change::Modify { new, .. } => by_shape(new),
+        Change::Move { .. }
+        | Change::Copy { .. }
+        | Change::Rename { .. }
+        | Change::ArrayOps(_)
+        | Change::Reorder { .. }
+        | Change::RemoveRange(_)
+        | Change::InsertRange(_) => ChangeCategory::Structural,
+        Change::NumberDelta { .. } | Change::Opaque { .. } => ChangeCategory::Scalar,
+    }
+}
+
+fn by_shape(value: &Value) -> ChangeCategory {
+    match value {
+        Value::Object(_) | Value::Array(_) => ChangeCategory::Structural,
+        _ => ChangeCategory::Scalar,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff;
+    use serde_json::json;
+
+    #[test]
+    fn categorizes_representative_changes() {
+        let before = json!({"name": "Ann", "age": "30", "tags": ["a"], "gone": 1});
+        let after = json!({
+            "name": "Bo",
+            "age": 30,
+            "tags": ["a", "b"],
+            "address": {"city": "Rio"}
+        });
+        let delta = diff(&before, &after);
+        let category = |path: &str| categorize(&delta[path]);
+
+        assert_eq!(category("name"), ChangeCategory::Scalar);
+        assert_eq!(category("age"), ChangeCategory::TypeChange);
+        assert_eq!(category("tags"), ChangeCategory::Structural);
+        assert_eq!(category("address"), ChangeCategory::Structural);
+        assert_eq!(category("gone"), ChangeCategory::Scalar);
+
+        let moved = Change::Move {
+            from: "a".to_string(),
+            to: "b".to_string(),
+        };
+        assert_eq!(categorize(&moved), ChangeCategory::Structural);
+        assert_eq!(
+            serde_json::to_value(ChangeCategory::TypeChange).unwrap(),
+            json!("type-change")
+        );
+    }
+}
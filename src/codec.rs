@@ -0,0 +1,351 @@
+//! Encoding paths to and from their segments.
+
+use crate::path::{join_path, parse_from_end, parse_position, path_matches};
+
+/// How a path is spelled: the separator between segments and how segments containing it
+/// are escaped.
+///
+/// Deltas produced by [`diff`](crate::diff) use [`DotCodec`]. Other codecs are used
+/// through [`diff_with_codec`](crate::diff_with_codec), [`apply_with_codec`] and
+/// [`revert_with_codec`], which encode every path they produce, and decode every path
+/// they read, with the given codec. Array segments keep their meaning in every codec: a
+/// plain index, a `+`-prefixed insertion index or a final `-`.
+///
+/// The empty path always refers to the whole document, so `decode("")` must return no
+/// segments.
+///
+/// [`apply_with_codec`]: crate::apply_with_codec
+/// [`revert_with_codec`]: crate::revert_with_codec
+pub trait PathCodec {
+    /// Spell out a path from its segments.
+    fn encode(&self, segments: &[String]) -> String;
+
+    /// Split a path into its segments.
+    fn decode(&self, path: &str) -> Vec<String>;
+
+    /// Append `segment` to `path`. The default decodes and re-encodes the whole path;
+    /// codecs should override it when a segment can be appended directly.
+    fn join(&self, path: &str, segment: &str) -> String {
+        let mut segments = self.decode(path);
+        segments.push(segment.to_string());
+        self.encode(&segments)
+    }
+
+    /// Whether `path` is spelled the way this codec writes paths. Applying a change at a
+    /// malformed path skips it, or fails with `JsonDiffError::InvalidPath` in strict mode.
+    /// The default accepts every path.
+    fn is_valid(&self, _path: &str) -> bool {
+        true
+    }
+
+    /// Whether `path` matches `pattern` segment by segment, where a `*` segment in the
+    /// pattern matches any single segment. Used for the path patterns of `DiffOptions`.
+    fn matches(&self, pattern: &str, path: &str) -> bool {
+        let pattern = self.decode(pattern);
+        let path = self.decode(path);
+        pattern.len() == path.len() && pattern.iter().zip(&path).all(|(p, s)| p == "*" || p == s)
+    }
+}
+
+/// The crate's own dot-paths (`user.roles.0`), with no escaping.
+///
+/// Keys containing `.` cannot be told apart from nested keys; use [`EscapedDotCodec`] or
+/// [`JsonPointerCodec`] for documents with such keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DotCodec;
+
+impl PathCodec for DotCodec {
+    fn encode(&self, segments: &[String]) -> String {
+        segments.join(".")
+    }
+
+    fn decode(&self, path: &str) -> Vec<String> {
+        if path.is_empty() {
+            return Vec::new();
+        }
+        path.split('.').map(str::to_string).collect()
+    }
+
+    fn join(&self, path: &str, segment: &str) -> String {
+        join_path(path, segment)
+    }
+
+    fn matches(&self, pattern: &str, path: &str) -> bool {
+        path_matches(pattern, path)
+    }
+}
+
+/// Dot-paths where a `.` or `\` inside a segment is escaped with a backslash, so the key
+/// `"a.b"` is spelled `a\.b`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EscapedDotCodec;
+
+impl EscapedDotCodec {
+    fn escape(segment: &str) -> String {
+        segment.replace('\\', "\\\\").replace('.', "\\.")
+    }
+}
+
+impl PathCodec for EscapedDotCodec {
+    fn encode(&self, segments: &[String]) -> String {
+        segments
+            .iter()
+            .map(|segment| Self::escape(segment))
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    fn decode(&self, path: &str) -> Vec<String> {
+        if path.is_empty() {
+            return Vec::new();
+        }
+        let mut segments = vec![String::new()];
+        let mut chars = path.chars();
+        while let Some(c) = chars.next() {
+            let current = segments
+                .last_mut()
+                .expect("there is always a current segment");
+            match c {
+                // A trailing lone backslash is kept as written.
+                '\\' => current.push(chars.next().unwrap_or('\\')),
+                '.' => segments.push(String::new()),
+                _ => current.push(c),
+            }
+        }
+        segments
+    }
+
+    fn join(&self, path: &str, segment: &str) -> String {
+        join_path(path, &Self::escape(segment))
+    }
+}
+
+/// RFC 6901 JSON Pointers (`/user/roles/0`), with `~1` for `/` and `~0` for `~` inside
+/// segments. A non-empty pointer without a leading `/` is malformed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonPointerCodec;
+
+impl JsonPointerCodec {
+    fn escape(segment: &str) -> String {
+        segment.replace('~', "~0").replace('/', "~1")
+    }
+}
+
+impl PathCodec for JsonPointerCodec {
+    fn encode(&self, segments: &[String]) -> String {
+        segments
+            .iter()
+            .map(|segment| format!("/{}", Self::escape(segment)))
+            .collect()
+    }
+
+    fn decode(&self, path: &str) -> Vec<String> {
+        path.split('/')
+            .skip(1)
+            .map(|token| token.replace("~1", "/").replace("~0", "~"))
+            .collect()
+    }
+
+    fn join(&self, path: &str, segment: &str) -> String {
+        format!("{path}/{}", Self::escape(segment))
+    }
+
+    /// A non-empty pointer must start with `/`.
+    fn is_valid(&self, path: &str) -> bool {
+        path.is_empty() || path.starts_with('/')
+    }
+}
+
+/// Dot-paths with array segments in brackets (`items[0].name`), reading naturally for
+/// documents that mix objects and arrays.
+///
+/// Whether a segment is written in brackets depends only on its spelling: indices (`0`),
+/// insertion positions (`+3`), counts from the end (`-1`) and the final `-` are bracketed,
+/// everything else is a dot-separated key. Integer keys of objects are bracketed too,
+/// which `apply` still resolves against the object. As with [`DotCodec`], keys containing
+/// `.` or `[` are not escaped.
+///
+/// ```rust
+/// use json_diff::{BracketCodec, PathCodec};
+///
+/// let segments = ["a", "b", "0", "c"].map(String::from);
+/// assert_eq!(BracketCodec.encode(&segments), "a.b[0].c");
+/// assert_eq!(BracketCodec.decode("a.b[0].c"), segments);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BracketCodec;
+
+impl BracketCodec {
+    fn is_array_segment(segment: &str) -> bool {
+        segment == "-" || parse_position(segment).is_some() || parse_from_end(segment).is_some()
+    }
+}
+
+impl PathCodec for BracketCodec {
+    fn encode(&self, segments: &[String]) -> String {
+        segments
+            .iter()
+            .fold(String::new(), |path, segment| self.join(&path, segment))
+    }
+
+    fn decode(&self, path: &str) -> Vec<String> {
+        if path.is_empty() {
+            return Vec::new();
+        }
+        let mut segments = Vec::new();
+        // The key segment being read, if the last separator was a `.` (or the start).
+        let mut key = (!path.starts_with('[')).then(String::new);
+        let mut chars = path.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => {
+                    segments.extend(key.take());
+                    key = Some(String::new());
+                }
+                '[' => {
+                    segments.extend(key.take());
+                    segments.push(chars.by_ref().take_while(|&c| c != ']').collect());
+                }
+                _ => key.get_or_insert_default().push(c),
+            }
+        }
+        segments.extend(key);
+        segments
+    }
+
+    fn join(&self, path: &str, segment: &str) -> String {
+        if Self::is_array_segment(segment) {
+            format!("{path}[{segment}]")
+        } else {
+            join_path(path, segment)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apply::{Edit, set_value};
+    use crate::{
+        ArrayStrategy, DiffOptions, JsonDiffError, apply_with_codec, diff_with_codec,
+        revert_with_codec,
+    };
+    use serde_json::json;
+
+    fn segments(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn encodings_round_trip() {
+        let tricky = segments(&["a.b", "c/d", "e~f", "g\\h", "", "0"]);
+        assert_eq!(EscapedDotCodec.encode(&tricky), "a\\.b.c/d.e~f.g\\\\h..0");
+        assert_eq!(JsonPointerCodec.encode(&tricky), "/a.b/c~1d/e~0f/g\\h//0");
+        let codecs: [&dyn PathCodec; 2] = [&EscapedDotCodec, &JsonPointerCodec];
+        for codec in codecs {
+            assert_eq!(codec.decode(&codec.encode(&tricky)), tricky);
+            assert_eq!(codec.decode(""), Vec::<String>::new());
+            assert_eq!(
+                codec.join(&codec.encode(&tricky[..2]), "x.y"),
+                codec.encode(&segments(&["a.b", "c/d", "x.y"]))
+            );
+        }
+        assert_eq!(DotCodec.decode("a.b.0"), segments(&["a", "b", "0"]));
+        assert_eq!(DotCodec.encode(&segments(&["a", "b", "0"])), "a.b.0");
+    }
+
+    #[test]
+    fn malformed_pointers_are_skipped_or_rejected() {
+        assert!(JsonPointerCodec.is_valid("") && JsonPointerCodec.is_valid("/a"));
+        for path in ["a", "a/b"] {
+            assert!(!JsonPointerCodec.is_valid(path));
+            let mut root = json!({"a": {"b": 1}, "b": 2});
+            let edit = || Edit::Replace(json!(3));
+            assert_eq!(
+                set_value(&mut root, path, edit(), true, &JsonPointerCodec),
+                Err(JsonDiffError::InvalidPath {
+                    path: path.to_string()
+                })
+            );
+            assert_eq!(
+                set_value(&mut root, path, edit(), false, &JsonPointerCodec),
+                Ok(false)
+            );
+            assert_eq!(root, json!({"a": {"b": 1}, "b": 2}));
+        }
+    }
+
+    #[test]
+    fn diffs_are_consistent_and_reversible_under_each_codec() {
+        let before = json!({"a": {"x": 1, "y.z": [1, 2]}, "/": "slash", "gone": true});
+        let after = json!({"a": {"x": 2, "y.z": [1, 2, 3]}, "/": "SLASH", "new": {"k": null}});
+        // Plain dot-paths cannot tell the key `y.z` from `y` then `z`, so only the
+        // escaping codecs can replay the delta.
+        let codecs: [(&dyn PathCodec, [&str; 5], bool); 3] = [
+            (&DotCodec, ["/", "a.x", "a.y.z", "gone", "new"], false),
+            (
+                &EscapedDotCodec,
+                ["/", "a.x", "a.y\\.z", "gone", "new"],
+                true,
+            ),
+            (
+                &JsonPointerCodec,
+                ["/a/x", "/a/y.z", "/gone", "/new", "/~1"],
+                true,
+            ),
+        ];
+        for (codec, paths, reversible) in codecs {
+            let delta = diff_with_codec(&before, &after, &DiffOptions::default(), codec);
+            assert_eq!(delta.keys().map(String::as_str).collect::<Vec<_>>(), paths);
+            if !reversible {
+                continue;
+            }
+            assert_eq!(apply_with_codec(&before, &delta, codec), after);
+            assert_eq!(revert_with_codec(&after, &delta, codec), before);
+        }
+    }
+
+    #[test]
+    fn brackets_mark_array_segments() {
+        let mixed = segments(&["a", "b", "0", "c", "+2", "1", "-"]);
+        assert_eq!(BracketCodec.encode(&mixed), "a.b[0].c[+2][1][-]");
+        assert_eq!(BracketCodec.decode("a.b[0].c[+2][1][-]"), mixed);
+        assert_eq!(
+            BracketCodec.decode("a.b[0].c"),
+            segments(&["a", "b", "0", "c"])
+        );
+        assert_eq!(BracketCodec.decode("[0].c"), segments(&["0", "c"]));
+        assert_eq!(BracketCodec.encode(&segments(&["0", "c"])), "[0].c");
+        assert_eq!(BracketCodec.decode("a..b"), segments(&["a", "", "b"]));
+        assert_eq!(BracketCodec.join("a[0]", "-1"), "a[0][-1]");
+        assert_eq!(BracketCodec.join("", "k"), "k");
+
+        let before = json!({"a": {"b": [{"c": 1}, {"c": 2}]}, "tags": ["x"]});
+        let after = json!({"a": {"b": [{"c": 1}, {"c": 3}]}, "tags": ["x", "y"]});
+        let options = DiffOptions {
+            array_strategy: ArrayStrategy::SameLengthObjects,
+            set_arrays: vec!["tags".to_string()],
+            ..Default::default()
+        };
+        let delta = diff_with_codec(&before, &after, &options, &BracketCodec);
+        assert_eq!(
+            delta.keys().map(String::as_str).collect::<Vec<_>>(),
+            ["a.b[1].c", "tags[+1]"]
+        );
+        assert_eq!(apply_with_codec(&before, &delta, &BracketCodec), after);
+        assert_eq!(revert_with_codec(&after, &delta, &BracketCodec), before);
+    }
+
+    #[test]
+    fn option_patterns_use_the_codec() {
+        let before = json!({"items": [{"id": 1, "seen": "a"}], "tags": ["x", "y"]});
+        let after = json!({"items": [{"id": 1, "seen": "b"}], "tags": ["y", "x"]});
+        let options = DiffOptions {
+            set_arrays: vec!["/tags".to_string()],
+            ignore_paths: vec!["/items/*/seen".to_string()],
+            array_strategy: ArrayStrategy::SameLengthObjects,
+            ..Default::default()
+        };
+        assert!(diff_with_codec(&before, &after, &options, &JsonPointerCodec).is_empty());
+    }
+}
@@ -0,0 +1,362 @@
+//! Transforming concurrent deltas against each other, for operational transformation.
+
+use crate::path::{is_within, parse_from_end, parse_position};
+use crate::{Change, Delta, apply, number};
+use serde_json::Value;
+
+/// Transform two concurrent deltas `a` and `b`, both made against the same document, into
+/// `(a_prime, b_prime)` such that applying `a` then `b_prime` gives the same document as
+/// applying `b` then `a_prime`.
+///
+/// Returns `None` when one delta inserts or removes elements of an array (by index, or
+/// with an `ArrayOps` script) that the other also changes within: the other delta's
+/// indices would have to be shifted, and paths alone do not tell array indices from
+/// numeric object keys. Arrays replaced whole, and arrays only one side changes, are fine.
+///
+/// Changes at paths that do not overlap commute and are kept as they are. Where the two
+/// deltas touch the same value, `a` has priority:
+///
+/// - Two changes at the same path: `a`'s result is kept and `b`'s change is dropped, except
+///   that increments (`NumberDelta`) add up with each other and are added on top of a
+///   numeric value written by the other side.
+/// - A change inside a value the other side removes is dropped, whichever side made it:
+///   removing a value removes every concurrent edit within it.
+/// - A change inside a value the other side replaces: if the change is `a`'s, it is
+///   carried into `b`'s replacement value; if it is `b`'s, it is dropped in favour of `a`'s
+///   replacement.
+///
+/// Changes are rewritten to hold the values they meet after the other delta (a `Modify`
+/// of a value the other side removed becomes an `Add`, the `old` value of a replacement
+/// includes the other side's edits within it), so they stay exact for strict application
+/// where possible. Relocations (`Move`, `Copy`, `Rename`) are kept or dropped like other
+/// changes but not rewritten.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{apply, diff, transform};
+///
+/// let doc = json!({"title": "Draft", "count": 1, "tags": ["a"]});
+/// let a = diff(&doc, &json!({"title": "Final", "count": 1, "tags": ["a"]}));
+/// let b = diff(&doc, &json!({"title": "Notes", "count": 2, "tags": ["a"]}));
+///
+/// let (a_prime, b_prime) = transform(&a, &b).unwrap();
+/// let merged = apply(&apply(&doc, &a), &b_prime);
+/// assert_eq!(merged, apply(&apply(&doc, &b), &a_prime));
+/// assert_eq!(merged, json!({"title": "Final", "count": 2, "tags": ["a"]}));
+/// ```
+pub fn transform(a: &Delta, b: &Delta) -> Option<(Delta, Delta)> {
+    if reshapes_under(a, b) || reshapes_under(b, a) {
+        return None;
+    }
+    let rebase_all = |delta: &Delta, other: &Delta, wins: bool| -> Delta {
+        delta
+            .iter()
+            .filter_map(|(path, change)| {
+                rebase(path, change, other, wins).map(|change| (path.clone(), change))
+            })
+            .collect()
+    };
+    Some((rebase_all(a, b, true), rebase_all(b, a, false)))
+}
+
+/// Whether `delta` inserts or removes elements of an array that `other` changes within,
+/// or rewrites with `ArrayOps` an array that `other` changes at all.
+fn reshapes_under(delta: &Delta, other: &Delta) -> bool {
+    let is_index = |segment: &str| {
+        segment == "-" || parse_position(segment).is_some() || parse_from_end(segment).is_some()
+    };
+    delta.iter().any(|(path, change)| {
+        let (array, whole) = match change {
+            Change::ArrayOps(_) => (path.as_str(), true),
+            Change::Add(_)
+            | Change::Remove(_)
+            | Change::InsertRange(_)
+            | Change::RemoveRange(_) => match path.rsplit_once('.') {
+                Some((parent, last)) if is_index(last) => (parent, false),
+                None if is_index(path) => ("", false),
+                _ => return false,
+            },
+            _ => return false,
+        };
+        other.iter().any(|(inner, change)| {
+            !matches!(change, Change::Unchanged(_))
+                && is_within(inner, array)
+                && (whole || inner != array)
+        })
+    })
+}
+
+/// `change` at `path`, rewritten to apply after `other`, a concurrent delta, or `None` if
+/// it should be dropped. `wins` says whether `change` has priority over `other`.
+fn rebase(path: &str, change: &Change, other: &Delta, wins: bool) -> Option<Change> {
+    if matches!(change, Change::Unchanged(_)) {
+        return Some(change.clone());
+    }
+    if let Some(theirs) = other
+        .get(path)
+        .filter(|c| !matches!(c, Change::Unchanged(_)))
+    {
+        return same_path(change, theirs, wins);
+    }
+    if let Some(outer) = enclosing(other, path) {
+        return match outer {
+            Change::Remove(_) => None,
+            Change::Add(_) | Change::Modify { .. } | Change::Set(_) if !wins => None,
+            _ => Some(change.clone()),
+        };
+    }
+    let inner = inside(other, path);
+    if inner.is_empty() {
+        return Some(change.clone());
+    }
+    let merged = |value: &Value| apply(value, &inner);
+    Some(match change {
+        Change::Remove(value) => Change::Remove(merged(value)),
+        Change::Modify { old, new } if wins => Change::Modify {
+            old: merged(old),
+            new: new.clone(),
+        },
+        Change::Modify { old, new } => Change::Modify {
+            old: merged(old),
+            new: merged(new),
+        },
+        Change::Add(value) if !wins => Change::Add(merged(value)),
+        Change::Set(value) if !wins => Change::Set(merged(value)),
+        _ => change.clone(),
+    })
+}
+
+/// Resolve `mine` against `theirs`, a concurrent change at the same path.
+fn same_path(mine: &Change, theirs: &Change, wins: bool) -> Option<Change> {
+    let numeric_write = |write: &Change, by| written(write).is_some_and(|v| plus(v, by).is_some());
+    match (mine, theirs) {
+        (Change::NumberDelta { .. }, Change::NumberDelta { .. }) => Some(mine.clone()),
+        (Change::NumberDelta { by }, _) if numeric_write(theirs, by) => Some(mine.clone()),
+        (_, Change::NumberDelta { by }) if numeric_write(mine, by) => Some(shifted(mine, by)),
+        (Change::NumberDelta { .. }, _) => None,
+        (Change::Remove(value), Change::NumberDelta { by }) => Some(Change::Remove(
+            plus(value, by).unwrap_or_else(|| value.clone()),
+        )),
+        (_, Change::NumberDelta { .. }) if written(mine).is_some() => Some(mine.clone()),
+        _ if !wins => None,
+        (Change::Remove(_), Change::Remove(_)) => None,
+        (Change::Remove(_), _) => Some(
+            written(theirs).map_or_else(|| mine.clone(), |value| Change::Remove(value.clone())),
+        ),
+        (_, Change::Remove(_)) => {
+            Some(written(mine).map_or_else(|| mine.clone(), |value| Change::Add(value.clone())))
+        }
+        _ => match (written(mine), written(theirs)) {
+            (Some(new), Some(old)) => (new != old).then(|| Change::Modify {
+                old: old.clone(),
+                new: new.clone(),
+            }),
+            _ => Some(mine.clone()),
+        },
+    }
+}
+
+/// The value `change` leaves at its path, if it writes one.
+fn written(change: &Change) -> Option<&Value> {
+    match change {
+        Change::Add(value) | Change::Set(value) | Change::Modify { new: value, .. } => Some(value),
+        _ => None,
+    }
+}
+
+/// `value + by`, if `value` is a number.
+fn plus(value: &Value, by: &serde_json::Number) -> Option<Value> {
+    number::add(value.as_number()?, by).map(Value::Number)
+}
+
+/// The write `change` with `by` added to the values it holds.
+fn shifted(change: &Change, by: &serde_json::Number) -> Change {
+    let plus = |value: &Value| plus(value, by).unwrap_or_else(|| value.clone());
+    match change {
+        Change::Add(value) => Change::Add(plus(value)),
+        Change::Set(value) => Change::Set(plus(value)),
+        Change::Modify { old, new } => Change::Modify {
+            old: plus(old),
+            new: plus(new),
+        },
+        _ => change.clone(),
+    }
+}
+
+/// The change of `delta` at a path strictly enclosing `path`, if any.
+fn enclosing<'a>(delta: &'a Delta, path: &str) -> Option<&'a Change> {
+    delta
+        .iter()
+        .find(|(outer, change)| {
+            outer.as_str() != path
+                && is_within(path, outer)
+                && !matches!(change, Change::Unchanged(_))
+        })
+        .map(|(_, change)| change)
+}
+
+/// The changes of `delta` strictly inside `path`, keyed relative to it.
+fn inside(delta: &Delta, path: &str) -> Delta {
+    delta
+        .iter()
+        .filter(|(inner, change)| {
+            inner.as_str() != path
+                && is_within(inner, path)
+                && !matches!(change, Change::Unchanged(_))
+        })
+        .map(|(inner, change)| {
+            let relative = if path.is_empty() {
+                inner.clone()
+            } else {
+                inner[path.len() + 1..].to_string()
+            };
+            (relative, change.clone())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArrayStrategy, DiffOptions, diff, diff_with};
+    use serde_json::json;
+
+    /// Transform `a` and `b`, check both orders converge and return the result.
+    fn converge(doc: &Value, a: &Delta, b: &Delta) -> Value {
+        let (a_prime, b_prime) = transform(a, b).unwrap();
+        let ab = apply(&apply(doc, a), &b_prime);
+        assert_eq!(ab, apply(&apply(doc, b), &a_prime));
+        ab
+    }
+
+    #[test]
+    fn non_overlapping_edits_commute() {
+        let doc = json!({"user": {"name": "ann", "age": 30}, "tags": ["x"], "old": 1});
+        let a = diff(
+            &doc,
+            &json!({"user": {"name": "bob", "age": 30}, "tags": ["x"], "old": 1}),
+        );
+        let b = diff(
+            &doc,
+            &json!({"user": {"name": "ann", "age": 31}, "tags": ["x"], "new": 2}),
+        );
+        let (a_prime, b_prime) = transform(&a, &b).unwrap();
+        assert_eq!((&a_prime, &b_prime), (&a, &b));
+        assert_eq!(
+            converge(&doc, &a, &b),
+            json!({"user": {"name": "bob", "age": 31}, "tags": ["x"], "new": 2})
+        );
+    }
+
+    #[test]
+    fn overlapping_edits_resolve_in_favour_of_a() {
+        let doc = json!({"title": "t", "note": "n", "gone": 1, "user": {"name": "ann", "age": 30}});
+
+        // Same path: `a`'s result wins, whatever `b` did there.
+        let a = diff(
+            &doc,
+            &json!({"title": "A", "note": "n", "user": {"name": "ann", "age": 30}}),
+        );
+        let b = diff(
+            &doc,
+            &json!({"title": "B", "note": "n", "gone": 2, "user": {"name": "ann", "age": 30}}),
+        );
+        let (a_prime, b_prime) = transform(&a, &b).unwrap();
+        assert!(b_prime.is_empty());
+        assert_eq!(
+            a_prime["title"],
+            Change::Modify {
+                old: json!("B"),
+                new: json!("A"),
+            }
+        );
+        assert_eq!(a_prime["gone"], Change::Remove(json!(2)));
+        assert_eq!(
+            converge(&doc, &a, &b),
+            json!({"title": "A", "note": "n", "user": {"name": "ann", "age": 30}})
+        );
+
+        // `b` removes the object `a` edits within: the removal wins.
+        let a = diff(
+            &doc,
+            &json!({"title": "t", "note": "n", "gone": 1, "user": {"name": "bob", "age": 30}}),
+        );
+        let b = diff(&doc, &json!({"title": "t", "note": "n", "gone": 1}));
+        let (a_prime, b_prime) = transform(&a, &b).unwrap();
+        assert!(a_prime.is_empty());
+        assert_eq!(
+            b_prime["user"],
+            Change::Remove(json!({"name": "bob", "age": 30}))
+        );
+        assert_eq!(
+            converge(&doc, &a, &b),
+            json!({"title": "t", "note": "n", "gone": 1})
+        );
+
+        // `b` replaces the object `a` edits within: `a`'s edit is carried into it.
+        let options = DiffOptions {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        let b = diff_with(
+            &doc,
+            &json!({"title": "t", "note": "n", "gone": 1, "user": {"name": "ann", "role": "admin"}}),
+            &options,
+        );
+        assert_eq!(
+            converge(&doc, &a, &b)["user"],
+            json!({"name": "bob", "role": "admin"})
+        );
+        // ... and the other way round, `a`'s replacement drops `b`'s edit.
+        assert_eq!(
+            converge(&doc, &b, &a)["user"],
+            json!({"name": "ann", "role": "admin"})
+        );
+    }
+
+    #[test]
+    fn concurrent_element_insertions_and_removals_are_refused() {
+        let doc = json!({"tags": ["x", "y", "z"], "rows": [{"n": 1}, {"n": 2}], "title": "t"});
+        let options = DiffOptions {
+            set_arrays: vec!["tags".to_string()],
+            array_strategy: ArrayStrategy::SameLengthObjects,
+            ..Default::default()
+        };
+        let edit = |after: Value| diff_with(&doc, &after, &options);
+        let drop_x = edit(json!({"tags": ["y", "z"], "rows": doc["rows"], "title": "t"}));
+        let drop_y = edit(json!({"tags": ["x", "z"], "rows": doc["rows"], "title": "t"}));
+        assert_eq!(transform(&drop_x, &drop_y), None);
+        assert_eq!(transform(&drop_y, &drop_x), None);
+
+        // Without a concurrent change inside the array, or with in-place element edits
+        // only, the indices need no shifting.
+        let retitle = edit(json!({"tags": doc["tags"], "rows": doc["rows"], "title": "T"}));
+        assert_eq!(
+            converge(&doc, &drop_x, &retitle),
+            json!({"tags": ["y", "z"], "rows": doc["rows"], "title": "T"})
+        );
+        let first = edit(json!({"tags": doc["tags"], "rows": [{"n": 5}, {"n": 2}], "title": "t"}));
+        let second = edit(json!({"tags": doc["tags"], "rows": [{"n": 1}, {"n": 6}], "title": "t"}));
+        assert_eq!(
+            converge(&doc, &first, &second)["rows"],
+            json!([{"n": 5}, {"n": 6}])
+        );
+    }
+
+    #[test]
+    fn increments_add_up() {
+        let doc = json!({"hits": 10, "misses": 1});
+        let a = Delta::from_iter([
+            ("hits".to_string(), Change::NumberDelta { by: 2.into() }),
+            ("misses".to_string(), Change::NumberDelta { by: 1.into() }),
+        ]);
+        let b = diff(&doc, &json!({"hits": 10, "misses": 5}));
+        let b = Delta::from_iter(
+            b.into_inner()
+                .into_iter()
+                .chain([("hits".to_string(), Change::NumberDelta { by: 3.into() })]),
+        );
+        assert_eq!(converge(&doc, &a, &b), json!({"hits": 15, "misses": 6}));
+        assert_eq!(converge(&doc, &b, &a), json!({"hits": 15, "misses": 6}));
+    }
+}
@@ -0,0 +1,154 @@
+//! Recording which kind of container each change happened in.
+
+use crate::path::{get_path, parse_index};
+use crate::{Delta, DiffOptions, diff_with};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// The kind of container holding the location a change applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ContainerKind {
+    /// The change replaces the whole document (the empty path).
+    Root,
+    /// The last path segment is an object key.
+    Object,
+    /// The last path segment is an array index.
+    Array,
+}
+
+/// Like [`diff_with`], also reporting for every changed path whether its last segment is an
+/// object key or an array index.
+///
+/// A numeric segment is ambiguous on its own: `items.0` may address key `"0"` of an object
+/// or the first element of an array. The kind is looked up from the parent in `before`,
+/// falling back to `after` where the parent only exists there.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff_with_containers, ContainerKind, DiffOptions};
+///
+/// let (delta, kinds) = diff_with_containers(
+///     &json!({"byId": {"0": "a"}}),
+///     &json!({"byId": {"0": "b"}}),
+///     &DiffOptions::default(),
+/// );
+/// assert!(delta.contains_key("byId.0"));
+/// assert_eq!(kinds["byId.0"], ContainerKind::Object);
+/// ```
+pub fn diff_with_containers(
+    before: &Value,
+    after: &Value,
+    options: &DiffOptions,
+) -> (Delta, BTreeMap<String, ContainerKind>) {
+    let delta = diff_with(before, after, options);
+    let kinds = delta
+        .keys()
+        .map(|path| (path.clone(), container_kind(before, after, path)))
+        .collect();
+    (delta, kinds)
+}
+
+/// Whether `value` is a non-empty object whose keys are all canonical non-negative integers
+/// (`"0"`, `"7"`, `"10"`, but not `"01"` or `"-1"`), such as a map keyed by numeric ids.
+///
+/// Paths into such an object look exactly like array paths (`byId.10`). `apply` and
+/// `revert` resolve them against the document, giving keys of an existing object key
+/// semantics, so the ambiguity only matters when reading a delta without the document;
+/// see [`diff_with_containers`].
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::is_index_map;
+///
+/// assert!(is_index_map(&json!({"0": "a", "1": "b", "10": "c"})));
+/// assert!(!is_index_map(&json!(["a", "b"])));
+/// assert!(!is_index_map(&json!({"01": "a"})));
+/// ```
+pub fn is_index_map(value: &Value) -> bool {
+    match value {
+        Value::Object(obj) => {
+            !obj.is_empty()
+                && obj
+                    .keys()
+                    .all(|key| parse_index(key).is_some() && (key == "0" || !key.starts_with('0')))
+        }
+        _ => false,
+    }
+}
+
+fn container_kind(before: &Value, after: &Value, path: &str) -> ContainerKind {
+    if path.is_empty() {
+        return ContainerKind::Root;
+    }
+    let parent = path.rsplit_once('.').map_or("", |(parent, _)| parent);
+    match get_path(before, parent).or_else(|| get_path(after, parent)) {
+        Some(Value::Array(_)) => ContainerKind::Array,
+        _ => ContainerKind::Object,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArrayStrategy;
+    use serde_json::json;
+
+    #[test]
+    fn numeric_object_keys_and_array_indices_are_told_apart() {
+        let options = DiffOptions {
+            array_strategy: ArrayStrategy::SameLengthObjects,
+            set_arrays: vec!["tags".to_string()],
+            ..Default::default()
+        };
+        let before = json!({
+            "byId": {"0": {"v": 1}},
+            "rows": [{"v": 1}],
+            "tags": ["a"]
+        });
+        let after = json!({
+            "byId": {"0": {"v": 2}},
+            "rows": [{"v": 2}],
+            "tags": ["a", "b"]
+        });
+        let (delta, kinds) = diff_with_containers(&before, &after, &options);
+
+        assert_eq!(delta.len(), 3);
+        assert_eq!(kinds["byId.0.v"], ContainerKind::Object);
+        assert_eq!(kinds["rows.0.v"], ContainerKind::Object);
+        assert_eq!(kinds["tags.+1"], ContainerKind::Array);
+
+        let (_, root) = diff_with_containers(&json!(1), &json!(2), &options);
+        assert_eq!(root[""], ContainerKind::Root);
+    }
+
+    #[test]
+    fn element_changes_report_array_parents() {
+        let options = DiffOptions {
+            set_arrays: vec!["ids".to_string()],
+            ..Default::default()
+        };
+        let (_, kinds) =
+            diff_with_containers(&json!({"ids": [1, 2]}), &json!({"ids": [2]}), &options);
+        assert_eq!(kinds["ids.0"], ContainerKind::Array);
+    }
+
+    #[test]
+    fn index_maps_are_recognized() {
+        let by_id = json!({"0": "a", "1": "b", "10": "c"});
+        assert!(is_index_map(&by_id));
+        for other in [
+            json!({}),
+            json!({"0": 1, "x": 2}),
+            json!({"-1": 1}),
+            json!([1]),
+        ] {
+            assert!(!is_index_map(&other), "{other}");
+        }
+
+        let after = json!({"0": "a", "10": "C"});
+        let (_, kinds) = diff_with_containers(&by_id, &after, &DiffOptions::default());
+        assert_eq!(kinds["1"], ContainerKind::Object);
+        assert_eq!(kinds["10"], ContainerKind::Object);
+    }
+}
@@ -0,0 +1,150 @@
+//! Helpers for diffing and applying against typed, serde-serializable data and raw JSON
+//! bytes.
+
+use crate::{Delta, JsonDiffError, apply, diff};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Diff a JSON value against any serializable value.
+///
+/// `after` is converted with `serde_json::to_value` before diffing, so the delta uses the
+/// same field names and representation as the type's `Serialize` impl.
+///
+/// ```rust
+/// use serde::Serialize;
+/// use serde_json::json;
+/// use json_diff::{diff_serializable, Change};
+///
+/// #[derive(Serialize)]
+/// struct User { name: String, age: u32 }
+///
+/// let stored = json!({"name": "Ann", "age": 30});
+/// let delta = diff_serializable(&stored, &User { name: "Ann".into(), age: 31 }).unwrap();
+/// assert_eq!(delta.get("age"), Some(&Change::Modify { old: json!(30), new: json!(31) }));
+/// ```
+pub fn diff_serializable<T: Serialize>(before: &Value, after: &T) -> Result<Delta, JsonDiffError> {
+    let after = serde_json::to_value(after)?;
+    Ok(diff(before, &after))
+}
+
+/// Apply a `Delta` and deserialize the result into `T`.
+///
+/// Fails with `JsonDiffError::Serialization` if the result does not match `T`.
+pub fn apply_into<T: DeserializeOwned>(
+    original: &Value,
+    delta: &Delta,
+) -> Result<T, JsonDiffError> {
+    Ok(serde_json::from_value(apply(original, delta))?)
+}
+
+/// Parse two JSON documents from bytes and diff them.
+///
+/// Fails with `JsonDiffError::Serialization` if either input is not valid JSON.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff_bytes, Change};
+///
+/// let delta = diff_bytes(br#"{"a": 1}"#, br#"{"a": 2}"#).unwrap();
+/// assert_eq!(delta["a"], Change::Modify { old: json!(1), new: json!(2) });
+/// ```
+pub fn diff_bytes(before: &[u8], after: &[u8]) -> Result<Delta, JsonDiffError> {
+    let before: Value = serde_json::from_slice(before)?;
+    let after: Value = serde_json::from_slice(after)?;
+    Ok(diff(&before, &after))
+}
+
+/// Parse a JSON document from bytes, apply `delta` and serialize the result back to
+/// compact JSON bytes.
+///
+/// Fails with `JsonDiffError::Serialization` if `original` is not valid JSON.
+pub fn apply_bytes(original: &[u8], delta: &Delta) -> Result<Vec<u8>, JsonDiffError> {
+    let original: Value = serde_json::from_slice(original)?;
+    Ok(serde_json::to_vec(&apply(&original, delta))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Change;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Settings {
+        theme: String,
+        volume: u8,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        language: Option<String>,
+    }
+
+    #[test]
+    fn diff_against_struct() {
+        let stored = json!({"theme": "dark", "volume": 3});
+        let current = Settings {
+            theme: "dark".to_string(),
+            volume: 5,
+            language: Some("pt-BR".to_string()),
+        };
+        let delta = diff_serializable(&stored, &current).unwrap();
+
+        let mut expected = Delta::new();
+        expected.insert(
+            "volume".to_string(),
+            Change::Modify {
+                old: json!(3),
+                new: json!(5),
+            },
+        );
+        expected.insert("language".to_string(), Change::Add(json!("pt-BR")));
+        assert_eq!(delta, expected);
+    }
+
+    #[test]
+    fn apply_into_struct_round_trip() {
+        let stored = json!({"theme": "dark", "volume": 3});
+        let current = Settings {
+            theme: "light".to_string(),
+            volume: 3,
+            language: None,
+        };
+        let delta = diff_serializable(&stored, &current).unwrap();
+        let restored: Settings = apply_into(&stored, &delta).unwrap();
+        assert_eq!(restored, current);
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let before = br#"{"name": "Ann", "tags": ["a"]}"#;
+        let after = br#"{"name": "Bo", "tags": ["a"], "age": 3}"#;
+        let delta = diff_bytes(before, after).unwrap();
+        assert_eq!(delta.len(), 2);
+        let applied = apply_bytes(before, &delta).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&applied).unwrap(),
+            serde_json::from_slice::<Value>(after).unwrap()
+        );
+    }
+
+    #[test]
+    fn invalid_bytes_are_errors() {
+        let valid = b"{}";
+        for invalid in [&b"{"[..], b"", b"{\"a\": nope}", b"\xff"] {
+            assert!(matches!(
+                diff_bytes(invalid, valid),
+                Err(JsonDiffError::Serialization { .. })
+            ));
+            assert!(diff_bytes(valid, invalid).is_err());
+            assert!(apply_bytes(invalid, &Delta::new()).is_err());
+        }
+    }
+
+    #[test]
+    fn apply_into_reports_shape_mismatch() {
+        let stored = json!({"theme": "dark", "volume": 3});
+        let mut delta = Delta::new();
+        delta.insert("volume".to_string(), Change::Remove(json!(3)));
+        assert!(apply_into::<Settings>(&stored, &delta).is_err());
+    }
+}
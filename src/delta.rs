@@ -0,0 +1,497 @@
+//! The `Delta` map type and its domain methods.
+
+use crate::number;
+use crate::path::{get_path, natural_cmp, parse_index, parse_insertion};
+use crate::{Change, JsonDiffError};
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+use std::collections::BTreeMap;
+use std::collections::btree_map;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// A mapping from JSON dot-paths to `Change` values.
+///
+/// `Delta` wraps a `BTreeMap<String, Change>` and dereferences to it, so the usual map
+/// methods (`get`, `insert`, `len`, `is_empty`, iteration, ...) work unchanged. It
+/// serializes exactly like the map, and converts to and from one with `From`/`Into`.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::diff;
+///
+/// let before = json!({"a": 1});
+/// let after = json!({"a": 2, "b": true});
+/// let delta = diff(&before, &after);
+///
+/// assert_eq!(delta.len(), 2);
+/// assert_eq!(delta.apply(&before), after);
+/// assert_eq!(delta.revert(&after), before);
+/// assert_eq!(delta.invert(&after).apply(&after), before);
+/// ```
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Delta(BTreeMap<String, Change>);
+
+impl Delta {
+    /// Create an empty delta.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply this delta to `original`; see [`apply`](crate::apply).
+    pub fn apply(&self, original: &Value) -> Value {
+        crate::apply(original, self)
+    }
+
+    /// Apply this delta strictly; see [`try_apply`](crate::try_apply).
+    pub fn try_apply(&self, original: &Value) -> Result<Value, JsonDiffError> {
+        crate::try_apply(original, self)
+    }
+
+    /// Revert this delta on `modified`; see [`revert`](crate::revert).
+    pub fn revert(&self, modified: &Value) -> Value {
+        crate::revert(modified, self)
+    }
+
+    /// Return the delta that undoes this one, such that
+    /// `delta.invert(&after).apply(&after)` restores `before`.
+    ///
+    /// Each change is replaced by its [`inverse`](Change::inverse). Element-level array
+    /// changes are re-keyed as well: a `Remove` at old index `i` becomes an `Add` at new
+    /// position `+i`, and an `Add` at `+j` becomes a `Remove` at `j` (likewise for
+    /// ranges). `modified`, the document this delta produced, tells those apart from
+    /// object keys spelled like indices, which keep their paths. Changes nested under a
+    /// `Rename` (the remaining differences of a similar value) are moved from its `to` key
+    /// back to its `from` key, since the inverse renames first. `Copy` and `Set` changes
+    /// have no self-contained inverse and are carried over unchanged; use
+    /// [`try_revert`](crate::try_revert) for deltas that contain them.
+    pub fn invert(&self, modified: &Value) -> Delta {
+        let in_array = |path: &str| {
+            let parent = path.rsplit_once('.').map_or("", |(parent, _)| parent);
+            matches!(get_path(modified, parent), Some(Value::Array(_)))
+        };
+        let renames: Vec<(&str, &str)> = self
+            .values()
+            .filter_map(|change| match change {
+                Change::Rename { from, to } => Some((to.as_str(), from.as_str())),
+                _ => None,
+            })
+            .collect();
+        let renamed_back = |path: String| {
+            renames
+                .iter()
+                .find_map(|(to, from)| {
+                    let rest = path.strip_prefix(to)?;
+                    rest.starts_with('.').then(|| format!("{from}{rest}"))
+                })
+                .unwrap_or(path)
+        };
+        self.0
+            .iter()
+            .map(|(path, change)| {
+                let path = match change {
+                    Change::Move { from, .. } | Change::Rename { from, .. } => from.clone(),
+                    Change::Remove(_) | Change::RemoveRange(_) if in_array(path) => {
+                        rekey(path, parse_index, "+")
+                    }
+                    Change::Add(_) | Change::InsertRange(_) if in_array(path) => {
+                        rekey(path, parse_insertion, "")
+                    }
+                    _ => path.clone(),
+                };
+                (renamed_back(path), change.clone().inverse())
+            })
+            .collect()
+    }
+
+    /// Fold `other`, a delta recorded after this one, into `self`.
+    ///
+    /// Changes at the same path are composed: an `Add` followed by a `Modify` becomes an
+    /// `Add` of the final value, an `Add` followed by a `Remove` disappears, a `Remove`
+    /// followed by an `Add` becomes a `Modify`, and two `Modify`s collapse into one (or
+    /// vanish if the value ends where it started). Two `NumberDelta`s add up (vanishing
+    /// if they cancel out), and a `NumberDelta` after an `Add` or `Modify` is folded into
+    /// its new value. Two `ArrayOps` scripts are concatenated, and two `Reorder`s collapse
+    /// into one (vanishing if the keys end in their previous order). Otherwise the later
+    /// change wins.
+    /// Composition is per path, so it is exact for object keys; element-level array
+    /// changes are only combined when both deltas use the same key.
+    ///
+    /// Fails with `JsonDiffError::NumberOverflow`, leaving `self` unchanged, if numbers
+    /// folded together add up to more than an `f64` can hold.
+    pub fn merge(&mut self, other: Delta) -> Result<(), JsonDiffError> {
+        let mut composed = Vec::with_capacity(other.len());
+        for (path, later) in other {
+            let change = match self.0.get(&path) {
+                Some(earlier) => compose(&path, earlier.clone(), later)?,
+                None => Some(later),
+            };
+            composed.push((path, change));
+        }
+        for (path, change) in composed {
+            match change {
+                Some(change) => self.0.insert(path, change),
+                None => self.0.remove(&path),
+            };
+        }
+        Ok(())
+    }
+
+    /// Iterate over the changes with array indices in numeric order.
+    ///
+    /// The map itself is ordered by comparing paths as strings, which puts `arr.10` before
+    /// `arr.2`. This iterator compares paths segment by segment instead, ordering index
+    /// segments (`2`, `+10`) by their value and other segments as strings, so element
+    /// changes come out by index.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use json_diff::DeltaBuilder;
+    ///
+    /// let delta = DeltaBuilder::new()
+    ///     .remove("arr.10", json!(10))
+    ///     .remove("arr.2", json!(2))
+    ///     .build();
+    /// let paths: Vec<&String> = delta.iter_natural().map(|(path, _)| path).collect();
+    /// assert_eq!(paths, ["arr.2", "arr.10"]);
+    /// ```
+    pub fn iter_natural(&self) -> impl Iterator<Item = (&String, &Change)> {
+        let mut entries: Vec<(Vec<&str>, &String, &Change)> = self
+            .0
+            .iter()
+            .map(|(path, change)| (path.split('.').collect(), path, change))
+            .collect();
+        entries.sort_by(|(a, ..), (b, ..)| natural_cmp(a, b));
+        entries.into_iter().map(|(_, path, change)| (path, change))
+    }
+
+    /// Unwrap into the underlying map.
+    pub fn into_inner(self) -> BTreeMap<String, Change> {
+        self.0
+    }
+
+    /// Serialize this delta in the crate's persistence format: a compact JSON object from
+    /// path to change, each change tagged by its variant name, e.g.
+    /// `{"a":{"Modify":{"old":1,"new":2}},"b":{"Add":true}}`. Paths appear in sorted order.
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use json_diff::{diff, Delta};
+    ///
+    /// let delta = diff(&json!({"a": 1}), &json!({"a": 2, "b": true}));
+    /// let stored = delta.to_json_string();
+    /// assert_eq!(stored, r#"{"a":{"Modify":{"old":1,"new":2}},"b":{"Add":true}}"#);
+    /// assert_eq!(Delta::from_json_str(&stored).unwrap(), delta);
+    /// ```
+    pub fn to_json_string(&self) -> String {
+        serde_json::to_string(self).expect("deltas always serialize")
+    }
+
+    /// Parse a delta written by [`to_json_string`](Delta::to_json_string) (or any JSON in
+    /// the same format), failing with `JsonDiffError::Serialization` on malformed input.
+    pub fn from_json_str(s: &str) -> Result<Delta, JsonDiffError> {
+        Ok(serde_json::from_str(s)?)
+    }
+}
+
+/// Replace the last segment of `path` when `parse` accepts it, prefixing the index with
+/// `marker`.
+fn rekey(path: &str, parse: fn(&str) -> Option<usize>, marker: &str) -> String {
+    let (parent, last) = match path.rsplit_once('.') {
+        Some((parent, last)) => (Some(parent), last),
+        None => (None, path),
+    };
+    match (parse(last), parent) {
+        (Some(i), Some(parent)) => format!("{parent}.{marker}{i}"),
+        (Some(i), None) => format!("{marker}{i}"),
+        (None, _) => path.to_string(),
+    }
+}
+
+/// `later` recorded at `path` after `earlier`, as one change, or `None` if together they
+/// change nothing.
+fn compose(path: &str, earlier: Change, later: Change) -> Result<Option<Change>, JsonDiffError> {
+    let modify = |old: Value, new: Value| (old != new).then_some(Change::Modify { old, new });
+    let add = |n: &Number, by: &Number| {
+        number::add(n, by).ok_or_else(|| JsonDiffError::NumberOverflow {
+            path: path.to_string(),
+        })
+    };
+    Ok(match (earlier, later) {
+        (earlier, Change::Unchanged(_)) => Some(earlier),
+        (Change::Add(_), Change::Modify { new, .. } | Change::Set(new)) => Some(Change::Add(new)),
+        (Change::Add(_), Change::Remove(_)) => None,
+        (Change::Remove(old), Change::Add(new)) => modify(old, new),
+        (Change::Modify { old, .. }, Change::Modify { new, .. } | Change::Set(new)) => {
+            modify(old, new)
+        }
+        (Change::Modify { old, .. }, Change::Remove(_)) => Some(Change::Remove(old)),
+        (Change::NumberDelta { by: first }, Change::NumberDelta { by: then }) => {
+            let by = add(&first, &then)?;
+            (!number::is_zero(&by)).then_some(Change::NumberDelta { by })
+        }
+        (Change::Add(Value::Number(n)), Change::NumberDelta { by }) => {
+            Some(Change::Add(add(&n, &by)?.into()))
+        }
+        (
+            Change::Modify {
+                old,
+                new: Value::Number(n),
+            },
+            Change::NumberDelta { by },
+        ) => modify(old, add(&n, &by)?.into()),
+        (Change::ArrayOps(mut first), Change::ArrayOps(then)) => {
+            first.extend(then);
+            Some(Change::ArrayOps(first))
+        }
+        (Change::Reorder { previous, .. }, Change::Reorder { keys, .. }) => {
+            (keys != previous).then_some(Change::Reorder { keys, previous })
+        }
+        (_, later) => Some(later),
+    })
+}
+
+impl fmt::Debug for Delta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Deref for Delta {
+    type Target = BTreeMap<String, Change>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Delta {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<BTreeMap<String, Change>> for Delta {
+    fn from(map: BTreeMap<String, Change>) -> Self {
+        Delta(map)
+    }
+}
+
+impl From<Delta> for BTreeMap<String, Change> {
+    fn from(delta: Delta) -> Self {
+        delta.0
+    }
+}
+
+impl FromIterator<(String, Change)> for Delta {
+    fn from_iter<I: IntoIterator<Item = (String, Change)>>(iter: I) -> Self {
+        Delta(iter.into_iter().collect())
+    }
+}
+
+impl Extend<(String, Change)> for Delta {
+    fn extend<I: IntoIterator<Item = (String, Change)>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+impl IntoIterator for Delta {
+    type Item = (String, Change);
+    type IntoIter = btree_map::IntoIter<String, Change>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Delta {
+    type Item = (&'a String, &'a Change);
+    type IntoIter = btree_map::Iter<'a, String, Change>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Delta {
+    type Item = (&'a String, &'a mut Change);
+    type IntoIter = btree_map::IterMut<'a, String, Change>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeltaBuilder, DiffOptions, diff, diff_with};
+    use serde_json::{Number, json};
+
+    #[test]
+    fn map_access_and_serialization_are_unchanged() {
+        let delta = diff(&json!({"a": 1}), &json!({"a": 2}));
+        assert!(!delta.is_empty());
+        assert_eq!(delta.len(), 1);
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["a"]);
+
+        let json = serde_json::to_value(&delta).unwrap();
+        assert_eq!(json, json!({"a": {"Modify": {"old": 1, "new": 2}}}));
+        let back: Delta = serde_json::from_value(json).unwrap();
+        assert_eq!(back, delta);
+
+        let map: BTreeMap<String, Change> = delta.clone().into();
+        assert_eq!(Delta::from(map), delta);
+    }
+
+    #[test]
+    fn json_string_round_trip_still_applies() {
+        let before = json!({"user": {"name": "Ann", "tags": ["a"]}, "n": 1.5});
+        let after = json!({"user": {"name": "Bo", "tags": ["a", "b"]}, "flag": null});
+        let delta = diff(&before, &after);
+
+        let restored = Delta::from_json_str(&delta.to_json_string()).unwrap();
+        assert_eq!(restored, delta);
+        assert_eq!(restored.apply(&before), after);
+        assert!(matches!(
+            Delta::from_json_str(r#"{"a": {"Bogus": 1}}"#),
+            Err(JsonDiffError::Serialization { .. })
+        ));
+    }
+
+    #[test]
+    fn invert_undoes_object_and_array_changes() {
+        let before = json!({"a": 1, "gone": true, "tags": ["x", "y", "z"]});
+        let after = json!({"a": 2, "new": [1], "tags": ["w", "y", "z", "v"]});
+        let options = DiffOptions {
+            set_arrays: vec!["tags".to_string()],
+            ..Default::default()
+        };
+        let delta = diff_with(&before, &after, &options);
+
+        let inverse = delta.invert(&after);
+        assert_eq!(inverse.apply(&after), before);
+        assert_eq!(inverse.invert(&before), delta);
+    }
+
+    #[test]
+    fn invert_keeps_numeric_object_keys() {
+        let before = json!({"1": {}, "m": {"2": true}, "list": [0]});
+        let after = json!({"+3": 1, "m": {}, "list": [0, 1]});
+        let options = DiffOptions {
+            set_arrays: vec!["list".to_string()],
+            ..Default::default()
+        };
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(
+            delta.keys().collect::<Vec<_>>(),
+            ["+3", "1", "list.+1", "m.2"]
+        );
+
+        let inverse = delta.invert(&after);
+        assert_eq!(
+            inverse.keys().collect::<Vec<_>>(),
+            ["+3", "1", "list.1", "m.2"]
+        );
+        assert_eq!(inverse.apply(&after), before);
+    }
+
+    #[test]
+    fn invert_moves_back() {
+        let mut delta = Delta::new();
+        delta.insert(
+            "b".to_string(),
+            Change::Move {
+                from: "a".to_string(),
+                to: "b".to_string(),
+            },
+        );
+        let before = json!({"a": 1});
+        let after = delta.apply(&before);
+        assert_eq!(after, json!({"b": 1}));
+        assert_eq!(delta.invert(&after).apply(&after), before);
+    }
+
+    #[test]
+    fn merge_composes_successive_deltas() {
+        let v1 = json!({"a": 1, "b": 1, "c": 1});
+        let v2 = json!({"a": 2, "c": 1, "d": 1});
+        let v3 = json!({"a": 1, "b": 5, "d": 2});
+
+        let mut delta = diff(&v1, &v2);
+        delta.merge(diff(&v2, &v3)).unwrap();
+        assert_eq!(delta, diff(&v1, &v3));
+        assert_eq!(delta.apply(&v1), v3);
+        assert_eq!(delta.revert(&v3), v1);
+    }
+
+    #[test]
+    fn iter_natural_orders_indices_numerically() {
+        let before = json!({"arr": (0..12).collect::<Vec<_>>(), "b": 1});
+        let after = json!({"arr": [0, 1, 3, 4, 5, 6, 7, 8, 9, 11, 20, 30], "b": 2});
+        let options = DiffOptions {
+            set_arrays: vec!["arr".to_string()],
+            ..Default::default()
+        };
+        let delta = diff_with(&before, &after, &options);
+        // Plain map order compares strings.
+        assert_eq!(
+            delta.keys().collect::<Vec<_>>(),
+            ["arr.+10", "arr.+11", "arr.10", "arr.2", "b"]
+        );
+        assert_eq!(
+            delta
+                .iter_natural()
+                .map(|(path, _)| path)
+                .collect::<Vec<_>>(),
+            ["arr.2", "arr.10", "arr.+10", "arr.+11", "b"]
+        );
+    }
+
+    #[test]
+    fn merge_adds_up_number_deltas() {
+        let options = DiffOptions {
+            number_deltas: true,
+            ..Default::default()
+        };
+        let base = json!({"hits": 10, "score": 1.5});
+        let first = diff_with(&base, &json!({"hits": 13, "score": 2.0}), &options);
+        let second = diff_with(&base, &json!({"hits": 8, "score": 1.5}), &options);
+
+        let mut both = first.clone();
+        both.merge(second.clone()).unwrap();
+        let mut reversed = second;
+        reversed.merge(first).unwrap();
+        assert_eq!(both, reversed);
+        assert_eq!(both.apply(&base), json!({"hits": 11, "score": 2.0}));
+        assert_eq!(both.revert(&both.apply(&base)), base);
+
+        let mut cancelled = DeltaBuilder::new().increment("hits", 3).build();
+        cancelled
+            .merge(DeltaBuilder::new().increment("hits", -3).build())
+            .unwrap();
+        assert!(cancelled.is_empty());
+
+        let mut added = DeltaBuilder::new().add("hits", json!(1)).build();
+        added
+            .merge(DeltaBuilder::new().increment("hits", 4).build())
+            .unwrap();
+        assert_eq!(added, DeltaBuilder::new().add("hits", json!(5)).build());
+
+        let big = || Number::from_f64(1e308).unwrap();
+        let mut huge = DeltaBuilder::new().increment("x", big()).build();
+        let before = huge.clone();
+        let more = DeltaBuilder::new()
+            .increment("a", 1)
+            .increment("x", big())
+            .build();
+        assert_eq!(
+            huge.merge(more),
+            Err(JsonDiffError::NumberOverflow {
+                path: "x".to_string()
+            })
+        );
+        assert_eq!(huge, before);
+    }
+}
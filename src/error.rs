@@ -0,0 +1,186 @@
+//! The crate's error type.
+
+use std::fmt;
+
+/// An error returned by the fallible functions of this crate.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonDiffError {
+    /// The location a change targets (or, for `Move`/`Copy`, reads from) does not exist.
+    PathNotFound { path: String },
+    /// A change that must create `path` found it already present.
+    PathExists { path: String },
+    /// The path is malformed: it contains an empty segment (`a..b`), or is not spelled the
+    /// way its codec writes paths (see `PathCodec::is_valid`).
+    InvalidPath { path: String },
+    /// An array element index at the end of `path` is not valid for an array of length `len`.
+    /// For a negative index `-N`, `index` is `N`.
+    IndexOutOfBounds {
+        path: String,
+        index: usize,
+        len: usize,
+    },
+    /// The path has more segments than `ApplyOptions::max_apply_depth` (or, when
+    /// diffing, `DiffOptions::max_depth`) allows.
+    PathTooDeep { path: String, max_depth: usize },
+    /// The value found at `path` is not the one the operation expected (for a
+    /// `Change::NumberDelta`, not a number the amount can be added to).
+    StaleValue { path: String },
+    /// Numbers folded together at `path` by `Delta::merge` add up to more than an `f64`
+    /// can hold.
+    NumberOverflow { path: String },
+    /// A `Copy` could not be undone because the copied value at `to` no longer matches
+    /// the value at `from` (or one of them is missing).
+    IrreversibleCopy { from: String, to: String },
+    /// The change at `path` does not carry the old value needed to revert it.
+    Irreversible { path: String },
+    /// A diff found `count` changes, more than `DiffOptions::max_changes` allows.
+    TooManyChanges { count: usize, max_changes: usize },
+    /// The arrays at `path` cannot be compared element by element under
+    /// `DiffOptions::array_strategy` (for `ArrayStrategy::SameLengthObjects`, they differ
+    /// in length or hold something other than objects).
+    UnsupportedArray { path: String },
+    /// The change at `path` is a `Change::Opaque`, which records digests instead of
+    /// values and so cannot be applied.
+    Opaque { path: String },
+    /// A batch function was given different numbers of documents and deltas.
+    LengthMismatch { bases: usize, deltas: usize },
+    /// A patch document is malformed.
+    InvalidPatch { message: String },
+    /// Converting between typed data and JSON failed.
+    Serialization { message: String },
+}
+
+/// Former name of [`JsonDiffError`], kept so existing code keeps compiling.
+#[deprecated(note = "renamed to `JsonDiffError`")]
+pub type ApplyError = JsonDiffError;
+
+impl fmt::Display for JsonDiffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonDiffError::PathNotFound { path } => write!(f, "path `{path}` not found"),
+            JsonDiffError::PathExists { path } => write!(f, "path `{path}` already exists"),
+            JsonDiffError::InvalidPath { path } => {
+                write!(f, "invalid path `{path}`")
+            }
+            JsonDiffError::IndexOutOfBounds { path, index, len } => write!(
+                f,
+                "index {index} out of bounds at `{path}` (array length {len})"
+            ),
+            JsonDiffError::PathTooDeep { path, max_depth } => {
+                write!(f, "path `{path}` is deeper than {max_depth} segments")
+            }
+            JsonDiffError::StaleValue { path } => {
+                write!(f, "value at `{path}` does not match the expected value")
+            }
+            JsonDiffError::NumberOverflow { path } => {
+                write!(f, "numbers at `{path}` add up to more than an f64 can hold")
+            }
+            JsonDiffError::IrreversibleCopy { from, to } => write!(
+                f,
+                "cannot revert copy from `{from}` to `{to}`: target no longer matches source"
+            ),
+            JsonDiffError::Irreversible { path } => {
+                write!(f, "cannot revert `{path}`: the old value was not captured")
+            }
+            JsonDiffError::Opaque { path } => {
+                write!(
+                    f,
+                    "cannot apply `{path}`: only digests of the values were captured"
+                )
+            }
+            JsonDiffError::TooManyChanges { count, max_changes } => {
+                write!(
+                    f,
+                    "diff has {count} changes, more than the limit of {max_changes}"
+                )
+            }
+            JsonDiffError::UnsupportedArray { path } => {
+                write!(
+                    f,
+                    "arrays at `{path}` cannot be compared element by element"
+                )
+            }
+            JsonDiffError::LengthMismatch { bases, deltas } => {
+                write!(f, "batch has {bases} documents but {deltas} deltas")
+            }
+            JsonDiffError::InvalidPatch { message } => write!(f, "invalid patch: {message}"),
+            JsonDiffError::Serialization { message } => {
+                write!(f, "serialization failed: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for JsonDiffError {}
+
+impl From<serde_json::Error> for JsonDiffError {
+    fn from(error: serde_json::Error) -> Self {
+        JsonDiffError::Serialization {
+            message: error.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_names_the_offending_path() {
+        let errors = [
+            JsonDiffError::PathNotFound {
+                path: "user.name".to_string(),
+            },
+            JsonDiffError::IndexOutOfBounds {
+                path: "user.name".to_string(),
+                index: 4,
+                len: 2,
+            },
+            JsonDiffError::StaleValue {
+                path: "user.name".to_string(),
+            },
+            JsonDiffError::InvalidPath {
+                path: "user.name".to_string(),
+            },
+            JsonDiffError::PathExists {
+                path: "user.name".to_string(),
+            },
+            JsonDiffError::Irreversible {
+                path: "user.name".to_string(),
+            },
+            JsonDiffError::PathTooDeep {
+                path: "user.name".to_string(),
+                max_depth: 1,
+            },
+            JsonDiffError::Opaque {
+                path: "user.name".to_string(),
+            },
+            JsonDiffError::NumberOverflow {
+                path: "user.name".to_string(),
+            },
+        ];
+        for error in errors {
+            assert!(error.to_string().contains("`user.name`"), "{error}");
+        }
+        let copy = JsonDiffError::IrreversibleCopy {
+            from: "a".to_string(),
+            to: "b".to_string(),
+        };
+        assert!(copy.to_string().contains("`a`") && copy.to_string().contains("`b`"));
+        let limit = JsonDiffError::TooManyChanges {
+            count: 12,
+            max_changes: 10,
+        };
+        assert_eq!(
+            limit.to_string(),
+            "diff has 12 changes, more than the limit of 10"
+        );
+    }
+
+    #[test]
+    fn serde_errors_convert() {
+        let error: JsonDiffError = serde_json::from_str::<u32>("\"x\"").unwrap_err().into();
+        assert!(matches!(error, JsonDiffError::Serialization { .. }));
+        assert!(error.to_string().starts_with("serialization failed: "));
+    }
+}
@@ -0,0 +1,74 @@
+//! Method-call syntax for diffing and applying on `serde_json::Value`.
+
+use crate::{Delta, apply, apply_in_place, diff, revert};
+use serde_json::Value;
+
+/// Extension methods on [`Value`] delegating to the crate's free functions.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::JsonDiffExt;
+///
+/// let before = json!({"count": 1});
+/// let after = json!({"count": 2, "done": true});
+///
+/// let delta = before.diff(&after);
+/// assert_eq!(before.apply(&delta), after);
+///
+/// let mut doc = before.clone();
+/// doc.apply_mut(&delta);
+/// assert_eq!(doc, after);
+/// assert_eq!(doc.revert(&delta), before);
+/// ```
+pub trait JsonDiffExt {
+    /// Compute the delta from `self` to `other`; see [`diff`].
+    fn diff(&self, other: &Value) -> Delta;
+
+    /// Return a copy of `self` with `delta` applied; see [`apply`].
+    fn apply(&self, delta: &Delta) -> Value;
+
+    /// Apply `delta` to `self` in place; see [`apply_in_place`].
+    fn apply_mut(&mut self, delta: &Delta);
+
+    /// Return a copy of `self` with `delta` reverted; see [`revert`].
+    fn revert(&self, delta: &Delta) -> Value;
+}
+
+impl JsonDiffExt for Value {
+    fn diff(&self, other: &Value) -> Delta {
+        diff(self, other)
+    }
+
+    fn apply(&self, delta: &Delta) -> Value {
+        apply(self, delta)
+    }
+
+    fn apply_mut(&mut self, delta: &Delta) {
+        apply_in_place(self, delta)
+    }
+
+    fn revert(&self, delta: &Delta) -> Value {
+        revert(self, delta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn methods_match_free_functions() {
+        let before = json!({"user": {"name": "Ann"}, "tags": ["a"]});
+        let after = json!({"user": {"name": "Bo", "age": 3}, "tags": []});
+
+        let delta = before.diff(&after);
+        assert_eq!(delta, diff(&before, &after));
+        assert_eq!(before.apply(&delta), after);
+        assert_eq!(after.revert(&delta), before);
+
+        let mut doc = before.clone();
+        doc.apply_mut(&delta);
+        assert_eq!(doc, after);
+    }
+}
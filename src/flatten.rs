@@ -0,0 +1,141 @@
+//! Enumerating and flattening the leaves of a document.
+
+use crate::DotCodec;
+use crate::apply::{Edit, set_value};
+use crate::path::join_path;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Dot-paths of every leaf of `value`, in document order: keys sorted, or in insertion
+/// order under the `preserve_order` feature.
+///
+/// Objects are descended into; everything else is a leaf. Like `diff`, arrays are treated
+/// as single values, so `{"tags": ["a", "b"]}` yields just `tags`. Empty objects are
+/// leaves too, so no part of the document goes unlisted. A scalar (or array) root yields
+/// the empty path.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::leaf_paths;
+///
+/// let doc = json!({"user": {"name": "Ann", "roles": ["admin"]}, "active": true});
+/// let mut paths = leaf_paths(&doc);
+/// paths.sort(); // the order depends on `preserve_order`
+/// assert_eq!(paths, ["active", "user.name", "user.roles"]);
+/// ```
+pub fn leaf_paths(value: &Value) -> Vec<String> {
+    let mut paths = Vec::new();
+    visit_leaves(value, String::new(), &mut |path, _| paths.push(path));
+    paths
+}
+
+/// Map each leaf path of `value` (see [`leaf_paths`]) to the leaf found there.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{flatten, unflatten};
+///
+/// let doc = json!({"db": {"host": "localhost", "port": 5432}, "tags": ["a"]});
+/// let flat = flatten(&doc);
+/// assert_eq!(flat["db.port"], json!(5432));
+/// assert_eq!(flat["tags"], json!(["a"]));
+/// assert_eq!(unflatten(&flat), doc);
+/// ```
+pub fn flatten(value: &Value) -> BTreeMap<String, Value> {
+    let mut map = BTreeMap::new();
+    visit_leaves(value, String::new(), &mut |path, leaf| {
+        map.insert(path, leaf.clone());
+    });
+    map
+}
+
+/// Rebuild a document from a path→value map, the inverse of [`flatten`].
+///
+/// Each entry is written the way `apply` writes an `Add`, creating intermediate objects
+/// as needed, so `unflatten(&flatten(&v)) == v` for any document whose keys contain no
+/// `.` and are not empty. Segments are always read as object keys here: `items.0` yields
+/// `{"items": {"0": ...}}`, not an array. An empty map yields `{}`.
+pub fn unflatten(map: &BTreeMap<String, Value>) -> Value {
+    let mut root = Value::Object(Map::new());
+    for (path, value) in map {
+        // Lenient mode never fails.
+        let _ = set_value(
+            &mut root,
+            path,
+            Edit::Insert(value.clone()),
+            false,
+            &DotCodec,
+        );
+    }
+    root
+}
+
+/// Call `f` with the path and value of every leaf of `value` (see [`leaf_paths`]).
+pub(crate) fn visit_leaves(value: &Value, path: String, f: &mut impl FnMut(String, &Value)) {
+    match value {
+        Value::Object(obj) if !obj.is_empty() => {
+            for (key, child) in obj {
+                visit_leaves(child, join_path(&path, key), f);
+            }
+        }
+        _ => f(path, value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn lists_nested_object_leaves() {
+        let doc = json!({"a": {"b": {"c": 1, "d": null}, "e": "x"}, "f": {}});
+        assert_eq!(leaf_paths(&doc), ["a.b.c", "a.b.d", "a.e", "f"]);
+        assert_eq!(leaf_paths(&json!(3)), [""]);
+    }
+
+    #[test]
+    fn flatten_round_trips_nested_objects() {
+        let doc = json!({
+            "server": {"http": {"port": 80, "tls": false}, "name": "edge"},
+            "limits": {},
+            "owner": null
+        });
+        let flat = flatten(&doc);
+        assert_eq!(
+            flat.keys().collect::<Vec<_>>(),
+            [
+                "limits",
+                "owner",
+                "server.http.port",
+                "server.http.tls",
+                "server.name"
+            ]
+        );
+        assert_eq!(unflatten(&flat), doc);
+        assert_eq!(unflatten(&BTreeMap::new()), json!({}));
+    }
+
+    #[test]
+    fn flatten_keeps_arrays_whole() {
+        let doc = json!({"matrix": [[1, 2], [3]], "rows": [{"id": 1}]});
+        let flat = flatten(&doc);
+        assert_eq!(flat["rows"], json!([{"id": 1}]));
+        assert_eq!(unflatten(&flat), doc);
+
+        let mut indexed = BTreeMap::new();
+        indexed.insert("items.0".to_string(), json!("a"));
+        assert_eq!(unflatten(&indexed), json!({"items": {"0": "a"}}));
+    }
+
+    #[test]
+    fn arrays_are_single_leaves() {
+        let doc = json!({"items": [{"id": 1}, {"id": 2}], "count": 2});
+        let expected = if cfg!(feature = "preserve_order") {
+            ["items", "count"]
+        } else {
+            ["count", "items"]
+        };
+        assert_eq!(leaf_paths(&doc), expected);
+    }
+}
@@ -0,0 +1,138 @@
+//! Stable, dependency-free hashing of JSON values and deltas.
+
+use crate::Delta;
+use serde_json::Value;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// 64-bit FNV-1a over `bytes`, continuing from `state`.
+pub(crate) fn fnv1a(state: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(state, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Structural hash of `value`, stable across runs, platforms and object key order.
+///
+/// Equal values always hash equally; integers and floats hash differently, matching
+/// `Value`'s own equality (`1` and `1.0` differ).
+pub(crate) fn hash_value(value: &Value) -> u64 {
+    match value {
+        Value::Null => fnv1a(FNV_OFFSET, b"n"),
+        Value::Bool(b) => fnv1a(FNV_OFFSET, if *b { b"t" } else { b"f" }),
+        Value::Number(n) => {
+            if let Some(u) = n.as_u64() {
+                fnv1a(fnv1a(FNV_OFFSET, b"u"), &u.to_le_bytes())
+            } else if let Some(i) = n.as_i64() {
+                fnv1a(fnv1a(FNV_OFFSET, b"i"), &i.to_le_bytes())
+            } else {
+                // `-0.0 == 0.0`, so both must hash alike.
+                let f = n.as_f64().unwrap_or_default() + 0.0;
+                fnv1a(fnv1a(FNV_OFFSET, b"f"), &f.to_bits().to_le_bytes())
+            }
+        }
+        Value::String(s) => fnv1a(fnv1a(FNV_OFFSET, b"s"), s.as_bytes()),
+        Value::Array(items) => items.iter().fold(fnv1a(FNV_OFFSET, b"["), |hash, item| {
+            fnv1a(hash, &hash_value(item).to_le_bytes())
+        }),
+        Value::Object(obj) => {
+            // Entries are combined commutatively so key order cannot matter.
+            let entries = obj.iter().fold(0u64, |sum, (key, child)| {
+                sum.wrapping_add(hash_entry(key, hash_value(child)))
+            });
+            object_hash(entries)
+        }
+    }
+}
+
+/// A short textual digest of `value`: its [`hash_value`] as 16 hex digits.
+pub(crate) fn digest(value: &Value) -> String {
+    format!("{:016x}", hash_value(value))
+}
+
+/// Final hash of an object from the wrapping sum of its `hash_entry` values.
+pub(crate) fn object_hash(entries: u64) -> u64 {
+    fnv1a(fnv1a(FNV_OFFSET, b"{"), &entries.to_le_bytes())
+}
+
+/// Hash of one object entry, given the hash of its value.
+pub(crate) fn hash_entry(key: &str, child: u64) -> u64 {
+    fnv1a(fnv1a(FNV_OFFSET, key.as_bytes()), &child.to_le_bytes())
+}
+
+/// A content hash of `delta`, for deduplicating deltas or keying caches by them.
+///
+/// The hash covers every path and the full contents of its change: equal deltas always
+/// hash equally, and any difference in a path, a change kind or a value changes the hash
+/// (barring collisions of the 64-bit hash). It is computed with FNV-1a over the paths in
+/// order and the structural hash of each change in its serialized form (as written by
+/// [`Delta::to_json_string`]), so it does not depend on the platform, the process, or the
+/// key order of objects within values.
+///
+/// The hash is stable across crate versions: a delta hashes the same in every release
+/// that serializes it the same way. Changing how it is computed, or the serialized form of
+/// an existing change, is a breaking change and would be called out in the changelog.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{delta_hash, diff};
+///
+/// let a = diff(&json!({"n": 1}), &json!({"n": 2}));
+/// let b = diff(&json!({"n": 1, "x": true}), &json!({"n": 2, "x": true}));
+/// assert_eq!(delta_hash(&a), delta_hash(&b));
+/// assert_ne!(delta_hash(&a), delta_hash(&diff(&json!({"n": 1}), &json!({"n": 3}))));
+/// ```
+pub fn delta_hash(delta: &Delta) -> u64 {
+    delta
+        .iter()
+        .fold(fnv1a(FNV_OFFSET, b"delta"), |hash, (path, change)| {
+            let change = serde_json::to_value(change).unwrap_or(Value::Null);
+            let hash = fnv1a(hash, &(path.len() as u64).to_le_bytes());
+            let hash = fnv1a(hash, path.as_bytes());
+            fnv1a(hash, &hash_value(&change).to_le_bytes())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn hashes_are_structural_and_stable() {
+        assert_eq!(fnv1a(FNV_OFFSET, b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(
+            hash_value(&json!({"a": [1, "x"], "b": null})),
+            hash_value(&json!({"b": null, "a": [1, "x"]}))
+        );
+        assert_ne!(hash_value(&json!([1, 2])), hash_value(&json!([2, 1])));
+        assert_ne!(hash_value(&json!({"a": 1})), hash_value(&json!({"a": "1"})));
+        assert_ne!(hash_value(&json!(1)), hash_value(&json!(1.0)));
+        assert_eq!(hash_value(&json!(-0.0)), hash_value(&json!(0.0)));
+    }
+
+    #[test]
+    fn delta_hashes_follow_content() {
+        let delta = crate::diff(&json!({"a": {"x": 1, "y": 2}, "b": 1}), &json!({"b": 2}));
+        let same = crate::DeltaBuilder::new()
+            .remove("a", json!({"y": 2, "x": 1}))
+            .modify("b", json!(1), json!(2))
+            .build();
+        assert_eq!(delta_hash(&delta), delta_hash(&same));
+        // Pinned so an accidental change to the hash shows up here.
+        assert_eq!(delta_hash(&delta), 0x8d94_bb11_c71e_30ed);
+
+        let other = crate::DeltaBuilder::new()
+            .remove("a", json!({"y": 2, "x": 1}))
+            .modify("b", json!(1), json!(3))
+            .build();
+        assert_ne!(delta_hash(&delta), delta_hash(&other));
+        let moved = crate::DeltaBuilder::new()
+            .remove("a", json!({"y": 2, "x": 1}))
+            .modify("c", json!(1), json!(2))
+            .build();
+        assert_ne!(delta_hash(&delta), delta_hash(&moved));
+        assert_ne!(delta_hash(&Delta::new()), delta_hash(&delta));
+    }
+}
@@ -0,0 +1,362 @@
+//! Deltas with interned paths or values.
+
+use crate::hash::hash_value;
+use crate::{Change, ChangeSink, Delta, DiffOptions, DotCodec, compare};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// A path stored as its segments, each shared through a [`PathInterner`].
+///
+/// Cloning a `SegmentPath` copies pointers rather than the text of every segment, so
+/// thousands of deeply nested paths share one copy of each distinct key. `Display`
+/// renders the usual dot-path.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SegmentPath(Vec<Rc<str>>);
+
+impl SegmentPath {
+    /// Split a dot-path into interned segments. The empty path has no segments.
+    pub fn parse(path: &str, interner: &mut PathInterner) -> Self {
+        if path.is_empty() {
+            return SegmentPath::default();
+        }
+        SegmentPath(path.split('.').map(|s| interner.intern(s)).collect())
+    }
+
+    /// The path's segments, outermost first.
+    pub fn segments(&self) -> &[Rc<str>] {
+        &self.0
+    }
+}
+
+impl fmt::Display for SegmentPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            f.write_str(segment)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deduplicates path segments so equal keys share one allocation.
+#[derive(Debug, Default)]
+pub struct PathInterner {
+    segments: HashSet<Rc<str>>,
+}
+
+impl PathInterner {
+    /// Create an empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the shared copy of `segment`, storing it on first use.
+    pub fn intern(&mut self, segment: &str) -> Rc<str> {
+        if let Some(existing) = self.segments.get(segment) {
+            return Rc::clone(existing);
+        }
+        let segment: Rc<str> = Rc::from(segment);
+        self.segments.insert(Rc::clone(&segment));
+        segment
+    }
+
+    /// Number of distinct segments stored.
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Whether no segment has been interned yet.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+/// A `Delta` keyed by [`SegmentPath`]s instead of flat strings.
+///
+/// Use [`diff_interned`] to build one directly, or convert from and to a [`Delta`] with
+/// [`InternedDelta::from_delta`] and [`InternedDelta::to_delta`].
+#[derive(Debug, Default)]
+pub struct InternedDelta {
+    /// The changes, keyed by structured path.
+    pub changes: BTreeMap<SegmentPath, Change>,
+    /// The interner holding every segment used by `changes`.
+    pub interner: PathInterner,
+}
+
+impl InternedDelta {
+    /// Convert a string-keyed delta, interning the segments of every path.
+    pub fn from_delta(delta: &Delta) -> Self {
+        let mut interner = PathInterner::new();
+        let changes = delta
+            .iter()
+            .map(|(path, change)| (SegmentPath::parse(path, &mut interner), change.clone()))
+            .collect();
+        InternedDelta { changes, interner }
+    }
+
+    /// Convert back to a string-keyed `Delta`.
+    pub fn to_delta(&self) -> Delta {
+        self.changes
+            .iter()
+            .map(|(path, change)| (path.to_string(), change.clone()))
+            .collect()
+    }
+}
+
+/// Like [`diff`](crate::diff), but keyed by interned [`SegmentPath`]s.
+///
+/// The walk is `diff`'s own; each changed path is split into interned segments once, as
+/// it is recorded. `diff_interned(a, b).to_delta() == diff(a, b)` for all inputs.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff, diff_interned};
+///
+/// let before = json!({"a": {"x": 1}, "b": {"x": 1}});
+/// let after = json!({"a": {"x": 2}, "b": {"x": 3}});
+/// let interned = diff_interned(&before, &after);
+/// assert_eq!(interned.interner.len(), 3); // "a", "b" and a single shared "x"
+/// assert_eq!(interned.to_delta(), diff(&before, &after));
+/// ```
+pub fn diff_interned(before: &Value, after: &Value) -> InternedDelta {
+    let mut delta = InternedDelta::default();
+    let options = DiffOptions::default();
+    compare(
+        &mut delta,
+        &options,
+        &DotCodec,
+        String::new(),
+        before,
+        after,
+    );
+    delta
+}
+
+impl ChangeSink for InternedDelta {
+    fn record(&mut self, path: String, change: Change) {
+        let path = SegmentPath::parse(&path, &mut self.interner);
+        self.changes.insert(path, change);
+    }
+}
+
+/// Deduplicates values so equal ones share one allocation.
+#[derive(Debug, Default)]
+pub struct ValueInterner {
+    values: HashMap<u64, Vec<Arc<Value>>>,
+}
+
+impl ValueInterner {
+    /// Create an empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the shared copy of `value`, storing it on first use.
+    pub fn intern(&mut self, value: Value) -> Arc<Value> {
+        let bucket = self.values.entry(hash_value(&value)).or_default();
+        if let Some(existing) = bucket.iter().find(|existing| ***existing == value) {
+            return Arc::clone(existing);
+        }
+        let value = Arc::new(value);
+        bucket.push(Arc::clone(&value));
+        value
+    }
+
+    /// Number of distinct values stored.
+    pub fn len(&self) -> usize {
+        self.values.values().map(Vec::len).sum()
+    }
+
+    /// Whether no value has been interned yet.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+/// A [`Change`] whose values are shared through a [`ValueInterner`].
+///
+/// The changes that carry whole values have a variant holding them behind `Arc`s; the
+/// others are kept as they are in `Other`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SharedChange {
+    /// A shared [`Change::Add`]: the value was added at the path.
+    Add(Arc<Value>),
+    /// A shared [`Change::Remove`]: the value was removed from the path.
+    Remove(Arc<Value>),
+    /// A shared [`Change::Modify`]: the value was modified, `old` → `new`.
+    Modify { old: Arc<Value>, new: Arc<Value> },
+    /// A shared [`Change::Set`]: the value was overwritten, without the old value.
+    Set(Arc<Value>),
+    /// A shared [`Change::Unchanged`]: a watched path still holds the value.
+    Unchanged(Arc<Value>),
+    /// Any other change, such as a `Move` or a `NumberDelta`.
+    Other(Change),
+}
+
+impl SharedChange {
+    fn new(change: Change, interner: &mut ValueInterner) -> Self {
+        match change {
+            Change::Add(v) => SharedChange::Add(interner.intern(v)),
+            Change::Remove(v) => SharedChange::Remove(interner.intern(v)),
+            Change::Modify { old, new } => SharedChange::Modify {
+                old: interner.intern(old),
+                new: interner.intern(new),
+            },
+            Change::Set(v) => SharedChange::Set(interner.intern(v)),
+            Change::Unchanged(v) => SharedChange::Unchanged(interner.intern(v)),
+            other => SharedChange::Other(other),
+        }
+    }
+
+    /// Convert to a plain `Change`, copying the shared values.
+    pub fn to_change(&self) -> Change {
+        let owned = |value: &Arc<Value>| Value::clone(value);
+        match self {
+            SharedChange::Add(v) => Change::Add(owned(v)),
+            SharedChange::Remove(v) => Change::Remove(owned(v)),
+            SharedChange::Modify { old, new } => Change::Modify {
+                old: owned(old),
+                new: owned(new),
+            },
+            SharedChange::Set(v) => Change::Set(owned(v)),
+            SharedChange::Unchanged(v) => Change::Unchanged(owned(v)),
+            SharedChange::Other(change) => change.clone(),
+        }
+    }
+}
+
+/// A `Delta` whose equal values share one allocation, for documents that repeat large
+/// values (the same config object under many keys, say).
+///
+/// Use [`diff_shared`] to build one, or [`SharedDelta::from_delta`] to convert an existing
+/// delta; [`SharedDelta::to_delta`] converts back to the plain owned form, e.g. to apply it.
+#[derive(Debug, Default)]
+pub struct SharedDelta {
+    /// The changes, keyed by path.
+    pub changes: BTreeMap<String, SharedChange>,
+    /// The interner holding every value used by `changes`.
+    pub interner: ValueInterner,
+}
+
+impl SharedDelta {
+    /// Convert a delta, moving its values into the interner so equal ones are stored once.
+    pub fn from_delta(delta: Delta) -> Self {
+        let mut interner = ValueInterner::new();
+        let changes = delta
+            .into_inner()
+            .into_iter()
+            .map(|(path, change)| (path, SharedChange::new(change, &mut interner)))
+            .collect();
+        SharedDelta { changes, interner }
+    }
+
+    /// Convert back to a plain `Delta`, copying each shared value where it is used.
+    pub fn to_delta(&self) -> Delta {
+        self.changes
+            .iter()
+            .map(|(path, change)| (path.clone(), change.to_change()))
+            .collect()
+    }
+}
+
+impl ChangeSink for SharedDelta {
+    fn record(&mut self, path: String, change: Change) {
+        let change = SharedChange::new(change, &mut self.interner);
+        self.changes.insert(path, change);
+    }
+}
+
+/// Like [`diff`](crate::diff), with equal values of the delta shared (see [`SharedDelta`]).
+///
+/// Each value is interned as soon as the diff captures it, so a value repeated across the
+/// delta is held once while diffing too, not only in the result.
+///
+/// `diff_shared(a, b).to_delta() == diff(a, b)` for all inputs.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff, diff_shared};
+///
+/// let config = json!({"retries": 3, "timeout": 30});
+/// let after = json!({"a": config, "b": config, "c": config});
+/// let shared = diff_shared(&json!({}), &after);
+/// assert_eq!(shared.changes.len(), 3);
+/// assert_eq!(shared.interner.len(), 1);
+/// assert_eq!(shared.to_delta(), diff(&json!({}), &after));
+/// ```
+pub fn diff_shared(before: &Value, after: &Value) -> SharedDelta {
+    let mut shared = SharedDelta::default();
+    let options = DiffOptions::default();
+    compare(
+        &mut shared,
+        &options,
+        &DotCodec,
+        String::new(),
+        before,
+        after,
+    );
+    shared
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{apply, diff};
+    use serde_json::json;
+
+    #[test]
+    fn conversion_round_trips() {
+        let before =
+            json!({"users": {"u1": {"name": "a", "tags": [1]}, "u2": {"name": "b"}}, "v": 1});
+        let after =
+            json!({"users": {"u1": {"name": "c", "tags": [2]}, "u3": {"name": "b"}}, "w": 1});
+        let delta = diff(&before, &after);
+
+        let interned = InternedDelta::from_delta(&delta);
+        assert_eq!(interned.to_delta(), delta);
+        assert_eq!(diff_interned(&before, &after).to_delta(), delta);
+    }
+
+    #[test]
+    fn repeated_segments_share_storage() {
+        let mut interner = PathInterner::new();
+        let a = SegmentPath::parse("items.k1.name", &mut interner);
+        let b = SegmentPath::parse("items.k2.name", &mut interner);
+        assert_eq!(interner.len(), 4);
+        assert!(Rc::ptr_eq(&a.segments()[2], &b.segments()[2]));
+        assert_eq!(a.to_string(), "items.k1.name");
+        assert!(SegmentPath::parse("", &mut interner).segments().is_empty());
+    }
+
+    #[test]
+    fn repeated_values_share_storage() {
+        let subtree = json!({"pool": {"min": 1, "max": 10}, "hosts": ["a", "b"]});
+        let before = json!({"keep": 1, "gone": subtree});
+        let mut after = json!({"keep": 2});
+        for i in 0..10 {
+            after[format!("k{i}")] = subtree.clone();
+        }
+        let shared = diff_shared(&before, &after);
+        assert_eq!(shared.changes.len(), 12);
+        // The subtree (added ten times and removed once), 1 and 2.
+        assert_eq!(shared.interner.len(), 3);
+        let (SharedChange::Add(first), SharedChange::Add(last), SharedChange::Remove(removed)) = (
+            &shared.changes["k0"],
+            &shared.changes["k9"],
+            &shared.changes["gone"],
+        ) else {
+            panic!("expected adds and a remove");
+        };
+        assert!(Arc::ptr_eq(first, last) && Arc::ptr_eq(first, removed));
+
+        let delta = shared.to_delta();
+        assert_eq!(delta, diff(&before, &after));
+        assert_eq!(apply(&before, &delta), after);
+        assert!(ValueInterner::new().is_empty());
+    }
+}
@@ -21,13 +21,144 @@
 //! let reverted = revert(&after, &delta);
 //! assert_eq!(reverted, before);
 //! ```
+//!
+//! ## Array element paths
+//!
+//! Arrays are compared as a whole by default. When an option diffs them element by element
+//! (such as `DiffOptions::set_arrays`), a numeric segment addresses an element of the
+//! **old** array, while a `+`-prefixed segment addresses a position in the **new** array:
+//!
+//! - `tags.2` with `Change::Remove` removes the element at index 2 of the old array;
+//! - `tags.+3` with `Change::Add` inserts an element so that it ends up at index 3.
+//!
+//! `apply` performs in-place edits first, then removals of object keys (deepest first), then
+//! removals of elements from the highest old index down, then insertions from the lowest new
+//! index up, so both kinds of index stay valid while the array changes underneath them. A
+//! hand-written `Add` at a plain index inserts there too.
+//!
+//! A final `-` segment addresses the end of an array, as in RFC 6902: `Add` at `log.-`
+//! appends (creating `log` as a one-element array if it is missing) and `Remove` at
+//! `log.-` removes the last element. On objects, `-` is an ordinary key.
 
+use hash::{digest, hash_entry, hash_value, object_hash};
+use options::Scope;
+use path::{get_segments, is_within, join_path};
 use serde::{Deserialize, Serialize};
-use serde_json::{Map, Value};
-use std::collections::{BTreeMap, HashSet};
+use serde_json::{Number, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+mod apply;
+mod array_ops;
+mod batch;
+mod builder;
+mod cache;
+mod category;
+mod codec;
+mod concurrent;
+mod container;
+mod convert;
+mod delta;
+mod error;
+mod ext;
+mod flatten;
+mod hash;
+mod interned;
+mod merge;
+mod normalize;
+mod number;
+mod operation;
+mod options;
+mod ordered;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod patch;
+mod path;
+mod query;
+mod reorder;
+mod shape;
+mod similarity;
+mod snapshot;
+mod span;
+mod stats;
+mod stream;
+mod template;
+#[cfg(feature = "testing")]
+mod testing;
+#[cfg(feature = "toml")]
+mod toml;
+mod trace;
+mod transform;
+mod unified;
+mod visitor;
+#[cfg(feature = "yaml")]
+mod yaml;
+
+#[cfg(feature = "profiling")]
+pub use apply::apply_profiled;
+pub use apply::{
+    ApplyReport, apply, apply_checked, apply_cow, apply_in_place, apply_in_place_txn,
+    apply_non_destructive, apply_ordered, apply_report, apply_with, apply_with_codec,
+    apply_with_undo, revert, revert_with_codec, try_apply, try_apply_with, try_revert,
+};
+pub use array_ops::ArrayOp;
+pub use batch::{apply_batch, diff_batch};
+pub use builder::DeltaBuilder;
+pub use cache::DiffCache;
+pub use category::{ChangeCategory, categorize};
+pub use codec::{BracketCodec, DotCodec, EscapedDotCodec, JsonPointerCodec, PathCodec};
+pub use concurrent::transform;
+pub use container::{ContainerKind, diff_with_containers, is_index_map};
+pub use convert::{apply_bytes, apply_into, diff_bytes, diff_serializable};
+pub use delta::Delta;
+#[allow(deprecated)]
+pub use error::{ApplyError, JsonDiffError};
+pub use ext::JsonDiffExt;
+pub use flatten::{flatten, leaf_paths, unflatten};
+pub use hash::delta_hash;
+pub use interned::{
+    InternedDelta, PathInterner, SegmentPath, SharedChange, SharedDelta, ValueInterner,
+    diff_interned, diff_shared,
+};
+pub use merge::{common, deep_merge};
+pub use normalize::normalize;
+pub use operation::{Operation, from_operations, to_operations};
+pub use options::{
+    ApplyOptions, ArrayStrategy, DiffOptions, JsonType, ValuePredicate, round_significant,
+};
+pub use ordered::diff_ordered;
+#[cfg(feature = "rayon")]
+pub use parallel::diff_parallel;
+pub use patch::apply_json_patch;
+pub use path::{get_path, get_path_mut, path_segments, path_segments_with};
+pub use query::{
+    DeltaChange, Presence, SymDiff, adds, diff_deltas, group_by_top_level, modifies,
+    presence_changes, removes, symmetric_diff,
+};
+pub use shape::check_shape;
+pub use similarity::{distance, similarity};
+pub use snapshot::to_snapshot;
+pub use span::{Span, Spans, diff_with_spans};
+pub use stats::{DeltaStats, estimated_size, stats, stats_by_section};
+pub use stream::apply_streaming;
+pub use template::apply_template;
+#[cfg(feature = "testing")]
+pub use testing::{assert_delta_eq, assert_round_trip};
+#[cfg(feature = "toml")]
+pub use toml::diff_toml;
+pub use transform::{coalesce_ranges, compact, map_paths, redact};
+pub use unified::unified_diff;
+pub use visitor::{DeltaVisitor, walk};
+#[cfg(feature = "yaml")]
+pub use yaml::{apply_yaml, diff_yaml};
 
 /// Represents a single JSON change.
+///
+/// The enum is `#[non_exhaustive]`, so new kinds of change can be added without a breaking
+/// release; `match`es outside this crate need a wildcard arm. Code that only cares about
+/// the shape of a change can use [`kind`](Change::kind) and the accessors instead.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum Change {
     /// A value was added at the specified path.
     Add(Value),
@@ -35,118 +166,933 @@ pub enum Change {
     Remove(Value),
     /// A value was modified: `old` → `new`.
     Modify { old: Value, new: Value },
+    /// The value at path `from` was moved to path `to` (keyed by `to` in a `Delta`).
+    Move { from: String, to: String },
+    /// The value at path `from` was copied to path `to` (keyed by `to` in a `Delta`).
+    Copy { from: String, to: String },
+    /// The key at path `from` was renamed to `to` within the same object, keeping its value
+    /// (keyed by `to` in a `Delta`). Applied like a `Move`.
+    Rename { from: String, to: String },
+    /// A value was overwritten with the given one; the old value was not captured (see
+    /// `DiffOptions::capture_old`), so this change cannot be reverted.
+    Set(Value),
+    /// A watched path (see `DiffOptions::watched`) was examined and still holds this
+    /// value. Applying or reverting it is a no-op.
+    Unchanged(Value),
+    /// The number at the path was increased by `by` (decreased, if negative); see
+    /// `DiffOptions::number_deltas`. Applying adds `by` to the current value and reverting
+    /// subtracts it, so increments from concurrent deltas compose additively.
+    NumberDelta { by: Number },
+    /// A value larger than `DiffOptions::max_value_size` changed. `old` and `new` are
+    /// digests of the two values rather than the values themselves, so the change records
+    /// that the path differs without the cost of capturing it, and can be neither applied
+    /// nor reverted.
+    Opaque { old: String, new: String },
+    /// The array at the path was edited by an ordered script of steps (see [`ArrayOp`]),
+    /// replayed one after another; see `ArrayStrategy::Ops`.
+    ArrayOps(Vec<ArrayOp>),
+    /// The keys of the object at the path were reordered from `previous` to `keys`, with
+    /// no change to their values; see `DiffOptions::detect_key_reorder` (`preserve_order`
+    /// feature). Without that feature objects keep their keys sorted, so applying or
+    /// reverting it changes nothing.
+    Reorder {
+        keys: Vec<String>,
+        previous: Vec<String>,
+    },
+    /// A run of consecutive array elements, `values`, was removed starting at the index
+    /// the path ends with: `tags.3` holding four values removes old indices 3 to 6. Produced
+    /// by [`coalesce_ranges`] and applied as one splice.
+    RemoveRange(Vec<Value>),
+    /// A run of consecutive array elements, `values`, was inserted starting at the position
+    /// the path ends with (`tags.+3`). Produced by [`coalesce_ranges`] and applied as one
+    /// splice.
+    InsertRange(Vec<Value>),
 }
 
 impl Change {
-    /// Returns the inverse of this change (adds ⇄ removes, swaps `old`/`new`).
+    /// Returns the inverse of this change (adds ⇄ removes, also of element ranges, swaps
+    /// `old`/`new`, moves and renames back from `to` to `from`, negates a `NumberDelta`,
+    /// swaps the digests of an `Opaque`, and inverts an `ArrayOps` script step by step in
+    /// reverse order).
+    ///
+    /// A `Copy` carries no value, so it has no self-contained inverse and is returned
+    /// unchanged; `revert` undoes it by checking the document instead (see `try_revert`).
+    /// A `Set` lacks its old value and is likewise returned unchanged, as is `Unchanged`.
     pub fn inverse(self) -> Self {
         match self {
             Change::Add(v) => Change::Remove(v),
             Change::Remove(v) => Change::Add(v),
             Change::Modify { old, new } => Change::Modify { old: new, new: old },
+            Change::Move { from, to } => Change::Move { from: to, to: from },
+            Change::Copy { from, to } => Change::Copy { from, to },
+            Change::Rename { from, to } => Change::Rename { from: to, to: from },
+            Change::Set(v) => Change::Set(v),
+            Change::Unchanged(v) => Change::Unchanged(v),
+            Change::NumberDelta { by } => Change::NumberDelta {
+                by: number::negate(&by),
+            },
+            Change::Opaque { old, new } => Change::Opaque { old: new, new: old },
+            Change::ArrayOps(ops) => Change::ArrayOps(array_ops::invert_ops(ops)),
+            Change::Reorder { keys, previous } => Change::Reorder {
+                keys: previous,
+                previous: keys,
+            },
+            Change::RemoveRange(values) => Change::InsertRange(values),
+            Change::InsertRange(values) => Change::RemoveRange(values),
+        }
+    }
+
+    /// The kind of this change, without its data.
+    pub fn kind(&self) -> ChangeKind {
+        match self {
+            Change::Add(_) => ChangeKind::Add,
+            Change::Remove(_) => ChangeKind::Remove,
+            Change::Modify { .. } => ChangeKind::Modify,
+            Change::Move { .. } => ChangeKind::Move,
+            Change::Copy { .. } => ChangeKind::Copy,
+            Change::Rename { .. } => ChangeKind::Rename,
+            Change::Set(_) => ChangeKind::Set,
+            Change::Unchanged(_) => ChangeKind::Unchanged,
+            Change::NumberDelta { .. } => ChangeKind::NumberDelta,
+            Change::Opaque { .. } => ChangeKind::Opaque,
+            Change::ArrayOps(_) => ChangeKind::ArrayOps,
+            Change::Reorder { .. } => ChangeKind::Reorder,
+            Change::RemoveRange(_) => ChangeKind::RemoveRange,
+            Change::InsertRange(_) => ChangeKind::InsertRange,
+        }
+    }
+
+    /// The value at the path before the change, when the change carries it (`Remove`,
+    /// `Modify` and `Unchanged`).
+    pub fn old_value(&self) -> Option<&Value> {
+        match self {
+            Change::Remove(v) | Change::Modify { old: v, .. } | Change::Unchanged(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// The value at the path after the change, when the change carries it (`Add`,
+    /// `Modify`, `Set` and `Unchanged`).
+    pub fn new_value(&self) -> Option<&Value> {
+        match self {
+            Change::Add(v)
+            | Change::Modify { new: v, .. }
+            | Change::Set(v)
+            | Change::Unchanged(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// The `from` and `to` paths of a change that relocates a value (`Move`, `Copy` and
+    /// `Rename`).
+    pub fn endpoints(&self) -> Option<(&str, &str)> {
+        match self {
+            Change::Move { from, to } | Change::Copy { from, to } | Change::Rename { from, to } => {
+                Some((from, to))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A compact one-line form for logs and debugging, with values as compact JSON: `+ "x"`
+/// for an `Add`, `- "y"` for a `Remove` and `"a" -> "b"` for a `Modify`. A `Set`, whose
+/// old value is unknown, reads `-> "b"`, an `Unchanged` `= "a"`, and a `NumberDelta`
+/// `+= 5`; relocations read `move a => b` (or `copy`, `rename`), an `Opaque` its two
+/// digests prefixed with `#`, and a `Reorder` its new key order. The steps of an
+/// `ArrayOps` script are listed as `insert 0 "x"`, `delete 2 "y"` and `update 1 (...)`,
+/// and element ranges as `-range [1,2]` or `+range [1,2]`.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::Change;
+///
+/// assert_eq!(Change::Add(json!("x")).to_string(), r#"+ "x""#);
+/// assert_eq!(
+///     Change::Modify { old: json!({"a": 1}), new: json!(null) }.to_string(),
+///     r#"{"a":1} -> null"#
+/// );
+/// ```
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Add(v) => write!(f, "+ {v}"),
+            Change::Remove(v) => write!(f, "- {v}"),
+            Change::Modify { old, new } => write!(f, "{old} -> {new}"),
+            Change::Set(v) => write!(f, "-> {v}"),
+            Change::Unchanged(v) => write!(f, "= {v}"),
+            Change::Move { from, to } => write!(f, "move {from} => {to}"),
+            Change::Copy { from, to } => write!(f, "copy {from} => {to}"),
+            Change::Rename { from, to } => write!(f, "rename {from} => {to}"),
+            Change::NumberDelta { by } => write!(f, "+= {by}"),
+            Change::Opaque { old, new } => write!(f, "#{old} -> #{new}"),
+            Change::ArrayOps(ops) => {
+                f.write_str("[")?;
+                for (i, op) in ops.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    match op {
+                        ArrayOp::Insert { index, value } => write!(f, "insert {index} {value}")?,
+                        ArrayOp::Delete { index, value } => write!(f, "delete {index} {value}")?,
+                        ArrayOp::Update { index, change } => {
+                            write!(f, "update {index} ({change})")?
+                        }
+                    }
+                }
+                f.write_str("]")
+            }
+            Change::Reorder { keys, .. } => write!(f, "reorder {}", keys.join(", ")),
+            Change::RemoveRange(values) => write!(f, "-range {}", Value::Array(values.clone())),
+            Change::InsertRange(values) => write!(f, "+range {}", Value::Array(values.clone())),
         }
     }
 }
 
-/// A mapping from JSON dot-paths to `Change` values.
-pub type Delta = BTreeMap<String, Change>;
+/// The kind of a [`Change`], as returned by [`Change::kind`].
+///
+/// Like `Change`, this enum is `#[non_exhaustive]` and gains a variant whenever `Change` does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum ChangeKind {
+    Add,
+    Remove,
+    Modify,
+    Move,
+    Copy,
+    Rename,
+    Set,
+    Unchanged,
+    NumberDelta,
+    Opaque,
+    ArrayOps,
+    Reorder,
+    RemoveRange,
+    InsertRange,
+}
 
 /// Compute the delta between two JSON values.
 ///
 /// Returns a `Delta` mapping each changed path to its corresponding `Change`.
 pub fn diff(before: &Value, after: &Value) -> Delta {
+    diff_with(before, after, &DiffOptions::default())
+}
+
+/// Compute the delta between two JSON values using the given `DiffOptions`.
+pub fn diff_with(before: &Value, after: &Value, options: &DiffOptions) -> Delta {
+    diff_with_codec(before, after, options, &DotCodec)
+}
+
+/// Like [`diff_with`], spelling every path of the delta with `codec`.
+///
+/// The path patterns of `options` (`set_arrays`, `ignore_paths`, `coerce`, `match_fields`
+/// and `watched`) are written with the same codec. Apply and revert the result with
+/// [`apply_with_codec`] and [`revert_with_codec`].
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{apply_with_codec, diff_with_codec, DiffOptions, JsonPointerCodec};
+///
+/// let before = json!({"hosts": {"example.com": {"port": 80}}});
+/// let after = json!({"hosts": {"example.com": {"port": 443}}});
+/// let delta = diff_with_codec(&before, &after, &DiffOptions::default(), &JsonPointerCodec);
+/// assert!(delta.contains_key("/hosts/example.com/port"));
+/// assert_eq!(apply_with_codec(&before, &delta, &JsonPointerCodec), after);
+/// ```
+pub fn diff_with_codec(
+    before: &Value,
+    after: &Value,
+    options: &DiffOptions,
+    codec: &dyn PathCodec,
+) -> Delta {
+    let changes = collect_changes(before, after, options, codec);
+    match options.max_changes {
+        Some(max) if changes.len() > max && !filtered_within(options, codec, "", before, after) => {
+            Delta::from_iter(whole_change(options, before, after).map(|c| (String::new(), c)))
+        }
+        _ => changes,
+    }
+}
+
+/// Like [`diff_with`], but fail instead of degrading when the input breaks a limit of
+/// `options`.
+///
+/// Where `diff_with` compares values nested deeper than `max_depth` as a whole, this
+/// returns `JsonDiffError::PathTooDeep` for the first path with more segments than that
+/// at which `before` and `after` differ (documents that are only deep where they agree
+/// are fine); where `diff_with` replaces a delta of more than `max_changes` entries with
+/// a single change of the root, this returns `JsonDiffError::TooManyChanges`; and where
+/// `array_strategy` is `ArrayStrategy::SameLengthObjects` but two differing arrays at the
+/// same path are not both objects of the same length, so `diff_with` would replace them
+/// whole, this returns `JsonDiffError::UnsupportedArray`. Otherwise it is
+/// `Ok(diff_with(..))`.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{try_diff, DiffOptions, JsonDiffError};
+///
+/// let options = DiffOptions { max_depth: Some(2), ..Default::default() };
+/// let deep = json!({"a": {"b": {"c": 1}}});
+/// assert_eq!(
+///     try_diff(&deep, &json!({"a": {"b": {"c": 2}}}), &options),
+///     Err(JsonDiffError::PathTooDeep { path: "a.b.c".to_string(), max_depth: 2 })
+/// );
+/// assert!(try_diff(&deep, &deep, &options).unwrap().is_empty());
+/// ```
+pub fn try_diff(
+    before: &Value,
+    after: &Value,
+    options: &DiffOptions,
+) -> Result<Delta, JsonDiffError> {
+    if let Some(max_depth) = options.max_depth
+        && let Some(path) =
+            deep_difference(options, max_depth, String::new(), Some(before), Some(after))
+    {
+        return Err(JsonDiffError::PathTooDeep { path, max_depth });
+    }
+    if let Some(path) = unsupported_array(options, String::new(), before, after) {
+        return Err(JsonDiffError::UnsupportedArray { path });
+    }
+    let changes = collect_changes(before, after, options, &DotCodec);
+    match options.max_changes {
+        Some(max_changes) if changes.len() > max_changes => Err(JsonDiffError::TooManyChanges {
+            count: changes.len(),
+            max_changes,
+        }),
+        _ => Ok(changes),
+    }
+}
+
+/// The first path with more than `max` segments at which `a` and `b` (found at `path`,
+/// `None` where absent) differ, following only the paths `compare` descends into: below
+/// `max` segments it stops where values are compared whole anyway, and at `max` it looks
+/// inside the subtrees `diff_with` would collapse.
+fn deep_difference(
+    options: &DiffOptions,
+    max: usize,
+    path: String,
+    a: Option<&Value>,
+    b: Option<&Value>,
+) -> Option<String> {
+    let filtered = |value: Option<&Value>| {
+        value.is_some_and(|value| options.should_skip(&path, value, &DotCodec))
+    };
+    if a == b || filtered(a) || filtered(b) || options.scope(&path, &DotCodec) == Scope::Outside {
+        return None;
+    }
+    let depth = DotCodec.decode(&path).len();
+    let (Some(a), Some(b)) = (a, b) else {
+        let present = a.or(b).is_some_and(|value| !options.equals_absent(value));
+        return (present && depth > max).then_some(path);
+    };
+    if depth > max {
+        return Some(path);
+    }
+    let children: Vec<(String, Option<&Value>, Option<&Value>)> = match (a, b) {
+        (Value::Object(obj_a), Value::Object(obj_b)) => obj_a
+            .keys()
+            .chain(obj_b.keys().filter(|key| !obj_a.contains_key(*key)))
+            .map(|key| (join_path(&path, key), obj_a.get(key), obj_b.get(key)))
+            .collect(),
+        (Value::Array(arr_a), Value::Array(arr_b)) => {
+            match options.match_fields_at(&path, &DotCodec) {
+                Some(fields) => {
+                    let mut matched = vec![false; arr_b.len()];
+                    let mut children = Vec::new();
+                    for (i, item) in arr_a.iter().enumerate() {
+                        let partner = (0..arr_b.len())
+                            .find(|&j| !matched[j] && same_identity(item, &arr_b[j], fields));
+                        if let Some(j) = partner {
+                            matched[j] = true;
+                        }
+                        let element_path = join_path(&path, &i.to_string());
+                        children.push((element_path, Some(item), partner.map(|j| &arr_b[j])));
+                    }
+                    for (j, item) in arr_b.iter().enumerate().filter(|(j, _)| !matched[*j]) {
+                        children.push((join_path(&path, &format!("+{j}")), None, Some(item)));
+                    }
+                    children
+                }
+                None if !options.is_set_array(&path) && options.pairs_elements(arr_a, arr_b) => {
+                    arr_a
+                        .iter()
+                        .zip(arr_b)
+                        .enumerate()
+                        .map(|(i, (va, vb))| (join_path(&path, &i.to_string()), Some(va), Some(vb)))
+                        .collect()
+                }
+                None => return None,
+            }
+        }
+        _ => return None,
+    };
+    children
+        .into_iter()
+        .find_map(|(child, va, vb)| deep_difference(options, max, child, va, vb))
+}
+
+/// The first path at which `a` and `b` hold differing arrays that
+/// `ArrayStrategy::SameLengthObjects` cannot pair element by element.
+fn unsupported_array(options: &DiffOptions, path: String, a: &Value, b: &Value) -> Option<String> {
+    if a == b
+        || options.array_strategy != ArrayStrategy::SameLengthObjects
+        || options.should_skip(&path, a, &DotCodec)
+        || options.should_skip(&path, b, &DotCodec)
+    {
+        return None;
+    }
+    match (a, b) {
+        (Value::Object(obj_a), Value::Object(obj_b)) => obj_a.iter().find_map(|(key, va)| {
+            let vb = obj_b.get(key)?;
+            unsupported_array(options, join_path(&path, key), va, vb)
+        }),
+        (Value::Array(_), Value::Array(_))
+            if options.is_set_array(&path)
+                || options.match_fields_at(&path, &DotCodec).is_some() =>
+        {
+            None
+        }
+        (Value::Array(arr_a), Value::Array(arr_b)) if options.pairs_elements(arr_a, arr_b) => arr_a
+            .iter()
+            .zip(arr_b)
+            .enumerate()
+            .find_map(|(i, (va, vb))| {
+                unsupported_array(options, join_path(&path, &i.to_string()), va, vb)
+            }),
+        (Value::Array(_), Value::Array(_)) => Some(path),
+        _ => None,
+    }
+}
+
+/// The changes between `before` and `after`, without the `max_changes` fallback.
+fn collect_changes(
+    before: &Value,
+    after: &Value,
+    options: &DiffOptions,
+    codec: &dyn PathCodec,
+) -> Delta {
     let mut changes = Delta::new();
-    compare(&mut changes, String::new(), before, after);
+    compare(&mut changes, options, codec, String::new(), before, after);
+    for path in &options.watched {
+        if changes.contains_key(path) {
+            continue;
+        }
+        let segments = codec.decode(path);
+        let (a, b) = (
+            get_segments(before, &segments),
+            get_segments(after, &segments),
+        );
+        let change = match (a, b) {
+            (Some(a), Some(b)) if options.equivalent_at(path, a, b, codec) => {
+                Change::Unchanged(options.capture(b))
+            }
+            (Some(value), None) | (None, Some(value)) => {
+                let covered = changes
+                    .keys()
+                    .any(|changed| segments.starts_with(&codec.decode(changed)));
+                if covered
+                    || options.should_skip(path, value, codec)
+                    || options.scope(path, codec) != Scope::Inside
+                {
+                    continue;
+                }
+                if options.equals_absent(value) {
+                    Change::Unchanged(options.capture(value))
+                } else if b.is_none() {
+                    Change::Remove(options.capture(value))
+                } else {
+                    Change::Add(options.capture(value))
+                }
+            }
+            _ => continue,
+        };
+        changes.insert(path.clone(), change);
+    }
     changes
 }
 
-fn compare(delta: &mut Delta, path: String, a: &Value, b: &Value) {
+/// Whether `a` and `b` are equal once the subtrees at the `ignore` paths are disregarded.
+///
+/// Patterns are those of [`DiffOptions::ignore_paths`] (`*` matches any single segment);
+/// a path missing on one side counts as ignored too. Handy for asserting that two
+/// responses match except for timestamps or generated ids.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::equal_ignoring;
+///
+/// let a = json!({"id": 1, "meta": {"fetched_at": "10:00"}});
+/// let b = json!({"id": 1, "meta": {"fetched_at": "10:05"}});
+/// assert!(equal_ignoring(&a, &b, &["meta.fetched_at"]));
+/// assert!(!equal_ignoring(&a, &b, &["id"]));
+/// ```
+pub fn equal_ignoring(a: &Value, b: &Value, ignore: &[&str]) -> bool {
+    let options = DiffOptions {
+        ignore_paths: ignore.iter().map(|path| path.to_string()).collect(),
+        ..Default::default()
+    };
+    diff_with(a, b, &options).is_empty()
+}
+
+/// Re-diff only the subtree at `prefix`, updating `existing` in place.
+///
+/// Entries of `existing` at or below `prefix` are dropped and replaced by a fresh diff of
+/// that subtree of `before` and `after`, so a live delta can be kept current as edits
+/// happen without re-diffing the whole document. If `existing` was `diff(before_old,
+/// after_old)` and only the subtree at `prefix` changed since, the result equals
+/// `diff(before, after)`. `prefix` should name a location whose ancestors exist on both
+/// sides; the empty prefix re-diffs everything.
+pub fn diff_subtree(prefix: &str, before: &Value, after: &Value, existing: &mut Delta) {
+    existing.retain(|path, _| !is_within(path, prefix));
+    match (get_path(before, prefix), get_path(after, prefix)) {
+        (Some(a), Some(b)) => compare(
+            existing,
+            &DiffOptions::default(),
+            &DotCodec,
+            prefix.to_string(),
+            a,
+            b,
+        ),
+        (Some(a), None) => {
+            existing.insert(prefix.to_string(), Change::Remove(a.clone()));
+        }
+        (None, Some(b)) => {
+            existing.insert(prefix.to_string(), Change::Add(b.clone()));
+        }
+        (None, None) => {}
+    }
+}
+
+/// Where `compare` records the changes it finds. Recording a change at a path already
+/// recorded replaces the earlier one, as `Delta::insert` does.
+pub(crate) trait ChangeSink {
+    fn record(&mut self, path: String, change: Change);
+}
+
+impl ChangeSink for Delta {
+    fn record(&mut self, path: String, change: Change) {
+        self.insert(path, change);
+    }
+}
+
+pub(crate) fn compare(
+    delta: &mut dyn ChangeSink,
+    options: &DiffOptions,
+    codec: &dyn PathCodec,
+    path: String,
+    a: &Value,
+    b: &Value,
+) {
     if a == b {
+        // Objects compare equal whatever the order of their keys.
+        #[cfg(feature = "preserve_order")]
+        if options.detect_key_reorder {
+            reorder::record_reorders(delta, options, codec, &path, a, b);
+        }
+        return;
+    }
+    if options.should_skip(&path, a, codec) || options.should_skip(&path, b, codec) {
+        return;
+    }
+    let scope = options.scope(&path, codec);
+    let too_deep = options
+        .max_depth
+        .is_some_and(|max| codec.decode(&path).len() >= max);
+    let descends = !too_deep
+        && match (a, b) {
+            (Value::Object(_), Value::Object(_)) => true,
+            (Value::Array(arr_a), Value::Array(arr_b)) => {
+                options.match_fields_at(&path, codec).is_some()
+                    || (!options.is_set_array(&path) && options.pairs_elements(arr_a, arr_b))
+            }
+            _ => false,
+        };
+    if scope == Scope::Outside || (scope == Scope::Above && !descends) {
+        return;
+    }
+    if too_deep {
+        // A subtree holding filtered paths cannot be replaced whole without reporting
+        // them, so it is descended into until the filtered paths are reached.
+        if filtered_within(options, codec, &path, a, b) {
+            compare_parts(delta, options, codec, path, a, b);
+        } else {
+            compare_whole(delta, options, codec, path, a, b);
+        }
+        return;
+    }
+    let containers = matches!(
+        (a, b),
+        (Value::Object(_), Value::Object(_)) | (Value::Array(_), Value::Array(_))
+    );
+    if options.minimize_cost
+        && containers
+        && scope == Scope::Inside
+        && !filtered_within(options, codec, &path, a, b)
+    {
+        // Subtrees below have already been minimized, so comparing the cheapest nested
+        // changes with one whole replacement minimizes this one.
+        let mut nested = Delta::new();
+        compare_parts(&mut nested, options, codec, path.clone(), a, b);
+        if nested.is_empty() {
+            return;
+        }
+        let whole = Delta::from_iter(whole_change(options, a, b).map(|change| (path, change)));
+        let cheapest = if estimated_size(&whole) < estimated_size(&nested) {
+            whole
+        } else {
+            nested
+        };
+        for (path, change) in cheapest.into_inner() {
+            delta.record(path, change);
+        }
         return;
     }
+    compare_parts(delta, options, codec, path, a, b);
+}
 
+/// Compare `a` and `b` at `path` according to their types and the options, descending
+/// into containers where the options call for it.
+fn compare_parts(
+    delta: &mut dyn ChangeSink,
+    options: &DiffOptions,
+    codec: &dyn PathCodec,
+    path: String,
+    a: &Value,
+    b: &Value,
+) {
     match (a, b) {
         (Value::Object(obj_a), Value::Object(obj_b)) => {
+            #[cfg(feature = "preserve_order")]
+            if options.detect_key_reorder
+                && let Some(change) = reorder::key_order_change(obj_a, obj_b)
+            {
+                delta.record(path.clone(), change);
+            }
             // Collect all keys present in either object
             let all_keys: HashSet<_> = obj_a.keys().chain(obj_b.keys()).collect();
+            let mut removed = Vec::new();
+            let mut added = Vec::new();
             for key in all_keys {
-                let new_path = if path.is_empty() {
-                    key.clone()
-                } else {
-                    format!("{path}.{key}")
-                };
+                let new_path = codec.join(&path, key);
 
                 match (obj_a.get(key), obj_b.get(key)) {
-                    (Some(va), Some(vb)) => compare(delta, new_path, va, vb),
+                    (Some(va), Some(vb)) => compare(delta, options, codec, new_path, va, vb),
                     (Some(va), None) => {
-                        delta.insert(new_path, Change::Remove(va.clone()));
+                        if options.scope(&new_path, codec) == Scope::Inside
+                            && !options.should_skip(&new_path, va, codec)
+                            && !options.equals_absent(va)
+                        {
+                            removed.push((new_path, va));
+                        }
                     }
                     (None, Some(vb)) => {
-                        delta.insert(new_path, Change::Add(vb.clone()));
+                        if !options.only_template_keys
+                            && options.scope(&new_path, codec) == Scope::Inside
+                            && !options.should_skip(&new_path, vb, codec)
+                            && !options.equals_absent(vb)
+                        {
+                            added.push((new_path, vb));
+                        }
                     }
                     _ => unreachable!(),
                 }
             }
+            if options.detect_renames || options.rename_similarity.is_some() {
+                pair_renames(delta, options, codec, &mut removed, &mut added);
+            }
+            for (new_path, va) in removed {
+                delta.record(new_path, Change::Remove(options.capture(va)));
+            }
+            for (new_path, vb) in added {
+                delta.record(new_path, Change::Add(options.capture(vb)));
+            }
         }
-        _ => {
-            delta.insert(
-                path,
-                Change::Modify {
-                    old: a.clone(),
-                    new: b.clone(),
-                },
-            );
+        (Value::Array(arr_a), Value::Array(arr_b)) if options.is_set_array(&path) => {
+            compare_set(delta, options, codec, &path, arr_a, arr_b);
+        }
+        (Value::Array(arr_a), Value::Array(arr_b))
+            if options.match_fields_at(&path, codec).is_some() =>
+        {
+            let fields = options.match_fields_at(&path, codec).unwrap_or_default();
+            compare_matched(delta, options, codec, &path, arr_a, arr_b, fields);
+        }
+        (Value::Array(arr_a), Value::Array(arr_b))
+            if options.array_strategy == ArrayStrategy::Ops =>
+        {
+            let ops = array_ops::diff_ops(options, arr_a, arr_b);
+            if !ops.is_empty() {
+                delta.record(path, Change::ArrayOps(ops));
+            }
+        }
+        (Value::Array(arr_a), Value::Array(arr_b))
+            if options.array_strategy == ArrayStrategy::Multiset =>
+        {
+            compare_multiset(delta, options, codec, &path, arr_a, arr_b);
+        }
+        (Value::Array(arr_a), Value::Array(arr_b)) if options.pairs_elements(arr_a, arr_b) => {
+            for (i, (va, vb)) in arr_a.iter().zip(arr_b).enumerate() {
+                compare(
+                    delta,
+                    options,
+                    codec,
+                    codec.join(&path, &i.to_string()),
+                    va,
+                    vb,
+                );
+            }
         }
+        _ => compare_whole(delta, options, codec, path, a, b),
     }
 }
 
-/// Apply a `Delta` to an original JSON value, returning a new `Value`.
-pub fn apply(original: &Value, delta: &Delta) -> Value {
-    let mut result = original.clone();
-    for (path, change) in delta {
-        let value = match change {
-            Change::Add(v) | Change::Modify { new: v, .. } => Some(v.clone()),
-            Change::Remove(_) => None,
+/// Whether `ignore_paths`, `skip` or `only_paths` leave out any part of the subtrees of
+/// `a` or `b` at `path`, in which case a single change replacing them whole would record
+/// what the filters are meant to keep out of the delta.
+fn filtered_within(
+    options: &DiffOptions,
+    codec: &dyn PathCodec,
+    path: &str,
+    a: &Value,
+    b: &Value,
+) -> bool {
+    fn skips_below(
+        options: &DiffOptions,
+        codec: &dyn PathCodec,
+        path: &str,
+        value: &Value,
+    ) -> bool {
+        let skips = |child: String, value: &Value| {
+            options.should_skip(&child, value, codec) || skips_below(options, codec, &child, value)
         };
-        set_value(&mut result, path, value);
+        match value {
+            Value::Object(obj) => obj.iter().any(|(key, v)| skips(codec.join(path, key), v)),
+            Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .any(|(i, v)| skips(codec.join(path, &i.to_string()), v)),
+            _ => false,
+        }
+    }
+    if options.scope(path, codec) != Scope::Inside {
+        return true;
+    }
+    (!options.ignore_paths.is_empty() || options.skip.is_some())
+        && [a, b]
+            .into_iter()
+            .any(|value| skips_below(options, codec, path, value))
+}
+
+/// Compare `a` and `b` as single values, recording one change at `path` if they differ.
+fn compare_whole(
+    delta: &mut dyn ChangeSink,
+    options: &DiffOptions,
+    codec: &dyn PathCodec,
+    path: String,
+    a: &Value,
+    b: &Value,
+) {
+    if !options.equivalent_at(&path, a, b, codec)
+        && let Some(change) = whole_change(options, a, b)
+    {
+        delta.record(path, change);
+    }
+}
+
+/// The change replacing `a` with `b` as a whole, or `None` if the captured values are
+/// equal.
+fn whole_change(options: &DiffOptions, a: &Value, b: &Value) -> Option<Change> {
+    if let Some(by) = options.number_delta(a, b) {
+        Some(Change::NumberDelta { by })
+    } else if options.exceeds_max_size(a) || options.exceeds_max_size(b) {
+        Some(Change::Opaque {
+            old: digest(a),
+            new: digest(b),
+        })
+    } else {
+        options.capture_change(a, b)
     }
-    result
 }
 
-/// Revert a `Delta` on a JSON value, returning the previous state.
-pub fn revert(original: &Value, delta: &Delta) -> Value {
-    let inverse_delta: Delta = delta
-        .iter()
-        .map(|(path, change)| (path.clone(), change.clone().inverse()))
-        .collect();
-    apply(original, &inverse_delta)
+/// Turn each removed key whose value reappears under an added key of the same object into
+/// a `Rename`, preferring an equal value (`detect_renames`) and otherwise the most similar
+/// one above `rename_similarity`, whose remaining differences are diffed under the new key.
+/// Keys are paired in path order, so the result is deterministic.
+fn pair_renames(
+    delta: &mut dyn ChangeSink,
+    options: &DiffOptions,
+    codec: &dyn PathCodec,
+    removed: &mut Vec<(String, &Value)>,
+    added: &mut Vec<(String, &Value)>,
+) {
+    removed.sort_by(|(a, _), (b, _)| a.cmp(b));
+    added.sort_by(|(a, _), (b, _)| a.cmp(b));
+    removed.retain(|(from, old)| {
+        let exact = if options.detect_renames {
+            added
+                .iter()
+                .position(|(to, new)| options.equivalent_at(to, old, new, codec))
+        } else {
+            None
+        };
+        let Some(i) = exact.or_else(|| most_similar(options, old, added)) else {
+            return true;
+        };
+        let (to, new) = added.remove(i);
+        compare(delta, options, codec, to.clone(), old, new);
+        delta.record(
+            to.clone(),
+            Change::Rename {
+                from: from.clone(),
+                to,
+            },
+        );
+        false
+    });
 }
 
-fn set_value(root: &mut Value, path: &str, value: Option<Value>) {
-    let parts: Vec<&str> = path.split('.').collect();
-    let mut current = root;
-    // Navigate to the parent of the target
-    for &segment in &parts[..parts.len() - 1] {
-        if !current.is_object() {
-            *current = Value::Object(Map::new());
+/// Index of the added value most similar to `old`, if any reaches `rename_similarity`.
+fn most_similar(options: &DiffOptions, old: &Value, added: &[(String, &Value)]) -> Option<usize> {
+    let threshold = options.rename_similarity?;
+    let mut best: Option<(usize, f64)> = None;
+    for (i, (_, new)) in added.iter().enumerate() {
+        let score = similarity(old, new);
+        if score > 0.0 && score >= threshold && best.is_none_or(|(_, top)| score > top) {
+            best = Some((i, score));
         }
-        current = current
-            .as_object_mut()
-            .unwrap()
-            .entry(segment)
-            .or_insert_with(|| Value::Object(Map::new()));
-    }
-    if let Some(obj) = current.as_object_mut() {
-        let key = parts.last().unwrap();
-        match value {
-            Some(v) => {
-                obj.insert(key.to_string(), v);
+    }
+    best.map(|(i, _)| i)
+}
+
+/// Diff two arrays by membership. Removed elements are keyed by their old index and added
+/// ones by their `+`-prefixed new index (see "Array element paths").
+fn compare_set(
+    delta: &mut dyn ChangeSink,
+    options: &DiffOptions,
+    codec: &dyn PathCodec,
+    path: &str,
+    a: &[Value],
+    b: &[Value],
+) {
+    let contains =
+        |items: &[Value], item: &Value| items.iter().any(|x| options.equivalent(x, item));
+    for (i, item) in a.iter().enumerate() {
+        if !contains(b, item) {
+            delta.record(
+                codec.join(path, &i.to_string()),
+                Change::Remove(options.capture(item)),
+            );
+        }
+    }
+    for (j, item) in b.iter().enumerate() {
+        if !contains(a, item) {
+            delta.record(
+                codec.join(path, &format!("+{j}")),
+                Change::Add(options.capture(item)),
+            );
+        }
+    }
+}
+
+/// Pair elements of `a` and `b` whose identifying `fields` are equal and diff each pair;
+/// unpaired elements were removed or added.
+fn compare_matched(
+    delta: &mut dyn ChangeSink,
+    options: &DiffOptions,
+    codec: &dyn PathCodec,
+    path: &str,
+    a: &[Value],
+    b: &[Value],
+    fields: &[String],
+) {
+    let mut candidates: HashMap<u64, VecDeque<usize>> = HashMap::new();
+    for (j, item) in b.iter().enumerate() {
+        candidates
+            .entry(identity_hash(item, fields))
+            .or_default()
+            .push_back(j);
+    }
+    let mut matched = vec![false; b.len()];
+    for (i, item) in a.iter().enumerate() {
+        let partner = candidates
+            .get_mut(&identity_hash(item, fields))
+            .and_then(|queue| {
+                let position = queue
+                    .iter()
+                    .position(|&j| same_identity(item, &b[j], fields))?;
+                queue.remove(position)
+            });
+        let element_path = codec.join(path, &i.to_string());
+        match partner {
+            Some(j) => {
+                matched[j] = true;
+                compare(delta, options, codec, element_path, item, &b[j]);
             }
+            None if options.scope(&element_path, codec) == Scope::Inside => {
+                delta.record(element_path, Change::Remove(options.capture(item)));
+            }
+            None => {}
+        }
+    }
+    for (j, item) in b.iter().enumerate() {
+        let element_path = codec.join(path, &format!("+{j}"));
+        if !matched[j] && options.scope(&element_path, codec) == Scope::Inside {
+            delta.record(element_path, Change::Add(options.capture(item)));
+        }
+    }
+}
+
+/// Hash of the identifying `fields` of an object element (missing fields are left out),
+/// or of the whole value for other elements.
+fn identity_hash(item: &Value, fields: &[String]) -> u64 {
+    match item {
+        Value::Object(obj) => object_hash(fields.iter().fold(0u64, |sum, field| {
+            obj.get(field)
+                .map_or(sum, |v| sum.wrapping_add(hash_entry(field, hash_value(v))))
+        })),
+        _ => hash_value(item),
+    }
+}
+
+/// Whether two elements agree on their identifying `fields`; guards against hash
+/// collisions in `compare_matched`.
+fn same_identity(a: &Value, b: &Value, fields: &[String]) -> bool {
+    match (a, b) {
+        (Value::Object(x), Value::Object(y)) => fields.iter().all(|f| x.get(f) == y.get(f)),
+        _ => a == b,
+    }
+}
+
+/// Match each element of `a` with a distinct equal element of `b`; whatever is left over
+/// on either side was removed or added.
+fn compare_multiset(
+    delta: &mut dyn ChangeSink,
+    options: &DiffOptions,
+    codec: &dyn PathCodec,
+    path: &str,
+    a: &[Value],
+    b: &[Value],
+) {
+    let mut matched = vec![false; b.len()];
+    for (i, item) in a.iter().enumerate() {
+        let partner = (0..b.len()).find(|&j| !matched[j] && options.equivalent(item, &b[j]));
+        match partner {
+            Some(j) => matched[j] = true,
             None => {
-                obj.remove(*key);
+                delta.record(
+                    codec.join(path, &i.to_string()),
+                    Change::Remove(options.capture(item)),
+                );
             }
         }
     }
+    for (j, item) in b.iter().enumerate() {
+        if !matched[j] {
+            delta.record(
+                codec.join(path, &format!("+{j}")),
+                Change::Add(options.capture(item)),
+            );
+        }
+    }
 }
 
 #[cfg(test)]
@@ -156,6 +1102,162 @@ mod tests {
     use super::*;
     use serde_json::{Value, json};
 
+    #[test]
+    fn change_accessors_cover_every_variant() {
+        let relocation = Change::Rename {
+            from: "a".to_string(),
+            to: "b".to_string(),
+        };
+        let cases = [
+            (Change::Add(json!(1)), ChangeKind::Add, None, Some(json!(1))),
+            (
+                Change::Remove(json!(1)),
+                ChangeKind::Remove,
+                Some(json!(1)),
+                None,
+            ),
+            (
+                Change::Modify {
+                    old: json!(1),
+                    new: json!(2),
+                },
+                ChangeKind::Modify,
+                Some(json!(1)),
+                Some(json!(2)),
+            ),
+            (Change::Set(json!(2)), ChangeKind::Set, None, Some(json!(2))),
+            (
+                Change::Unchanged(json!(3)),
+                ChangeKind::Unchanged,
+                Some(json!(3)),
+                Some(json!(3)),
+            ),
+            (relocation.clone(), ChangeKind::Rename, None, None),
+            (
+                Change::Opaque {
+                    old: "0".repeat(16),
+                    new: "1".repeat(16),
+                },
+                ChangeKind::Opaque,
+                None,
+                None,
+            ),
+            (
+                Change::ArrayOps(Vec::new()),
+                ChangeKind::ArrayOps,
+                None,
+                None,
+            ),
+            (
+                Change::Reorder {
+                    keys: vec!["b".into(), "a".into()],
+                    previous: vec!["a".into(), "b".into()],
+                },
+                ChangeKind::Reorder,
+                None,
+                None,
+            ),
+            (
+                Change::RemoveRange(vec![json!(1)]),
+                ChangeKind::RemoveRange,
+                None,
+                None,
+            ),
+            (
+                Change::InsertRange(vec![json!(1)]),
+                ChangeKind::InsertRange,
+                None,
+                None,
+            ),
+        ];
+        for (change, kind, old, new) in cases {
+            assert_eq!(change.kind(), kind);
+            assert_eq!(change.old_value(), old.as_ref());
+            assert_eq!(change.new_value(), new.as_ref());
+        }
+        assert_eq!(relocation.endpoints(), Some(("a", "b")));
+        assert_eq!(Change::Add(json!(1)).endpoints(), None);
+    }
+
+    #[test]
+    fn changes_display_compactly() {
+        let cases = [
+            (Change::Add(json!("x")), r#"+ "x""#),
+            (Change::Remove(json!("y")), r#"- "y""#),
+            (
+                Change::Modify {
+                    old: json!("a"),
+                    new: json!("b"),
+                },
+                r#""a" -> "b""#,
+            ),
+            (Change::Set(json!([1, 2])), "-> [1,2]"),
+            (Change::Unchanged(json!(null)), "= null"),
+            (
+                Change::Move {
+                    from: "a".into(),
+                    to: "b.c".into(),
+                },
+                "move a => b.c",
+            ),
+            (
+                Change::Copy {
+                    from: "a".into(),
+                    to: "b".into(),
+                },
+                "copy a => b",
+            ),
+            (
+                Change::Rename {
+                    from: "a.x".into(),
+                    to: "a.y".into(),
+                },
+                "rename a.x => a.y",
+            ),
+            (Change::NumberDelta { by: (-3).into() }, "+= -3"),
+            (
+                Change::Opaque {
+                    old: "0".repeat(16),
+                    new: "1".repeat(16),
+                },
+                "#0000000000000000 -> #1111111111111111",
+            ),
+            (
+                Change::ArrayOps(vec![
+                    ArrayOp::Insert {
+                        index: 0,
+                        value: json!("z"),
+                    },
+                    ArrayOp::Delete {
+                        index: 2,
+                        value: json!({"k": 1}),
+                    },
+                    ArrayOp::Update {
+                        index: 1,
+                        change: Box::new(Change::NumberDelta { by: 2.into() }),
+                    },
+                ]),
+                r#"[insert 0 "z", delete 2 {"k":1}, update 1 (+= 2)]"#,
+            ),
+            (Change::ArrayOps(Vec::new()), "[]"),
+            (
+                Change::Reorder {
+                    keys: vec!["b".into(), "a".into()],
+                    previous: vec!["a".into(), "b".into()],
+                },
+                "reorder b, a",
+            ),
+            (
+                Change::RemoveRange(vec![json!(1), json!("a")]),
+                r#"-range [1,"a"]"#,
+            ),
+            (Change::InsertRange(vec![json!(null)]), "+range [null]"),
+        ];
+        for (change, text) in cases {
+            assert_eq!(change.to_string(), text);
+        }
+    }
+
     #[test]
     fn nested_user_profile_field_change() {
         let old_profile = json!({"name": "John", "preferences": {"theme": "dark"}});
@@ -503,64 +1605,525 @@ mod tests {
     }
 
     #[test]
-    fn mixed_changes_in_user_profile() {
-        let before = json!({
-            "user": {
-                "name": "John",
-                "age": 30,
-                "settings": {"theme": "dark", "notifications": true}
-            },
-            "status": "active"
-        });
-        let after = json!({
-            "user": {
-                "name": "John",
-                "age": 31,
-                "settings": {"theme": "light", "notifications": true, "language": "en-US"}
-            },
-            "status": "inactive"
-        });
-        let delta: Delta = diff(&before, &after);
+    fn number_deltas_record_increments() {
+        let options = DiffOptions {
+            number_deltas: true,
+            ..Default::default()
+        };
+        let before = json!({"count": 41, "ratio": 0.5, "big": 1, "label": "x", "third": 0.7});
+        let after = json!({"count": 39, "ratio": 0.75, "big": 1.5, "label": "y", "third": 0.1});
+        let delta = diff_with(&before, &after, &options);
 
-        let mut expected = Delta::new();
-        expected.insert(
-            "user.age".to_string(),
-            Change::Modify {
-                old: json!(30),
-                new: json!(31),
-            },
-        );
-        expected.insert(
-            "user.settings.theme".to_string(),
-            Change::Modify {
-                old: json!("dark"),
-                new: json!("light"),
-            },
-        );
-        expected.insert(
-            "user.settings.language".to_string(),
-            Change::Add(json!("en-US")),
-        );
-        expected.insert(
-            "status".to_string(),
-            Change::Modify {
-                old: json!("active"),
-                new: json!("inactive"),
-            },
+        let increment = |by: Value| match by {
+            Value::Number(by) => Change::NumberDelta { by },
+            _ => unreachable!(),
+        };
+        assert_eq!(delta["count"], increment(json!(-2)));
+        assert_eq!(delta["ratio"], increment(json!(0.25)));
+        // Reverting 1.5 by 0.5 would give the float 1.0, not the integer 1.
+        assert!(matches!(delta["big"], Change::Modify { .. }));
+        assert!(matches!(delta["label"], Change::Modify { .. }));
+        // 0.7 + (0.1 - 0.7) is not exactly 0.1 in binary floating point.
+        assert!(matches!(delta["third"], Change::Modify { .. }));
+
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(revert(&after, &delta), before);
+        assert_eq!(try_revert(&after, &delta), Ok(before.clone()));
+        // Applied elsewhere, the increment is relative to what is found there.
+        assert_eq!(apply(&json!({"count": 100}), &delta)["count"], json!(98));
+        assert_eq!(
+            try_apply(
+                &json!({"count": "many"}),
+                &DeltaBuilder::new().increment("count", -2).build()
+            ),
+            Err(JsonDiffError::StaleValue {
+                path: "count".to_string()
+            })
         );
-        assert_eq!(delta, expected);
+    }
 
-        let reverted = revert(&after, &delta);
-        assert_eq!(reverted, before);
+    #[test]
+    fn oversized_values_are_recorded_as_digests() {
+        let options = DiffOptions {
+            max_value_size: Some(1024),
+            ..Default::default()
+        };
+        let blob = |c: char| Value::String(c.to_string().repeat(1 << 20));
+        let before = json!({"blob": blob('a'), "name": "x", "gone": blob('g')});
+        let after = json!({"blob": blob('b'), "name": "y"});
+        let delta = diff_with(&before, &after, &options);
 
+        let Change::Opaque { old, new } = &delta["blob"] else {
+            panic!("expected an opaque change, got {:?}", delta["blob"]);
+        };
+        assert_eq!(old.len(), 16);
+        assert_ne!(old, new);
+        assert_eq!(*old, hash::digest(&blob('a')));
+        // Values below the cap, and removed keys, are captured as usual.
+        assert_eq!(delta["name"], diff(&before, &after)["name"]);
+        assert_eq!(delta["gone"], Change::Remove(blob('g')));
+
+        // An opaque change is skipped when applied or reverted leniently...
         let applied = apply(&before, &delta);
-        assert_eq!(applied, after);
+        assert_eq!(applied["blob"], before["blob"]);
+        assert_eq!(applied["name"], json!("y"));
+        assert_eq!(revert(&applied, &delta)["name"], json!("x"));
+        // ...and rejected by the strict functions.
+        assert_eq!(
+            try_apply(&before, &delta),
+            Err(JsonDiffError::Opaque {
+                path: "blob".to_string()
+            })
+        );
+        assert_eq!(
+            try_revert(&after, &delta),
+            Err(JsonDiffError::Irreversible {
+                path: "blob".to_string()
+            })
+        );
+        assert!(
+            diff_with(&before, &after, &DiffOptions::default())["blob"]
+                .new_value()
+                .is_some()
+        );
     }
 
     #[test]
-    fn object_with_numeric_keys() {
-        let before = json!({"0": "zero", "1": "one"});
-        let after = json!({"0": "ZERO", "2": "two"});
+    fn only_paths_restricts_the_diff() {
+        let options = DiffOptions {
+            only_paths: vec!["user.age".to_string()],
+            ..Default::default()
+        };
+        let before = json!({"user": {"name": "Ann", "age": 30}, "items": [1], "v": 1});
+        let after = json!({"user": {"name": "Bo", "age": 31, "x": 1}, "items": [2]});
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["user.age"]);
+        assert_eq!(
+            delta["user.age"],
+            Change::Modify {
+                old: json!(30),
+                new: json!(31)
+            }
+        );
+
+        // An enclosing value that changes type is not reported.
+        assert!(diff_with(&before, &json!({"user": "Ann"}), &options).is_empty());
+
+        // Everything below a listed path is reported, and patterns take `*`.
+        let options = DiffOptions {
+            only_paths: vec!["rows.*.qty".to_string(), "meta".to_string()],
+            array_strategy: ArrayStrategy::SameLengthObjects,
+            ..Default::default()
+        };
+        let before = json!({"rows": [{"qty": 1, "sku": "a"}], "meta": {"a": 1}});
+        let after = json!({"rows": [{"qty": 2, "sku": "b"}], "meta": {"b": {"c": 1}}});
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(
+            delta.keys().collect::<Vec<_>>(),
+            ["meta.a", "meta.b", "rows.0.qty"]
+        );
+    }
+
+    #[test]
+    fn limits_degrade_diff_and_fail_try_diff() {
+        let before = json!({"a": {"b": {"c": 1, "d": 2}}, "x": [[1]]});
+        let after = json!({"a": {"b": {"c": 1, "d": 3}}, "x": [[2]]});
+        let options = DiffOptions {
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["a.b", "x"]);
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(
+            try_diff(&before, &after, &options),
+            Err(JsonDiffError::PathTooDeep {
+                path: "a.b.d".to_string(),
+                max_depth: 2
+            })
+        );
+        let shallow = DiffOptions {
+            max_depth: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            try_diff(&before, &after, &shallow),
+            Ok(diff(&before, &after))
+        );
+
+        let options = DiffOptions {
+            max_changes: Some(1),
+            ..Default::default()
+        };
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(
+            delta[""],
+            Change::Modify {
+                old: before.clone(),
+                new: after.clone()
+            }
+        );
+        assert_eq!(delta.len(), 1);
+        assert_eq!(
+            try_diff(&before, &after, &options),
+            Err(JsonDiffError::TooManyChanges {
+                count: 2,
+                max_changes: 1
+            })
+        );
+        assert!(try_diff(&before, &before, &options).unwrap().is_empty());
+
+        let rows = DiffOptions {
+            array_strategy: ArrayStrategy::SameLengthObjects,
+            ..Default::default()
+        };
+        assert_eq!(
+            try_diff(&before, &after, &rows),
+            Err(JsonDiffError::UnsupportedArray {
+                path: "x".to_string()
+            })
+        );
+        let rows_before = json!({"rows": [{"n": 1}]});
+        let rows_after = json!({"rows": [{"n": 2}]});
+        assert_eq!(
+            try_diff(&rows_before, &rows_after, &rows),
+            Ok(diff_with(&rows_before, &rows_after, &rows))
+        );
+    }
+
+    #[test]
+    fn try_diff_checks_depth_only_where_inputs_differ() {
+        let deep = json!({"a": {"b": {"c": {"d": 1}}}, "e": 1});
+        let options = DiffOptions {
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        assert!(try_diff(&deep, &deep, &options).unwrap().is_empty());
+        // Changing `e`, or removing `a` whole, collapses nothing.
+        let shallow_change = json!({"a": {"b": {"c": {"d": 1}}}, "e": 2});
+        assert_eq!(
+            try_diff(&deep, &shallow_change, &options),
+            Ok(diff(&deep, &shallow_change))
+        );
+        assert_eq!(
+            try_diff(&deep, &json!({"e": 1}), &options),
+            Ok(diff(&deep, &json!({"e": 1})))
+        );
+        let deep_change = json!({"a": {"b": {"c": {"d": 2}}}, "e": 1});
+        assert_eq!(
+            try_diff(&deep, &deep_change, &options),
+            Err(JsonDiffError::PathTooDeep {
+                path: "a.b.c".to_string(),
+                max_depth: 2
+            })
+        );
+    }
+
+    #[test]
+    fn limits_do_not_collapse_filtered_paths() {
+        let before = json!({"a": {"b": {"updated_at": 1, "v": 1}, "c": {"v": 1}}});
+        let after = json!({"a": {"b": {"updated_at": 2, "v": 1}, "c": {"v": 2}}});
+        let options = DiffOptions {
+            ignore_paths: vec!["a.b.updated_at".to_string()],
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["a.c"]);
+
+        let before = json!({"secret": "s1", "x": 1, "y": 1});
+        let after = json!({"secret": "s2", "x": 2, "y": 2});
+        let options = DiffOptions {
+            ignore_paths: vec!["secret".to_string()],
+            max_changes: Some(1),
+            ..Default::default()
+        };
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["x", "y"]);
+
+        let options = DiffOptions {
+            only_paths: vec!["x".to_string(), "y".to_string()],
+            max_changes: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(diff_with(&before, &after, &options).len(), 2);
+    }
+
+    #[test]
+    fn minimize_cost_picks_the_smaller_encoding() {
+        let options = DiffOptions {
+            minimize_cost: true,
+            ..Default::default()
+        };
+        // Every leaf of a small object changed: one `Modify` is smaller.
+        let before = json!({"p": {"a": 1, "b": 2, "c": 3, "d": 4}, "keep": "x"});
+        let after = json!({"p": {"a": 5, "b": 6, "c": 7, "d": 8}, "keep": "x"});
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["p"]);
+        assert!(estimated_size(&delta) < estimated_size(&diff(&before, &after)));
+        assert_eq!(apply(&before, &delta), after);
+
+        // One leaf of a large object changed: the leaf change is smaller.
+        let long = "lorem ipsum ".repeat(20);
+        let before = json!({"p": {"a": 1, "text": long, "q": {"x": 1, "y": 2}}});
+        let after = json!({"p": {"a": 2, "text": long, "q": {"x": 3, "y": 4}}});
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["p.a", "p.q"]);
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(revert(&after, &delta), before);
+
+        // An ignored path inside keeps the subtree from being replaced whole.
+        let options = DiffOptions {
+            minimize_cost: true,
+            ignore_paths: vec!["p.s".to_string()],
+            ..Default::default()
+        };
+        let before = json!({"p": {"a": 1, "b": 2, "c": 3, "s": "old"}});
+        let after = json!({"p": {"a": 5, "b": 6, "c": 7, "s": "new"}});
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["p.a", "p.b", "p.c"]);
+        assert_eq!(apply(&before, &delta)["p"]["s"], json!("old"));
+    }
+
+    #[test]
+    fn template_diff_ignores_extra_keys() {
+        let options = DiffOptions {
+            only_template_keys: true,
+            ..Default::default()
+        };
+        let template = json!({
+            "port": 80,
+            "tls": {"enabled": false, "cert": null},
+            "hosts": ["a"],
+            "log": "info"
+        });
+        let config = json!({
+            "port": 8080,
+            "tls": {"enabled": false, "cert": "/etc/cert", "extra": true},
+            "hosts": ["a", "b"],
+            "custom": {"anything": 1}
+        });
+        let delta = diff_with(&template, &config, &options);
+        assert_eq!(
+            delta.keys().collect::<Vec<_>>(),
+            ["hosts", "log", "port", "tls.cert"]
+        );
+        assert_eq!(delta["log"], Change::Remove(json!("info")));
+        assert_eq!(
+            delta["port"],
+            Change::Modify {
+                old: json!(80),
+                new: json!(8080)
+            }
+        );
+        assert!(
+            diff_with(&template, &json!({"x": 1}), &options)
+                .values()
+                .all(|change| matches!(change, Change::Remove(_)))
+        );
+        assert!(diff(&template, &config).contains_key("custom"));
+    }
+
+    #[test]
+    fn empty_containers_can_equal_absent_keys() {
+        let objects = DiffOptions {
+            empty_object_equals_absent: true,
+            ..Default::default()
+        };
+        let arrays = DiffOptions {
+            empty_array_equals_absent: true,
+            ..Default::default()
+        };
+        let with_object = json!({"a": 1, "meta": {}});
+        let with_array = json!({"a": 1, "tags": []});
+        let without = json!({"a": 1});
+
+        assert!(diff_with(&with_object, &without, &objects).is_empty());
+        assert!(diff_with(&without, &with_object, &objects).is_empty());
+        assert!(diff_with(&with_array, &without, &arrays).is_empty());
+        assert!(diff_with(&without, &with_array, &arrays).is_empty());
+
+        // Each option only covers its own kind of container.
+        assert_eq!(diff_with(&with_array, &without, &objects).len(), 1);
+        assert_eq!(diff_with(&with_object, &without, &arrays).len(), 1);
+
+        // Non-empty containers, and empty ones replacing other values, are still changes.
+        let both = DiffOptions {
+            empty_object_equals_absent: true,
+            empty_array_equals_absent: true,
+            ..Default::default()
+        };
+        let filled = json!({"a": 1, "meta": {"k": null}, "tags": [0]});
+        assert_eq!(diff_with(&filled, &without, &both).len(), 2);
+        assert_eq!(diff_with(&without, &filled, &both).len(), 2);
+        let delta = diff_with(&json!({"a": 1, "meta": null}), &with_object, &both);
+        assert_eq!(
+            delta["meta"],
+            Change::Modify {
+                old: json!(null),
+                new: json!({})
+            }
+        );
+    }
+
+    #[test]
+    fn type_changes_revert_exactly_under_every_array_strategy() {
+        let samples = [
+            json!(null),
+            json!(true),
+            json!(7),
+            json!(2.5),
+            json!("7"),
+            json!([]),
+            json!([1, {"id": 1}]),
+            json!({}),
+            json!({"0": 1, "id": 1}),
+        ];
+        // Each strategy, and whether it diffs the fields of `rows` elements individually.
+        let strategies = [
+            (DiffOptions::default(), false),
+            (
+                DiffOptions {
+                    array_strategy: ArrayStrategy::SameLengthObjects,
+                    ..Default::default()
+                },
+                true,
+            ),
+            (
+                DiffOptions {
+                    array_strategy: ArrayStrategy::Multiset,
+                    ..Default::default()
+                },
+                false,
+            ),
+            (
+                DiffOptions {
+                    set_arrays: vec!["v".to_string(), "rows.0.v".to_string()],
+                    ..Default::default()
+                },
+                false,
+            ),
+            (
+                DiffOptions {
+                    match_fields: vec![("*".to_string(), vec!["id".to_string()])],
+                    ..Default::default()
+                },
+                true,
+            ),
+        ];
+        let wrap = |path: &str, v: &Value| match path {
+            "v" => json!({"v": v}),
+            _ => json!({"rows": [{"id": 1, "v": v}]}),
+        };
+        for (options, per_element) in &strategies {
+            for old in &samples {
+                for new in &samples {
+                    if std::mem::discriminant(old) == std::mem::discriminant(new) {
+                        continue;
+                    }
+                    for path in ["v", "rows.0.v"] {
+                        let (before, after) = (wrap(path, old), wrap(path, new));
+                        let delta = diff_with(&before, &after, options);
+                        if path == "v" || *per_element {
+                            let change = Change::Modify {
+                                old: old.clone(),
+                                new: new.clone(),
+                            };
+                            assert_eq!(delta.len(), 1, "{before} -> {after}");
+                            assert_eq!(delta.get(path), Some(&change), "{before} -> {after}");
+                        }
+                        assert_eq!(apply(&before, &delta), after);
+                        assert_eq!(try_revert(&after, &delta), Ok(before));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn type_changes_that_cannot_round_trip() {
+        // Without the old value the change is refused rather than guessed at.
+        let options = DiffOptions {
+            capture_old: false,
+            ..Default::default()
+        };
+        let delta = diff_with(&json!({"v": [1]}), &json!({"v": {"0": 1}}), &options);
+        assert_eq!(
+            try_revert(&json!({"v": {"0": 1}}), &delta),
+            Err(JsonDiffError::Irreversible {
+                path: "v".to_string()
+            })
+        );
+
+        // Changes the options treat as equal are not recorded at all.
+        let options = DiffOptions {
+            coerce: vec![("v".to_string(), JsonType::Number)],
+            numbers_value_equal: true,
+            ..Default::default()
+        };
+        assert!(diff_with(&json!({"v": "1"}), &json!({"v": 1}), &options).is_empty());
+        assert!(diff_with(&json!({"v": 1}), &json!({"v": 1.0}), &options).is_empty());
+    }
+
+    #[test]
+    fn mixed_changes_in_user_profile() {
+        let before = json!({
+            "user": {
+                "name": "John",
+                "age": 30,
+                "settings": {"theme": "dark", "notifications": true}
+            },
+            "status": "active"
+        });
+        let after = json!({
+            "user": {
+                "name": "John",
+                "age": 31,
+                "settings": {"theme": "light", "notifications": true, "language": "en-US"}
+            },
+            "status": "inactive"
+        });
+        let delta: Delta = diff(&before, &after);
+
+        let mut expected = Delta::new();
+        expected.insert(
+            "user.age".to_string(),
+            Change::Modify {
+                old: json!(30),
+                new: json!(31),
+            },
+        );
+        expected.insert(
+            "user.settings.theme".to_string(),
+            Change::Modify {
+                old: json!("dark"),
+                new: json!("light"),
+            },
+        );
+        expected.insert(
+            "user.settings.language".to_string(),
+            Change::Add(json!("en-US")),
+        );
+        expected.insert(
+            "status".to_string(),
+            Change::Modify {
+                old: json!("active"),
+                new: json!("inactive"),
+            },
+        );
+        assert_eq!(delta, expected);
+
+        let reverted = revert(&after, &delta);
+        assert_eq!(reverted, before);
+
+        let applied = apply(&before, &delta);
+        assert_eq!(applied, after);
+    }
+
+    #[test]
+    fn object_with_numeric_keys() {
+        let before = json!({"0": "zero", "1": "one"});
+        let after = json!({"0": "ZERO", "2": "two"});
         let delta: Delta = diff(&before, &after);
 
         let mut expected = Delta::new();
@@ -1164,4 +2727,855 @@ mod tests {
         assert_eq!(revert(&after, &delta), before);
         assert_eq!(apply(&before, &delta), after);
     }
+
+    fn set_options(path: &str) -> DiffOptions {
+        let mut options = DiffOptions::default();
+        options.set_arrays.push(path.to_string());
+        options
+    }
+
+    #[test]
+    fn set_array_reorder_is_noop() {
+        let before = json!({"user": {"roles": ["admin", "editor", {"scope": "x"}]}});
+        let after = json!({"user": {"roles": [{"scope": "x"}, "editor", "admin"]}});
+        let delta = diff_with(&before, &after, &set_options("user.roles"));
+        assert_eq!(delta, Delta::new());
+
+        // Without the option the whole array is replaced.
+        assert_eq!(diff(&before, &after).len(), 1);
+    }
+
+    #[test]
+    fn set_array_single_add() {
+        let before = json!({"tags": ["a", "b"]});
+        let after = json!({"tags": ["a", "b", "c"]});
+        let delta = diff_with(&before, &after, &set_options("tags"));
+
+        let mut expected = Delta::new();
+        expected.insert("tags.+2".to_string(), Change::Add(json!("c")));
+        assert_eq!(delta, expected);
+
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(revert(&after, &delta), before);
+    }
+
+    #[test]
+    fn set_array_single_remove() {
+        let before = json!({"tags": ["a", {"k": 1}, "c"]});
+        let after = json!({"tags": ["c", "a"]});
+        let delta = diff_with(&before, &after, &set_options("tags"));
+
+        let mut expected = Delta::new();
+        expected.insert("tags.1".to_string(), Change::Remove(json!({"k": 1})));
+        assert_eq!(delta, expected);
+
+        assert_eq!(apply(&before, &delta), json!({"tags": ["a", "c"]}));
+        assert_eq!(
+            revert(&after, &delta),
+            json!({"tags": ["c", {"k": 1}, "a"]})
+        );
+    }
+
+    #[test]
+    fn set_array_mixed_changes_round_trip() {
+        let before = json!(["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l"]);
+        let after = json!([
+            "a", "x", "c", "d", "e", "f", "g", "h", "i", "j", "y", "l", "z"
+        ]);
+        let delta = diff_with(&before, &after, &set_options(""));
+
+        let mut expected = Delta::new();
+        expected.insert("1".to_string(), Change::Remove(json!("b")));
+        expected.insert("10".to_string(), Change::Remove(json!("k")));
+        expected.insert("+1".to_string(), Change::Add(json!("x")));
+        expected.insert("+10".to_string(), Change::Add(json!("y")));
+        expected.insert("+12".to_string(), Change::Add(json!("z")));
+        assert_eq!(delta, expected);
+
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(revert(&after, &delta), before);
+    }
+
+    #[test]
+    fn array_paths_distinguish_old_and_new_indices() {
+        let before = json!({"steps": ["plan", "build", "test", "ship"]});
+        let after = json!({"steps": ["build", "lint", "test", "ship", "monitor"]});
+        let delta = diff_with(&before, &after, &set_options("steps"));
+
+        let mut expected = Delta::new();
+        expected.insert("steps.0".to_string(), Change::Remove(json!("plan")));
+        expected.insert("steps.+1".to_string(), Change::Add(json!("lint")));
+        expected.insert("steps.+4".to_string(), Change::Add(json!("monitor")));
+        assert_eq!(delta, expected);
+
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(revert(&after, &delta), before);
+    }
+
+    #[test]
+    fn set_array_removals_apply_highest_index_first() {
+        let before = json!({"ids": [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]});
+        let after = json!({"ids": [0, 1, 3, 4, 5, 6, 7, 8, 9, 11]});
+        let delta = diff_with(&before, &after, &set_options("ids"));
+        assert_eq!(delta.keys().collect::<Vec<_>>(), vec!["ids.10", "ids.2"],);
+
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(revert(&after, &delta), before);
+    }
+
+    #[test]
+    fn apply_checked_empty_delta_reports_no_change() {
+        let object = json!({"a": 1});
+        let (applied, changed) = apply_checked(&object, &Delta::new());
+        assert_eq!(applied, object);
+        assert!(!changed);
+    }
+
+    #[test]
+    fn apply_checked_real_change_reports_change() {
+        let before = json!({"a": 1, "b": {"c": 2}});
+        let after = json!({"a": 1, "b": {"c": 3}});
+        let (applied, changed) = apply_checked(&before, &diff(&before, &after));
+        assert_eq!(applied, after);
+        assert!(changed);
+    }
+
+    #[test]
+    fn apply_checked_add_of_equal_value_reports_no_change() {
+        let object = json!({"a": 1, "b": {"c": [1, 2]}});
+        let mut delta = Delta::new();
+        delta.insert("b.c".to_string(), Change::Add(json!([1, 2])));
+        delta.insert("d".to_string(), Change::Remove(json!(null)));
+
+        let (applied, changed) = apply_checked(&object, &delta);
+        assert_eq!(applied, object);
+        assert!(!changed);
+    }
+
+    #[test]
+    fn move_round_trips_through_revert() {
+        let before = json!({"profile": {"name": "Ann"}, "count": 1});
+        let after = json!({"user": {"name": "Ann"}, "count": 2});
+
+        let mut delta = Delta::new();
+        delta.insert(
+            "user".to_string(),
+            Change::Move {
+                from: "profile".to_string(),
+                to: "user".to_string(),
+            },
+        );
+        delta.insert(
+            "count".to_string(),
+            Change::Modify {
+                old: json!(1),
+                new: json!(2),
+            },
+        );
+
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(revert(&after, &delta), before);
+        assert_eq!(try_revert(&after, &delta), Ok(before));
+    }
+
+    #[test]
+    fn move_inverse_swaps_endpoints() {
+        let change = Change::Move {
+            from: "a.b".to_string(),
+            to: "c".to_string(),
+        };
+        assert_eq!(
+            change.inverse(),
+            Change::Move {
+                from: "c".to_string(),
+                to: "a.b".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn copy_reverts_while_target_matches_source() {
+        let before = json!({"billing": {"city": "Lima"}});
+        let mut delta = Delta::new();
+        delta.insert(
+            "shipping".to_string(),
+            Change::Copy {
+                from: "billing".to_string(),
+                to: "shipping".to_string(),
+            },
+        );
+
+        let applied = apply(&before, &delta);
+        assert_eq!(
+            applied,
+            json!({"billing": {"city": "Lima"}, "shipping": {"city": "Lima"}})
+        );
+        assert_eq!(try_revert(&applied, &delta), Ok(before));
+    }
+
+    #[test]
+    fn copy_with_diverged_target_cannot_be_reverted() {
+        let current = json!({"billing": {"city": "Lima"}, "shipping": {"city": "Cusco"}});
+        let mut delta = Delta::new();
+        delta.insert(
+            "shipping".to_string(),
+            Change::Copy {
+                from: "billing".to_string(),
+                to: "shipping".to_string(),
+            },
+        );
+
+        assert_eq!(
+            try_revert(&current, &delta),
+            Err(JsonDiffError::IrreversibleCopy {
+                from: "billing".to_string(),
+                to: "shipping".to_string(),
+            })
+        );
+        // The lenient revert leaves the diverged copy alone.
+        assert_eq!(revert(&current, &delta), current);
+    }
+
+    #[test]
+    fn sentinel_strings_compare_equal() {
+        let before = json!({"reading": "NaN", "series": [1.5, "Infinity"], "unit": "K"});
+        let after = json!({"reading": "NaN", "series": [1.5, "NaN"], "unit": "C"});
+
+        let options = DiffOptions {
+            equal_sentinels: vec!["NaN".to_string(), "Infinity".to_string()],
+            ..Default::default()
+        };
+        let delta = diff_with(&before, &after, &options);
+
+        let mut expected = Delta::new();
+        expected.insert(
+            "unit".to_string(),
+            Change::Modify {
+                old: json!("K"),
+                new: json!("C"),
+            },
+        );
+        assert_eq!(delta, expected);
+
+        // Without the option the sentinel swap inside the array is a change.
+        assert!(diff(&before, &after).contains_key("series"));
+    }
+
+    #[test]
+    fn skip_predicate_ignores_marked_subtrees() {
+        let before = json!({
+            "total": {"_computed": true, "value": 10},
+            "items": {"subtotal": {"_computed": true, "value": 4}, "count": 2},
+            "name": "cart"
+        });
+        let after = json!({
+            "total": {"_computed": true, "value": 12},
+            "items": {"subtotal": {"_computed": true, "value": 6}, "count": 3},
+            "extra": {"_computed": true, "value": 1},
+            "name": "cart"
+        });
+
+        let options = DiffOptions {
+            skip: Some(ValuePredicate::new(|_path, value| {
+                value.get("_computed") == Some(&json!(true))
+            })),
+            ..Default::default()
+        };
+        let delta = diff_with(&before, &after, &options);
+
+        let mut expected = Delta::new();
+        expected.insert(
+            "items.count".to_string(),
+            Change::Modify {
+                old: json!(2),
+                new: json!(3),
+            },
+        );
+        assert_eq!(delta, expected);
+    }
+
+    #[test]
+    fn diff_subtree_matches_full_diff_after_local_edit() {
+        let before = json!({
+            "doc": {"title": "Draft", "sections": {"intro": "hi", "body": "text"}},
+            "meta": {"rev": 1}
+        });
+        let mut after = json!({
+            "doc": {"title": "Final", "sections": {"intro": "hi", "body": "text"}},
+            "meta": {"rev": 2}
+        });
+        let mut live = diff(&before, &after);
+
+        // Edit only the sections subtree, then refresh just that part of the delta.
+        after["doc"]["sections"] = json!({"intro": "hello", "outro": "bye"});
+        diff_subtree("doc.sections", &before, &after, &mut live);
+        assert_eq!(live, diff(&before, &after));
+
+        // Reverting the edit drops the stale entries again.
+        after["doc"]["sections"] = before["doc"]["sections"].clone();
+        diff_subtree("doc.sections", &before, &after, &mut live);
+        assert_eq!(live, diff(&before, &after));
+        assert!(!live.keys().any(|path| path.starts_with("doc.sections")));
+    }
+
+    #[test]
+    fn diff_subtree_handles_added_and_removed_subtrees() {
+        let before = json!({"a": {"x": 1}});
+        let after = json!({"b": {"y": 2}});
+        let mut live = Delta::new();
+        diff_subtree("a", &before, &after, &mut live);
+        diff_subtree("b", &before, &after, &mut live);
+        assert_eq!(live, diff(&before, &after));
+    }
+
+    #[test]
+    fn without_old_values_deltas_apply_but_do_not_revert() {
+        let before = json!({"status": "draft", "meta": {"rev": 1}, "old": true});
+        let after = json!({"status": "published", "meta": {"rev": 2}});
+        let options = DiffOptions {
+            capture_old: false,
+            ..Default::default()
+        };
+        let delta = diff_with(&before, &after, &options);
+
+        let mut expected = Delta::new();
+        expected.insert("status".to_string(), Change::Set(json!("published")));
+        expected.insert("meta.rev".to_string(), Change::Set(json!(2)));
+        expected.insert("old".to_string(), Change::Remove(json!(true)));
+        assert_eq!(delta, expected);
+
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(
+            try_revert(&after, &delta),
+            Err(JsonDiffError::Irreversible {
+                path: "status".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn whitespace_only_string_changes_are_suppressed_when_enabled() {
+        let options = DiffOptions {
+            ignore_whitespace: true,
+            ..Default::default()
+        };
+        let before = json!({"body": "Hello,\n  world. ", "notes": ["a  b"]});
+        let after = json!({"body": "  Hello, world.", "notes": ["a\tb"]});
+        assert!(diff_with(&before, &after, &options).is_empty());
+        assert_eq!(diff(&before, &after).len(), 2);
+    }
+
+    #[test]
+    fn whitespace_option_keeps_content_changes() {
+        let options = DiffOptions {
+            ignore_whitespace: true,
+            ..Default::default()
+        };
+        let before = json!({"body": "Hello, world."});
+        let after = json!({"body": "Hello,  there."});
+        let mut expected = Delta::new();
+        expected.insert(
+            "body".to_string(),
+            Change::Modify {
+                old: json!("Hello, world."),
+                new: json!("Hello,  there."),
+            },
+        );
+        assert_eq!(diff_with(&before, &after, &options), expected);
+    }
+
+    #[test]
+    fn case_only_string_changes_are_suppressed_when_enabled() {
+        let options = DiffOptions {
+            case_insensitive_values: true,
+            ..Default::default()
+        };
+        let before = json!({"status": "Active", "tags": ["NEW"], "title": "Draft  one"});
+        let after = json!({"status": "active", "tags": ["new"], "title": "draft one"});
+        assert_eq!(diff_with(&before, &after, &options).len(), 1);
+        assert_eq!(diff(&before, &after).len(), 3);
+
+        // Combined with `ignore_whitespace`, both differences are ignored at once.
+        let both = DiffOptions {
+            ignore_whitespace: true,
+            ..options.clone()
+        };
+        assert!(diff_with(&before, &after, &both).is_empty());
+    }
+
+    #[test]
+    fn case_insensitive_values_keep_text_changes() {
+        let options = DiffOptions {
+            case_insensitive_values: true,
+            ..Default::default()
+        };
+        let before = json!({"status": "Active", "Key": "x"});
+        let after = json!({"status": "inactive", "key": "x"});
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(
+            delta["status"],
+            Change::Modify {
+                old: json!("Active"),
+                new: json!("inactive"),
+            }
+        );
+        // Keys are still compared exactly.
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["Key", "key", "status"]);
+    }
+
+    #[test]
+    fn numbers_compare_by_value_when_enabled() {
+        let options = DiffOptions {
+            numbers_value_equal: true,
+            ..Default::default()
+        };
+        let before = json!({"n": 1, "neg": -3, "list": [2.0, 0]});
+        let after = json!({"n": 1.0, "neg": -3.0, "list": [2, 0.0]});
+        assert_eq!(diff(&before, &after).len(), 3);
+        assert!(diff_with(&before, &after, &options).is_empty());
+        assert_eq!(diff_with(&json!(1), &json!(1.5), &options).len(), 1);
+    }
+
+    #[test]
+    fn numbers_beyond_f64_precision_stay_changed() {
+        let options = DiffOptions {
+            numbers_value_equal: true,
+            ..Default::default()
+        };
+        let large = json!(9_007_199_254_740_993_u64);
+        let lossy = json!(9_007_199_254_740_993_u64 as f64);
+        assert_eq!(diff_with(&large, &lossy, &options), diff(&large, &lossy),);
+        assert_eq!(diff_with(&large, &lossy, &options).len(), 1);
+        // The cutoff itself is still exact.
+        let cutoff = 9_007_199_254_740_992_u64;
+        assert!(diff_with(&json!(cutoff), &json!(cutoff as f64), &options).is_empty());
+    }
+
+    #[test]
+    fn coercion_suppresses_loosely_serialized_changes() {
+        let options = DiffOptions {
+            coerce: vec![
+                ("count".to_string(), JsonType::Number),
+                ("items.*.active".to_string(), JsonType::Bool),
+            ],
+            ..Default::default()
+        };
+        let before = json!({"count": "42", "items": {"a": {"active": "true"}}});
+        let after = json!({"count": 42, "items": {"a": {"active": true}}});
+        assert!(diff_with(&before, &after, &options).is_empty());
+    }
+
+    #[test]
+    fn failed_coercion_keeps_the_change() {
+        let options = DiffOptions {
+            coerce: vec![("count".to_string(), JsonType::Number)],
+            ..Default::default()
+        };
+        let before = json!({"count": "forty-two", "other": "1"});
+        let after = json!({"count": 42, "other": 1});
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(
+            delta.get("count"),
+            Some(&Change::Modify {
+                old: json!("forty-two"),
+                new: json!(42),
+            })
+        );
+        // Paths without a configured type are not coerced.
+        assert!(delta.contains_key("other"));
+
+        // Integers compare exactly, and non-finite strings do not coerce.
+        let before = json!({"count": "9007199254740993", "a": "NaN", "b": "1.0"});
+        let after = json!({"count": 9007199254740992_u64, "a": "nan", "b": 1});
+        let options = DiffOptions {
+            coerce: vec![("*".to_string(), JsonType::Number)],
+            ..Default::default()
+        };
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["a", "count"]);
+    }
+
+    #[test]
+    fn watched_paths_are_recorded_when_unchanged() {
+        let options = DiffOptions {
+            watched: vec![
+                "owner".to_string(),
+                "status".to_string(),
+                "missing".to_string(),
+            ],
+            ..Default::default()
+        };
+        let before = json!({"owner": {"id": 7}, "status": "open", "title": "a"});
+        let after = json!({"owner": {"id": 7}, "status": "closed", "title": "b"});
+        let delta = diff_with(&before, &after, &options);
+
+        let mut expected = Delta::new();
+        expected.insert("owner".to_string(), Change::Unchanged(json!({"id": 7})));
+        expected.insert(
+            "status".to_string(),
+            Change::Modify {
+                old: json!("open"),
+                new: json!("closed"),
+            },
+        );
+        expected.insert(
+            "title".to_string(),
+            Change::Modify {
+                old: json!("a"),
+                new: json!("b"),
+            },
+        );
+        assert_eq!(delta, expected);
+
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(revert(&after, &delta), before);
+        assert_eq!(try_apply(&before, &delta), Ok(after));
+    }
+
+    #[test]
+    fn watched_paths_missing_on_one_side_are_reported() {
+        let options = DiffOptions {
+            watched: vec![
+                "extra".to_string(),
+                "empty".to_string(),
+                "gone".to_string(),
+                "user.name".to_string(),
+            ],
+            only_template_keys: true,
+            empty_object_equals_absent: true,
+            ..Default::default()
+        };
+        let before = json!({"gone": 1});
+        let after = json!({"extra": 2, "empty": {}, "user": {"name": "ann"}});
+        let delta = diff_with(&before, &after, &options);
+
+        let mut expected = Delta::new();
+        // Left out by `only_template_keys`, but watched.
+        expected.insert("extra".to_string(), Change::Add(json!(2)));
+        // Counted as absent, so examined and unchanged.
+        expected.insert("empty".to_string(), Change::Unchanged(json!({})));
+        expected.insert("gone".to_string(), Change::Remove(json!(1)));
+        // `only_template_keys` leaves out the added `user` too, so nothing covers it.
+        expected.insert("user.name".to_string(), Change::Add(json!("ann")));
+        assert_eq!(delta, expected);
+
+        // A path missing on both sides, or inside a reported change, is not repeated.
+        let options = DiffOptions {
+            watched: vec!["nowhere".to_string(), "user.name".to_string()],
+            ..Default::default()
+        };
+        let delta = diff_with(&before, &after, &options);
+        assert!(!delta.contains_key("nowhere"));
+        assert!(!delta.contains_key("user.name"));
+        assert!(delta.contains_key("user"));
+    }
+
+    #[test]
+    fn renamed_keys_with_equal_values_become_renames() {
+        let options = DiffOptions {
+            detect_renames: true,
+            ..Default::default()
+        };
+        let before = json!({"user": {"fullName": "X", "age": 3}});
+        let after = json!({"user": {"name": "X", "age": 3}});
+        let delta = diff_with(&before, &after, &options);
+
+        let mut expected = Delta::new();
+        expected.insert(
+            "user.name".to_string(),
+            Change::Rename {
+                from: "user.fullName".to_string(),
+                to: "user.name".to_string(),
+            },
+        );
+        assert_eq!(delta, expected);
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(revert(&after, &delta), before);
+        assert_eq!(try_revert(&after, &delta), Ok(before));
+    }
+
+    #[test]
+    fn renames_need_equal_values_in_the_same_object() {
+        let options = DiffOptions {
+            detect_renames: true,
+            ..Default::default()
+        };
+        let before = json!({"fullName": "X", "a": {"old": 1}});
+        let after = json!({"name": "Y", "b": {"new": 1}});
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(delta, diff(&before, &after));
+        assert!(
+            delta
+                .values()
+                .all(|change| !matches!(change, Change::Rename { .. }))
+        );
+    }
+
+    #[test]
+    fn same_length_object_arrays_are_diffed_by_position() {
+        let options = DiffOptions {
+            array_strategy: ArrayStrategy::SameLengthObjects,
+            ..Default::default()
+        };
+        let before = json!({"rows": [{"id": 1, "status": "open"}, {"id": 2, "status": "open"}]});
+        let after = json!({"rows": [{"id": 1, "status": "open"}, {"id": 2, "status": "done"}]});
+        let delta = diff_with(&before, &after, &options);
+
+        let mut expected = Delta::new();
+        expected.insert(
+            "rows.1.status".to_string(),
+            Change::Modify {
+                old: json!("open"),
+                new: json!("done"),
+            },
+        );
+        assert_eq!(delta, expected);
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(revert(&after, &delta), before);
+    }
+
+    #[test]
+    fn ignore_paths_drop_whole_subtrees() {
+        let options = DiffOptions {
+            ignore_paths: vec!["items.*.meta".to_string(), "etag".to_string()],
+            ..Default::default()
+        };
+        let before = json!({"etag": "a", "items": {"x": {"n": 1, "meta": {"at": 1}}}});
+        let after = json!({"items": {"x": {"n": 2, "meta": {"at": 2, "by": "bo"}}}});
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["items.x.n"]);
+    }
+
+    #[test]
+    fn equal_ignoring_disregards_only_the_listed_paths() {
+        let a = json!({"user": {"name": "Ann", "seen": "2024-01-01"}, "ok": true});
+        let b = json!({"user": {"name": "Ann", "seen": "2024-02-01"}, "ok": true});
+        assert!(equal_ignoring(&a, &b, &["user.seen"]));
+        assert!(equal_ignoring(&a, &b, &["user"]));
+        assert!(!equal_ignoring(&a, &b, &["ok"]));
+        assert!(!equal_ignoring(&a, &b, &[]));
+    }
+
+    #[test]
+    fn multiset_arrays_ignore_order_but_count_duplicates() {
+        let options = DiffOptions {
+            array_strategy: ArrayStrategy::Multiset,
+            ..Default::default()
+        };
+        let reordered = diff_with(
+            &json!({"events": ["a", "a", {"k": 1}, "b"]}),
+            &json!({"events": ["b", {"k": 1}, "a", "a"]}),
+            &options,
+        );
+        assert!(reordered.is_empty());
+
+        let before = json!({"events": ["a", "a"]});
+        let fewer = diff_with(&before, &json!({"events": ["a"]}), &options);
+        let mut expected = Delta::new();
+        expected.insert("events.1".to_string(), Change::Remove(json!("a")));
+        assert_eq!(fewer, expected);
+        assert_eq!(apply(&before, &fewer), json!({"events": ["a"]}));
+
+        let more = diff_with(&before, &json!({"events": ["a", "b", "a", "a"]}), &options);
+        let mut expected = Delta::new();
+        expected.insert("events.+1".to_string(), Change::Add(json!("b")));
+        expected.insert("events.+3".to_string(), Change::Add(json!("a")));
+        assert_eq!(more, expected);
+        assert_eq!(
+            apply(&before, &more),
+            json!({"events": ["a", "b", "a", "a"]})
+        );
+    }
+
+    #[test]
+    fn match_fields_pair_elements_by_identity() {
+        let options = DiffOptions {
+            match_fields: vec![("orders.*.lines".to_string(), vec!["sku".to_string()])],
+            ..Default::default()
+        };
+        let before = json!({"orders": {"o1": {"lines": [
+            {"sku": "A", "qty": 1},
+            {"sku": "B", "qty": 2}
+        ]}}});
+        let after = json!({"orders": {"o1": {"lines": [
+            {"sku": "A", "qty": 5},
+            {"sku": "B", "qty": 2}
+        ]}}});
+        let delta = diff_with(&before, &after, &options);
+
+        let mut expected = Delta::new();
+        expected.insert(
+            "orders.o1.lines.0.qty".to_string(),
+            Change::Modify {
+                old: json!(1),
+                new: json!(5),
+            },
+        );
+        assert_eq!(delta, expected);
+        assert_eq!(apply(&before, &delta), after);
+    }
+
+    #[test]
+    fn match_fields_replace_elements_whose_identity_changes() {
+        let options = DiffOptions {
+            match_fields: vec![("items".to_string(), vec!["sku".to_string()])],
+            ..Default::default()
+        };
+        let before = json!({"items": [{"sku": "A", "qty": 1}, {"sku": "B", "qty": 2}]});
+        let after = json!({"items": [{"sku": "A", "qty": 1}, {"sku": "C", "qty": 2}]});
+        let delta = diff_with(&before, &after, &options);
+
+        let mut expected = Delta::new();
+        expected.insert(
+            "items.1".to_string(),
+            Change::Remove(json!({"sku": "B", "qty": 2})),
+        );
+        expected.insert(
+            "items.+1".to_string(),
+            Change::Add(json!({"sku": "C", "qty": 2})),
+        );
+        assert_eq!(delta, expected);
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(revert(&after, &delta), before);
+    }
+
+    #[test]
+    fn object_array_strategy_falls_back_to_whole_arrays() {
+        let options = DiffOptions {
+            array_strategy: ArrayStrategy::SameLengthObjects,
+            ..Default::default()
+        };
+        for (before, after) in [
+            (
+                json!({"rows": [{"id": 1}]}),
+                json!({"rows": [{"id": 1}, {"id": 2}]}),
+            ),
+            (json!({"rows": [1, 2]}), json!({"rows": [1, 3]})),
+            (
+                json!({"rows": [{"id": 1}, 2]}),
+                json!({"rows": [{"id": 9}, 2]}),
+            ),
+        ] {
+            let delta = diff_with(&before, &after, &options);
+            assert_eq!(delta.keys().collect::<Vec<_>>(), ["rows"]);
+            assert_eq!(apply(&before, &delta), after);
+        }
+    }
+
+    #[test]
+    fn similar_values_are_renamed_with_nested_changes() {
+        let options = DiffOptions {
+            rename_similarity: Some(0.6),
+            ..Default::default()
+        };
+        let before = json!({"homeAddress": {"street": "Main", "city": "Rio", "zip": "1"}});
+        let after = json!({"address": {"street": "Main", "city": "Rio", "zip": "2"}});
+        let delta = diff_with(&before, &after, &options);
+
+        let mut expected = Delta::new();
+        expected.insert(
+            "address".to_string(),
+            Change::Rename {
+                from: "homeAddress".to_string(),
+                to: "address".to_string(),
+            },
+        );
+        expected.insert(
+            "address.zip".to_string(),
+            Change::Modify {
+                old: json!("1"),
+                new: json!("2"),
+            },
+        );
+        assert_eq!(delta, expected);
+        assert_eq!(apply(&before, &delta), after);
+        assert_eq!(revert(&after, &delta), before);
+        assert_eq!(delta.invert(&after).apply(&after), before);
+
+        // Keys added and removed inside the renamed value are undone under the old key.
+        let options = DiffOptions {
+            rename_similarity: Some(0.3),
+            ..Default::default()
+        };
+        let before = json!({"k": {"e": null, "gone": 1}});
+        let after = json!({"c": {"a": 2, "e": null}});
+        let delta = diff_with(&before, &after, &options);
+        assert!(matches!(delta["c"], Change::Rename { .. }));
+        assert_eq!(delta.invert(&after).apply(&after), before);
+        assert_eq!(revert(&after, &delta), before);
+    }
+
+    #[test]
+    fn dissimilar_values_stay_remove_and_add() {
+        let options = DiffOptions {
+            rename_similarity: Some(0.6),
+            ..Default::default()
+        };
+        let before = json!({"homeAddress": {"street": "Main", "city": "Rio", "zip": "1"}});
+        let after = json!({"address": {"street": "Elm", "city": "Lima", "zip": "1"}});
+        assert_eq!(diff_with(&before, &after, &options), diff(&before, &after));
+    }
+
+    #[test]
+    fn captured_floats_are_rounded_to_significant_digits() {
+        let options = DiffOptions {
+            significant_digits: Some(3),
+            ..Default::default()
+        };
+        let before = json!({"price": 2.500000000000001, "ratio": 0.123456, "n": 123456});
+        let after = json!({"price": 2.50001, "ratio": 98765.4, "n": 7, "added": [1.23456]});
+        let delta = diff_with(&before, &after, &options);
+
+        // Both prices round to 2.5, so there is nothing to record.
+        assert!(!delta.contains_key("price"));
+        assert_eq!(
+            delta["ratio"],
+            Change::Modify {
+                old: json!(0.123),
+                new: json!(98800.0),
+            }
+        );
+        assert_eq!(
+            delta["n"],
+            Change::Modify {
+                old: json!(123456),
+                new: json!(7),
+            }
+        );
+        assert_eq!(delta["added"], Change::Add(json!([1.23])));
+    }
+
+    #[test]
+    fn small_string_edits_are_suppressed_under_threshold() {
+        let options = DiffOptions {
+            string_edit_threshold: Some(1),
+            ..Default::default()
+        };
+        let before = json!({"title": "Recieve", "body": "hello world"});
+        let after = json!({"title": "Receive", "body": "goodbye world"});
+        let delta = diff_with(&before, &after, &options);
+        // "Recieve" -> "Receive" is two substitutions, above the threshold.
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["body", "title"]);
+
+        let typo = diff_with(&json!({"t": "colour"}), &json!({"t": "color"}), &options);
+        assert!(typo.is_empty());
+
+        // Any edit within the threshold is suppressed, even one to a short string...
+        let before = json!({"f": "Y", "n": "10", "t": "colour"});
+        let after = json!({"f": "N", "n": "11", "t": "color"});
+        assert!(diff_with(&before, &after, &options).is_empty());
+        let three = DiffOptions {
+            string_edit_threshold: Some(3),
+            ..Default::default()
+        };
+        assert!(diff_with(&json!("kitten"), &json!("sitting"), &three).is_empty());
+
+        // ...unless short strings are set to compare exactly.
+        let options = DiffOptions {
+            string_edit_min_len: 4,
+            ..options
+        };
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["f", "n"]);
+    }
 }
@@ -0,0 +1,168 @@
+//! Combining documents directly, without computing a delta.
+
+use serde_json::{Map, Value};
+
+/// Recursively merge `overlay` onto `base`, returning the merged document.
+///
+/// Where both sides hold an object, the result has the keys of both: keys present on one
+/// side only are kept as they are, and keys present on both are merged recursively. Any
+/// other pair of values is a conflict that `overlay` wins, so arrays are replaced as a
+/// whole rather than concatenated or merged by index, and a scalar replaces an object (or
+/// the other way round).
+///
+/// `null` in `overlay` is an ordinary value: it overwrites the key instead of deleting it
+/// as in JSON Merge Patch (RFC 7396), so `deep_merge(base, overlay)` never loses a key of
+/// either side.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::deep_merge;
+///
+/// let defaults = json!({"server": {"port": 80, "tls": false}, "tags": ["a"]});
+/// let config = json!({"server": {"tls": true}, "tags": ["b"]});
+/// assert_eq!(
+///     deep_merge(&defaults, &config),
+///     json!({"server": {"port": 80, "tls": true}, "tags": ["b"]})
+/// );
+/// ```
+pub fn deep_merge(base: &Value, overlay: &Value) -> Value {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            let mut merged = base.clone();
+            for (key, value) in overlay {
+                let value = match base.get(key) {
+                    Some(existing) => deep_merge(existing, value),
+                    None => value.clone(),
+                };
+                merged.insert(key.clone(), value);
+            }
+            Value::Object(merged)
+        }
+        (_, overlay) => overlay.clone(),
+    }
+}
+
+/// The part of `a` and `b` that is the same in both: every leaf equal on both sides, at
+/// its path, with the objects around it.
+///
+/// Objects are compared key by key, so the result is `a` without the keys `diff(a, b)`
+/// would report; keys on one side only are dropped, and so is a nested object that shares
+/// no leaf. Arrays are leaves like any other value: an array is kept only when it is equal
+/// as a whole, and dropped if any element differs. This makes `common` a usable base for
+/// a three-way merge when no explicit ancestor exists.
+///
+/// Two objects always yield an object (`{}` when nothing is shared); any other pair of
+/// unequal values yields `null`.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::common;
+///
+/// let ours = json!({"name": "svc", "port": 80, "tags": ["a"], "db": {"host": "x", "pool": 5}});
+/// let theirs = json!({"name": "svc", "port": 81, "tags": ["a"], "db": {"host": "y", "pool": 5}});
+/// assert_eq!(common(&ours, &theirs), json!({"name": "svc", "tags": ["a"], "db": {"pool": 5}}));
+/// ```
+pub fn common(a: &Value, b: &Value) -> Value {
+    match (a, b) {
+        (Value::Object(obj_a), Value::Object(obj_b)) => Value::Object(common_keys(obj_a, obj_b)),
+        _ if a == b => a.clone(),
+        _ => Value::Null,
+    }
+}
+
+fn common_keys(a: &Map<String, Value>, b: &Map<String, Value>) -> Map<String, Value> {
+    a.iter()
+        .filter_map(|(key, va)| {
+            let vb = b.get(key)?;
+            let shared = match (va, vb) {
+                _ if va == vb => va.clone(),
+                (Value::Object(obj_a), Value::Object(obj_b)) => {
+                    let shared = common_keys(obj_a, obj_b);
+                    if shared.is_empty() {
+                        return None;
+                    }
+                    Value::Object(shared)
+                }
+                _ => return None,
+            };
+            Some((key.clone(), shared))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn merges_nested_objects() {
+        let base = json!({"a": {"b": {"c": 1, "d": 2}, "e": 3}, "f": 4});
+        let overlay = json!({"a": {"b": {"d": 20, "x": 5}}, "g": 6});
+        assert_eq!(
+            deep_merge(&base, &overlay),
+            json!({"a": {"b": {"c": 1, "d": 20, "x": 5}, "e": 3}, "f": 4, "g": 6})
+        );
+    }
+
+    #[test]
+    fn overlay_wins_on_conflicts() {
+        let base = json!({"n": 1, "obj": {"k": true}, "s": "x", "keep": null});
+        let overlay = json!({"n": {"nested": 1}, "obj": 0, "s": null});
+        assert_eq!(
+            deep_merge(&base, &overlay),
+            json!({"n": {"nested": 1}, "obj": 0, "s": null, "keep": null})
+        );
+        assert_eq!(deep_merge(&json!({"a": 1}), &json!([1])), json!([1]));
+    }
+
+    #[test]
+    fn arrays_are_replaced() {
+        let base = json!({"list": [1, 2, 3], "objs": [{"a": 1}]});
+        let overlay = json!({"list": [4], "objs": [{"b": 2}]});
+        assert_eq!(
+            deep_merge(&base, &overlay),
+            json!({"list": [4], "objs": [{"b": 2}]})
+        );
+    }
+
+    #[test]
+    fn common_keeps_shared_leaves_only() {
+        let a = json!({
+            "same": {"x": 1, "empty": {}},
+            "mixed": {"keep": true, "drop": 1, "deep": {"k": "v", "n": 1}},
+            "gone": {"only": 1},
+            "list": [1, 2],
+            "other": [1, 2],
+            "type": {"a": 1},
+            "left": 1
+        });
+        let b = json!({
+            "same": {"x": 1, "empty": {}},
+            "mixed": {"keep": true, "drop": 2, "deep": {"k": "v", "n": 2}},
+            "gone": {"only": 2},
+            "list": [1, 2],
+            "other": [1, 3],
+            "type": [1],
+            "right": 1
+        });
+        let shared = common(&a, &b);
+        assert_eq!(
+            shared,
+            json!({
+                "same": {"x": 1, "empty": {}},
+                "mixed": {"keep": true, "deep": {"k": "v"}},
+                "list": [1, 2]
+            })
+        );
+        assert_eq!(common(&b, &a), shared);
+        assert_eq!(common(&a, &a), a);
+    }
+
+    #[test]
+    fn common_of_unequal_non_objects_is_null() {
+        assert_eq!(common(&json!([1]), &json!([2])), json!(null));
+        assert_eq!(common(&json!(3), &json!(3)), json!(3));
+        assert_eq!(common(&json!({"a": 1}), &json!({"a": 2})), json!({}));
+    }
+}
@@ -0,0 +1,71 @@
+//! Canonicalizing JSON values before diffing.
+
+use serde_json::{Map, Value};
+
+/// Return a canonical copy of `value` with every object's keys in sorted order.
+///
+/// `diff` already compares objects by key, so key order never produces a change. Duplicate
+/// keys cannot survive into a `Value` at all: `serde_json` keeps the *last* occurrence of a
+/// repeated key when parsing, and objects from other sources are deduplicated by their own
+/// parser before they reach this crate. Two documents that are equal after that step
+/// therefore always diff to empty, whichever parser produced them.
+///
+/// `normalize` makes the canonical form explicit, which is useful when values are also
+/// hashed, stored or printed (for example with serde_json's `preserve_order` feature, where
+/// insertion order would otherwise leak into the output). It is idempotent.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff, normalize};
+///
+/// let lenient: serde_json::Value = serde_json::from_str(r#"{"b": 1, "a": 0, "a": 2}"#).unwrap();
+/// assert_eq!(normalize(&lenient), json!({"a": 2, "b": 1}));
+/// assert!(diff(&normalize(&lenient), &json!({"a": 2, "b": 1})).is_empty());
+/// ```
+pub fn normalize(value: &Value) -> Value {
+    match value {
+        Value::Object(obj) => {
+            let mut entries: Vec<(&String, &Value)> = obj.iter().collect();
+            entries.sort_by_key(|(k, _)| *k);
+            let sorted: Map<String, Value> = entries
+                .into_iter()
+                .map(|(k, v)| (k.clone(), normalize(v)))
+                .collect();
+            Value::Object(sorted)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(normalize).collect()),
+        _ => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff;
+    use serde_json::json;
+
+    #[test]
+    fn duplicate_keys_keep_the_last_value() {
+        let parsed: Value =
+            serde_json::from_str(r#"{"user": {"id": 1, "id": 2}, "tags": [{"k": "x", "k": "y"}]}"#)
+                .unwrap();
+        let expected = json!({"user": {"id": 2}, "tags": [{"k": "y"}]});
+
+        assert!(diff(&parsed, &expected).is_empty());
+        assert!(diff(&normalize(&parsed), &expected).is_empty());
+    }
+
+    #[test]
+    fn normalized_and_raw_inputs_diff_alike() {
+        let raw: Value =
+            serde_json::from_str(r#"{"z": {"b": 2, "a": 1}, "a": [3, {"d": 0, "c": 0}]}"#).unwrap();
+        let reordered: Value =
+            serde_json::from_str(r#"{"a": [3, {"c": 0, "d": 0}], "z": {"a": 1, "b": 2}}"#).unwrap();
+
+        let normalized = normalize(&raw);
+        assert_eq!(normalize(&normalized), normalized);
+        assert!(diff(&raw, &reordered).is_empty());
+        assert!(diff(&normalized, &reordered).is_empty());
+        assert_eq!(normalized.to_string(), normalize(&reordered).to_string());
+    }
+}
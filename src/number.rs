@@ -0,0 +1,82 @@
+//! Arithmetic on JSON numbers for `Change::NumberDelta`.
+
+use serde_json::Number;
+
+/// `a + b`, exact when both are integers and the sum fits in an `i64` or `u64`, and in
+/// `f64` otherwise. `None` when the sum is not finite.
+pub(crate) fn add(a: &Number, b: &Number) -> Option<Number> {
+    if let (Some(x), Some(y)) = (a.as_i64(), b.as_i64())
+        && let Some(sum) = x.checked_add(y)
+    {
+        return Some(sum.into());
+    }
+    if let (Some(x), Some(y)) = (a.as_u64(), b.as_u64())
+        && let Some(sum) = x.checked_add(y)
+    {
+        return Some(sum.into());
+    }
+    Number::from_f64(a.as_f64()? + b.as_f64()?)
+}
+
+/// `a - b`, with the same rules as [`add`].
+pub(crate) fn sub(a: &Number, b: &Number) -> Option<Number> {
+    if let (Some(x), Some(y)) = (a.as_i64(), b.as_i64())
+        && let Some(difference) = x.checked_sub(y)
+    {
+        return Some(difference.into());
+    }
+    if let (Some(x), Some(y)) = (a.as_u64(), b.as_u64())
+        && let Some(difference) = x.checked_sub(y)
+    {
+        return Some(difference.into());
+    }
+    Number::from_f64(a.as_f64()? - b.as_f64()?)
+}
+
+/// `-n`. Integers stay integers where the negation fits in an `i64`.
+pub(crate) fn negate(n: &Number) -> Number {
+    match n.as_i64().and_then(i64::checked_neg) {
+        Some(negated) => negated.into(),
+        None => n
+            .as_f64()
+            .and_then(|f| Number::from_f64(-f))
+            .unwrap_or_else(|| n.clone()),
+    }
+}
+
+/// Whether `n` is zero, so that adding it changes nothing.
+pub(crate) fn is_zero(n: &Number) -> bool {
+    n.as_f64() == Some(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    fn num(value: Value) -> Number {
+        match value {
+            Value::Number(n) => n,
+            other => panic!("not a number: {other}"),
+        }
+    }
+
+    #[test]
+    fn integer_arithmetic_is_exact() {
+        assert_eq!(add(&num(json!(2)), &num(json!(-5))), Some(num(json!(-3))));
+        assert_eq!(
+            sub(&num(json!(u64::MAX)), &num(json!(1))),
+            Some(num(json!(u64::MAX - 1)))
+        );
+        assert_eq!(negate(&num(json!(7))), num(json!(-7)));
+        assert_eq!(
+            add(&num(json!(i64::MAX)), &num(json!(-1))),
+            Some(num(json!(i64::MAX - 1)))
+        );
+        // Overflow falls back to floats.
+        assert!(add(&num(json!(u64::MAX)), &num(json!(1))).unwrap().is_f64());
+        assert_eq!(add(&num(json!(0.5)), &num(json!(1))), Some(num(json!(1.5))));
+        // Only a float sum past the range of `f64` is refused.
+        assert_eq!(add(&num(json!(f64::MAX)), &num(json!(f64::MAX))), None);
+    }
+}
@@ -0,0 +1,100 @@
+//! Deltas as ordered lists of operations.
+
+use crate::apply::application_order;
+use crate::{Change, Delta, DotCodec};
+use serde::{Deserialize, Serialize};
+
+/// One change of a delta together with its path, as listed by [`to_operations`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Operation {
+    pub path: String,
+    pub change: Change,
+}
+
+/// List the changes of `delta` in an order that is safe to apply one after another.
+///
+/// This is the order `apply` itself uses: changes in place first, then removals of object
+/// keys (deepest first), then removals of array elements from the highest index down, then
+/// insertions from the lowest position up, so every index still refers to the element it
+/// was recorded against when its turn comes. With no document to look at, a numeric last
+/// segment is taken to be an array index. Applying the list with
+/// [`apply_ordered`](crate::apply_ordered) gives the same result as applying `delta`.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff_with, to_operations, DiffOptions};
+///
+/// let options = DiffOptions { set_arrays: vec!["tags".to_string()], ..Default::default() };
+/// let delta = diff_with(&json!({"tags": ["a", "b", "c"]}), &json!({"tags": ["c"]}), &options);
+/// let paths: Vec<String> = to_operations(&delta).into_iter().map(|op| op.path).collect();
+/// assert_eq!(paths, ["tags.1", "tags.0"]);
+/// ```
+pub fn to_operations(delta: &Delta) -> Vec<Operation> {
+    application_order(delta, &DotCodec, |_| false)
+        .into_iter()
+        .map(|(path, change)| Operation {
+            path: path.to_string(),
+            change: change.clone(),
+        })
+        .collect()
+}
+
+/// Collect `ops` back into a delta, the inverse of [`to_operations`]. When several
+/// operations share a path, the last one wins.
+pub fn from_operations(ops: impl IntoIterator<Item = Operation>) -> Delta {
+    ops.into_iter().map(|op| (op.path, op.change)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DiffOptions, apply, apply_ordered, diff, diff_with};
+    use serde_json::json;
+
+    #[test]
+    fn operations_round_trip_in_application_order() {
+        let options = DiffOptions {
+            set_arrays: vec!["tags".to_string()],
+            ..Default::default()
+        };
+        let before = json!({
+            "tags": ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k"],
+            "old": {"deep": {"x": 1}},
+            "n": 1
+        });
+        let after = json!({"tags": ["x", "a", "c", "y"], "n": 2, "new": true});
+        let delta = diff_with(&before, &after, &options);
+        let ops = to_operations(&delta);
+
+        let paths: Vec<&str> = ops.iter().map(|op| op.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "n", "new", "old", "tags.10", "tags.9", "tags.8", "tags.7", "tags.6", "tags.5",
+                "tags.4", "tags.3", "tags.1", "tags.+0", "tags.+3"
+            ]
+        );
+        let ordered: Vec<(String, Change)> = ops
+            .iter()
+            .map(|op| (op.path.clone(), op.change.clone()))
+            .collect();
+        assert_eq!(apply_ordered(&before, &ordered), apply(&before, &delta));
+        assert_eq!(from_operations(ops), delta);
+    }
+
+    #[test]
+    fn later_operations_win() {
+        let ops = vec![
+            Operation {
+                path: "a".to_string(),
+                change: Change::Add(json!(1)),
+            },
+            Operation {
+                path: "a".to_string(),
+                change: Change::Add(json!(2)),
+            },
+        ];
+        assert_eq!(from_operations(ops), diff(&json!({}), &json!({"a": 2})));
+        assert!(to_operations(&Delta::new()).is_empty());
+    }
+}
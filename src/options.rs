@@ -0,0 +1,634 @@
+//! Options controlling how [`diff_with`](crate::diff_with) compares two values.
+
+use crate::number;
+use crate::similarity::levenshtein;
+use crate::stats::value_size;
+use crate::{Change, PathCodec};
+use serde_json::{Number, Value};
+use std::fmt;
+use std::sync::Arc;
+
+/// Configuration for [`diff_with`](crate::diff_with).
+///
+/// The default options reproduce [`diff`](crate::diff) exactly.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff_with, DiffOptions};
+///
+/// let options = DiffOptions {
+///     set_arrays: vec!["tags".to_string()],
+///     ..Default::default()
+/// };
+/// let delta = diff_with(&json!({"tags": ["a", "b"]}), &json!({"tags": ["b", "a"]}), &options);
+/// assert!(delta.is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    /// Paths of arrays that hold logically unordered sets (tags, roles, ...).
+    ///
+    /// Such arrays are compared by membership instead of position: reordering is not a
+    /// change, and each element present on only one side becomes an element-level
+    /// `Remove` (keyed by its index in the old array) or `Add` (keyed by its index in the
+    /// new array). Membership uses structural equality, so objects and nested arrays work
+    /// as elements too.
+    pub set_arrays: Vec<String>,
+    /// String values treated as equal to one another at leaf comparisons.
+    ///
+    /// Some producers encode non-finite floats as strings such as `"NaN"` or `"Infinity"`.
+    /// When both sides of a comparison are strings from this list, no change is reported.
+    pub equal_sentinels: Vec<String>,
+    /// Value-driven filter evaluated before descending into, or reporting a change for,
+    /// any subtree. When it returns `true` for the value on either side, that path is
+    /// skipped entirely, wherever it occurs in the document.
+    pub skip: Option<ValuePredicate>,
+    /// Paths left out of the comparison, together with everything beneath them. Each
+    /// entry is a dot-path pattern where `*` matches any single segment, e.g.
+    /// `items.*.updated_at`. Changes at or below a matching path are never reported.
+    pub ignore_paths: Vec<String>,
+    /// Paths to compare, leaving out everything else: the opposite of `ignore_paths`,
+    /// with the same patterns (default empty, comparing the whole document).
+    ///
+    /// Changes are reported only at or below a matching path. Subtrees on the way to
+    /// one are still descended into, but a change at such an enclosing path is not
+    /// reported (with `user.age` listed, adding or replacing the whole `user` object is
+    /// skipped), and subtrees off every listed path are pruned without being compared.
+    pub only_paths: Vec<String>,
+    /// Whether changed values keep their old value (default `true`).
+    ///
+    /// When `false`, changed leaves are reported as `Change::Set` carrying only the new
+    /// value, roughly halving the size of write-only change logs. Such deltas apply
+    /// forwards as usual but cannot be reverted: `try_revert` returns
+    /// `JsonDiffError::Irreversible` and `revert` leaves those paths untouched.
+    pub capture_old: bool,
+    /// Treat strings that differ only in whitespace as equal (default `false`).
+    ///
+    /// Strings are compared after trimming both ends and collapsing each run of
+    /// whitespace to a single space, so reflowed or re-indented text is not reported.
+    /// Off by default because whitespace can be significant.
+    pub ignore_whitespace: bool,
+    /// Treat strings that differ only in ASCII letter case as equal (default `false`), so
+    /// enum-like values such as `"Active"` and `"active"` are not reported. Combines with
+    /// `ignore_whitespace`.
+    ///
+    /// Case differences are normalized away rather than recorded: applying the delta to
+    /// `before` keeps `before`'s spelling, so the result can differ from `after` in case.
+    pub case_insensitive_values: bool,
+    /// Treat two strings as equal when their Levenshtein edit distance (in characters) is
+    /// at most this many edits, suppressing typo fixes and other trivial text changes.
+    /// Off (`None`) by default.
+    ///
+    /// Every change within the threshold is suppressed, whatever the length of the
+    /// strings: at a threshold of 1, `"Y"` → `"N"` goes unreported just like `"colour"` →
+    /// `"color"`. Set `string_edit_min_len` to keep short values exact.
+    pub string_edit_threshold: Option<usize>,
+    /// Compare strings shorter than this many characters exactly, even under
+    /// `string_edit_threshold` (default `0`, so the threshold applies to all strings).
+    /// Flags and codes such as `"Y"` or `"10"` are typically short enough that a single
+    /// edit changes their meaning.
+    pub string_edit_min_len: usize,
+    /// Expected types for leaves at the given paths, applied before comparison.
+    ///
+    /// Each entry pairs a dot-path pattern, where `*` matches any single segment, with
+    /// the type both sides should be read as. If both leaves coerce (e.g. `"42"` and
+    /// `42` as [`JsonType::Number`]) and the results are equal, no change is reported.
+    /// If either side fails to coerce, the values are compared as usual. The first
+    /// matching pattern wins.
+    pub coerce: Vec<(String, JsonType)>,
+    /// Paths that always appear in the delta, giving audit consumers a complete record of
+    /// the fields they care about. A watched path that exists on both sides and did not
+    /// change is recorded as `Change::Unchanged`. One that exists on only one side and is
+    /// not already covered by a change at or above it (as when `only_template_keys` leaves
+    /// an added key out) is recorded as an `Add` or `Remove`, or as `Unchanged` if its
+    /// value counts as absent (`empty_object_equals_absent` and the like). Paths missing
+    /// on both sides, or left out by `ignore_paths`, `skip` or `only_paths`, do not
+    /// appear.
+    pub watched: Vec<String>,
+    /// Report a key removed and a key added in the same object with equal values as a
+    /// single `Change::Rename` instead of a `Remove` plus an `Add` (default `false`).
+    pub detect_renames: bool,
+    /// Also pair a removed and an added key of the same object whose values are merely
+    /// similar: when [`similarity`](crate::similarity) reaches this threshold (0.0–1.0),
+    /// a `Change::Rename` is emitted followed by the changes between the two values under
+    /// the new key. Off (`None`) by default, since loose thresholds can pair unrelated keys.
+    pub rename_similarity: Option<f64>,
+    /// Arrays of objects whose elements are matched by a subset of their fields.
+    ///
+    /// Each entry pairs an array path pattern (`*` matches any single segment) with the
+    /// fields that identify an element. Elements of the old and new array whose listed
+    /// fields are equal are paired (by a content hash of those fields, in order of
+    /// appearance) and diffed against each other, so editing another field yields a nested
+    /// change like `items.2.qty` rather than a whole-element replacement. Unpaired elements
+    /// become indexed `Remove`s and `Add`s, as with `set_arrays`; changing an identifying
+    /// field therefore removes the old element and adds the new one. Non-object elements
+    /// are identified by their whole value. Like `set_arrays`, these arrays are treated as
+    /// unordered: applying such a delta does not restore the order of paired elements.
+    /// The first matching pattern wins, and `set_arrays` takes precedence.
+    pub match_fields: Vec<(String, Vec<String>)>,
+    /// How arrays not listed in `set_arrays` are compared (default
+    /// [`ArrayStrategy::Whole`]).
+    pub array_strategy: ArrayStrategy,
+    /// Round floating-point numbers to this many significant digits when they are captured
+    /// into a `Change` (see [`round_significant`]), so persisted deltas do not churn on
+    /// values like `2.500000000000001`. This is lossy: applying such a delta writes the
+    /// rounded values. Values that differ only beyond the kept digits round to the same
+    /// number and produce no change. Integers are never rounded.
+    pub significant_digits: Option<u32>,
+    /// Compare numbers by numeric value rather than representation, so `1` and `1.0` are
+    /// equal (default `false`). An integer only equals a float when its magnitude is at
+    /// most 2^53 (`9007199254740992`), the range in which `f64` represents every integer
+    /// exactly; beyond it, integers and floats are always reported as different, since
+    /// the float may be a lossy copy of a different integer.
+    pub numbers_value_equal: bool,
+    /// Report a changed number as a `Change::NumberDelta` holding `new - old` instead of a
+    /// `Modify` (default `false`), so that increments recorded by separate deltas can be
+    /// merged by adding them up, in any order.
+    ///
+    /// Integer differences are exact. Float differences are rounded: a `NumberDelta` is
+    /// only emitted when adding it to `old` gives exactly `new` and subtracting it from
+    /// `new` gives exactly `old`, representation included (otherwise the change stays a
+    /// `Modify`), but adding it to any other value, as when concurrent increments are
+    /// composed, can be off in the last digits.
+    pub number_deltas: bool,
+    /// Treat a key holding an empty object (`{}`) as equal to the key being absent
+    /// (default `false`), for producers that emit `{}` where others omit the field.
+    ///
+    /// No change is reported for such a key, so the delta no longer round-trips exactly:
+    /// applying it to `before` keeps or lacks the empty object as `before` did, whatever
+    /// `after` has. Normalize both sides the same way when the exact shape matters.
+    pub empty_object_equals_absent: bool,
+    /// Like `empty_object_equals_absent`, for a key holding an empty array (`[]`).
+    pub empty_array_equals_absent: bool,
+    /// Record a changed value whose JSON encoding would exceed this many bytes (estimated
+    /// as [`estimated_size`](crate::estimated_size) does) as a `Change::Opaque`
+    /// holding digests of the old and new values, instead of a `Modify` holding copies of
+    /// both (default `None`: no limit).
+    ///
+    /// This bounds the memory a diff of documents with large embedded blobs takes, at the
+    /// cost of reversibility: an `Opaque` change is skipped by `apply` and `revert`, and
+    /// the strict functions reject it. Only values replaced in place are affected; keys
+    /// and elements that are added or removed are still captured in full.
+    pub max_value_size: Option<usize>,
+    /// Only report object keys present in `before`, treating it as a template (default
+    /// `false`): keys found only in `after` are not reported as `Add`s, while template keys
+    /// that changed or are missing from `after` still produce `Modify`s and `Remove`s.
+    /// Useful for "how does this config deviate from the template?" reports. Applying such
+    /// a delta to the template does not reproduce the extra keys.
+    pub only_template_keys: bool,
+    /// Report objects that hold the same keys as before in a different order as a
+    /// `Change::Reorder` at the object's path (default `false`). Objects compare equal
+    /// whatever their key order, so this is the only way a delta records it; changes to
+    /// the values are still reported under their own paths. Objects whose key sets differ
+    /// are not reordered.
+    #[cfg(feature = "preserve_order")]
+    pub detect_key_reorder: bool,
+    /// Compare values nested more than this many segments deep as a whole: objects and
+    /// arrays found at this depth are not descended into, and a difference anywhere inside
+    /// them is reported as one change of the whole value (default `None`: no limit).
+    /// Values holding paths left out by `ignore_paths`, `skip` or `only_paths` are still
+    /// descended into, down to those paths, so the filters hold.
+    /// [`try_diff`](crate::try_diff) rejects such inputs instead.
+    pub max_depth: Option<usize>,
+    /// Replace a delta of more than this many changes with a single change of the whole
+    /// document at the root path (default `None`: no limit). The delta is kept as it is
+    /// when `ignore_paths`, `skip` or `only_paths` leave out part of the document, which a
+    /// change of the whole would include.
+    /// [`try_diff`](crate::try_diff) fails with `JsonDiffError::TooManyChanges` instead.
+    pub max_changes: Option<usize>,
+    /// Pick, for each changed object or array, whichever is smaller when serialized (as
+    /// estimated by [`estimated_size`](crate::estimated_size)): the changes within it, or
+    /// a single change replacing it whole (default `false`).
+    ///
+    /// Subtrees are decided bottom-up, so the delta as a whole is as small as these two
+    /// choices allow; many small edits to a small object become one `Modify`, while a
+    /// small edit to a large object stays a leaf change. Ties keep the finer changes.
+    /// Subtrees holding paths left out by `ignore_paths`, `skip` or `only_paths` always
+    /// keep the finer changes, since a whole replacement would include those paths.
+    pub minimize_cost: bool,
+}
+
+/// Where a path lies relative to [`DiffOptions::only_paths`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Scope {
+    /// At or below a listed path (or nothing is listed): changes are reported.
+    Inside,
+    /// On the way to a listed path: descended into, but not reported.
+    Above,
+    /// Off every listed path: not compared.
+    Outside,
+}
+
+/// How [`diff_with`](crate::diff_with) compares ordered arrays.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArrayStrategy {
+    /// Any difference replaces the whole array with a single `Modify`.
+    #[default]
+    Whole,
+    /// When both arrays have the same length and contain only objects, diff the objects
+    /// pairwise by position (`rows.2.status`). Otherwise fall back to `Whole`.
+    SameLengthObjects,
+    /// Compare arrays as multisets (bags): order is ignored but duplicates count, so
+    /// `["a", "a", "b"]` and `["b", "a", "a"]` are equal while `["a", "a"]` to `["a"]`
+    /// removes one `"a"`. Elements are matched by structural equality (honouring the leaf
+    /// rules), and unmatched ones become indexed `Remove`s and `Add`s as with `set_arrays`.
+    /// Applying such a delta restores the elements but not necessarily their order.
+    Multiset,
+    /// Record the edits to an array as one `Change::ArrayOps` script under the array's
+    /// path: elements of a longest common subsequence stay in place, and the rest are
+    /// inserted, deleted or updated in order, each step's index referring to the array as
+    /// the previous steps left it. Unlike index-keyed paths, the script replays exactly,
+    /// in both directions. Takes time and memory proportional to the product of the two
+    /// array lengths.
+    Ops,
+}
+
+/// Extra checks for [`try_apply_with`](crate::try_apply_with) and
+/// [`apply_with`](crate::apply_with).
+///
+/// The default adds no checks beyond those of `try_apply` (or `apply`).
+#[derive(Debug, Clone, Default)]
+pub struct ApplyOptions {
+    /// Make `Change::Add` fail with `JsonDiffError::PathExists` when its target object
+    /// key is already present, instead of replacing the value (default `false`). Catches
+    /// two sources adding the same field. Inserting into an array is never affected.
+    pub add_must_not_exist: bool,
+    /// Reject changes whose target path has more than this many segments with
+    /// `JsonDiffError::PathTooDeep` (default `None`, unbounded, as before).
+    ///
+    /// Lenient application with [`apply_with`](crate::apply_with) creates missing
+    /// intermediate objects, so a single path string of `a.a.a...` builds one level of
+    /// nesting per segment; for deltas from untrusted sources this caps the structure a
+    /// change can create (and the recursion depth of anything that later walks the
+    /// result). Strict application never creates intermediates, so there it only bounds
+    /// how deep a path may reach into the existing document.
+    pub max_apply_depth: Option<usize>,
+}
+
+/// Target type for [`DiffOptions::coerce`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonType {
+    /// Numbers, or strings that parse as finite numbers (`"NaN"` and `"inf"` do not).
+    /// Compared by numeric value: integers exactly, and an integer equal to a float only
+    /// within 2^53, as with [`DiffOptions::numbers_value_equal`].
+    Number,
+    /// Strings, or numbers and booleans rendered as their JSON text.
+    String,
+    /// Booleans, or the strings `"true"` and `"false"`.
+    Bool,
+}
+
+impl JsonType {
+    fn coerce(self, value: &Value) -> Option<Value> {
+        match (self, value) {
+            (JsonType::Number, Value::Number(_)) => Some(value.clone()),
+            (JsonType::Number, Value::String(s)) => {
+                let s = s.trim();
+                let number = match (s.parse::<u64>(), s.parse::<i64>()) {
+                    (Ok(u), _) => Number::from(u),
+                    (_, Ok(i)) => Number::from(i),
+                    _ => Number::from_f64(s.parse().ok()?)?,
+                };
+                Some(Value::Number(number))
+            }
+            (JsonType::String, Value::String(_)) => Some(value.clone()),
+            (JsonType::String, Value::Number(_) | Value::Bool(_)) => {
+                Some(Value::String(value.to_string()))
+            }
+            (JsonType::Bool, Value::Bool(_)) => Some(value.clone()),
+            (JsonType::Bool, Value::String(s)) => s.parse::<bool>().ok().map(Value::Bool),
+            _ => None,
+        }
+    }
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            set_arrays: Vec::new(),
+            equal_sentinels: Vec::new(),
+            skip: None,
+            ignore_paths: Vec::new(),
+            only_paths: Vec::new(),
+            capture_old: true,
+            ignore_whitespace: false,
+            case_insensitive_values: false,
+            string_edit_threshold: None,
+            string_edit_min_len: 0,
+            coerce: Vec::new(),
+            watched: Vec::new(),
+            detect_renames: false,
+            rename_similarity: None,
+            match_fields: Vec::new(),
+            array_strategy: ArrayStrategy::Whole,
+            significant_digits: None,
+            numbers_value_equal: false,
+            number_deltas: false,
+            empty_object_equals_absent: false,
+            empty_array_equals_absent: false,
+            max_value_size: None,
+            only_template_keys: false,
+            #[cfg(feature = "preserve_order")]
+            detect_key_reorder: false,
+            max_depth: None,
+            max_changes: None,
+            minimize_cost: false,
+        }
+    }
+}
+
+/// A shareable `Fn(path, value) -> bool` used by options such as [`DiffOptions::skip`].
+#[derive(Clone)]
+pub struct ValuePredicate(Arc<PredicateFn>);
+
+type PredicateFn = dyn Fn(&str, &Value) -> bool + Send + Sync;
+
+impl ValuePredicate {
+    /// Wrap a closure receiving the dot-path and the value found there.
+    pub fn new(f: impl Fn(&str, &Value) -> bool + Send + Sync + 'static) -> Self {
+        ValuePredicate(Arc::new(f))
+    }
+
+    /// Evaluate the predicate.
+    pub fn test(&self, path: &str, value: &Value) -> bool {
+        (self.0)(path, value)
+    }
+}
+
+impl fmt::Debug for ValuePredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ValuePredicate(..)")
+    }
+}
+
+impl DiffOptions {
+    pub(crate) fn is_set_array(&self, path: &str) -> bool {
+        self.set_arrays.iter().any(|p| p == path)
+    }
+
+    /// The identifying fields configured for the array at `path`, if any.
+    pub(crate) fn match_fields_at(&self, path: &str, codec: &dyn PathCodec) -> Option<&[String]> {
+        self.match_fields
+            .iter()
+            .find(|(pattern, _)| codec.matches(pattern, path))
+            .map(|(_, fields)| fields.as_slice())
+    }
+
+    /// The amount to record as a `NumberDelta` for a change from `a` to `b`, if
+    /// `number_deltas` is on and the amount reproduces `b` from `a`, and `a` from `b`,
+    /// exactly.
+    pub(crate) fn number_delta(&self, a: &Value, b: &Value) -> Option<Number> {
+        let (Value::Number(old), Value::Number(new)) = (a, b) else {
+            return None;
+        };
+        if !self.number_deltas {
+            return None;
+        }
+        let by = number::sub(new, old)?;
+        let exact = number::add(old, &by).as_ref() == Some(new)
+            && number::add(new, &number::negate(&by)).as_ref() == Some(old);
+        exact.then_some(by)
+    }
+
+    /// The copy of `value` to store in a `Change`. Every value captured by `compare` goes
+    /// through here, so capture-time transformations apply uniformly.
+    pub(crate) fn capture(&self, value: &Value) -> Value {
+        match self.significant_digits {
+            Some(digits) => round_significant(value, digits),
+            None => value.clone(),
+        }
+    }
+
+    /// The `Modify` (or, without `capture_old`, `Set`) from `a` to `b`, holding captured
+    /// copies, or `None` if capturing makes them equal (as rounding can).
+    pub(crate) fn capture_change(&self, a: &Value, b: &Value) -> Option<Change> {
+        let new = self.capture(b);
+        if self.capture_old {
+            let old = self.capture(a);
+            (old != new).then_some(Change::Modify { old, new })
+        } else {
+            let rounded_away = self.significant_digits.is_some() && self.capture(a) == new;
+            (!rounded_away).then_some(Change::Set(new))
+        }
+    }
+
+    /// Whether arrays `a` and `b` should be diffed element by element, by position.
+    pub(crate) fn pairs_elements(&self, a: &[Value], b: &[Value]) -> bool {
+        match self.array_strategy {
+            ArrayStrategy::Whole | ArrayStrategy::Multiset | ArrayStrategy::Ops => false,
+            ArrayStrategy::SameLengthObjects => {
+                a.len() == b.len() && a.iter().chain(b).all(Value::is_object)
+            }
+        }
+    }
+
+    pub(crate) fn should_skip(&self, path: &str, value: &Value, codec: &dyn PathCodec) -> bool {
+        self.ignore_paths
+            .iter()
+            .any(|pattern| codec.matches(pattern, path))
+            || self
+                .skip
+                .as_ref()
+                .is_some_and(|skip| skip.test(path, value))
+    }
+
+    /// Where `path` lies relative to `only_paths`.
+    pub(crate) fn scope(&self, path: &str, codec: &dyn PathCodec) -> Scope {
+        if self.only_paths.is_empty() {
+            return Scope::Inside;
+        }
+        let segments = codec.decode(path);
+        let mut scope = Scope::Outside;
+        for pattern in &self.only_paths {
+            let pattern = codec.decode(pattern);
+            let agree = pattern
+                .iter()
+                .zip(&segments)
+                .all(|(p, s)| p == "*" || p == s);
+            if !agree {
+                continue;
+            }
+            if pattern.len() <= segments.len() {
+                return Scope::Inside;
+            }
+            scope = Scope::Above;
+        }
+        scope
+    }
+
+    /// Whether `value` is too large to be captured in a change (see `max_value_size`).
+    pub(crate) fn exceeds_max_size(&self, value: &Value) -> bool {
+        self.max_value_size
+            .is_some_and(|max| value_size(value) > max)
+    }
+
+    /// Whether an object key holding `value` counts as absent, so that adding or removing
+    /// it is not a change.
+    pub(crate) fn equals_absent(&self, value: &Value) -> bool {
+        match value {
+            Value::Object(obj) => self.empty_object_equals_absent && obj.is_empty(),
+            Value::Array(items) => self.empty_array_equals_absent && items.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Like [`equivalent`](Self::equivalent), but also applies path-dependent rules
+    /// such as type coercion to the values found at `path`.
+    pub(crate) fn equivalent_at(
+        &self,
+        path: &str,
+        a: &Value,
+        b: &Value,
+        codec: &dyn PathCodec,
+    ) -> bool {
+        self.equivalent(a, b) || self.coerced_equal(path, a, b, codec)
+    }
+
+    fn coerced_equal(&self, path: &str, a: &Value, b: &Value, codec: &dyn PathCodec) -> bool {
+        let Some((_, ty)) = self
+            .coerce
+            .iter()
+            .find(|(pattern, _)| codec.matches(pattern, path))
+        else {
+            return false;
+        };
+        match (ty.coerce(a), ty.coerce(b)) {
+            (Some(Value::Number(x)), Some(Value::Number(y))) => x == y || numbers_equal(&x, &y),
+            (Some(x), Some(y)) => x == y,
+            _ => false,
+        }
+    }
+
+    /// Whether two values are equal under these options. Containers are compared
+    /// structurally so leaf rules also apply inside arrays that are diffed as a whole.
+    pub(crate) fn equivalent(&self, a: &Value, b: &Value) -> bool {
+        if a == b {
+            return true;
+        }
+        if !self.has_leaf_rules() {
+            return false;
+        }
+        match (a, b) {
+            (Value::Object(obj_a), Value::Object(obj_b)) => {
+                obj_a.len() == obj_b.len()
+                    && obj_a
+                        .iter()
+                        .all(|(k, va)| obj_b.get(k).is_some_and(|vb| self.equivalent(va, vb)))
+            }
+            (Value::Array(arr_a), Value::Array(arr_b)) => {
+                arr_a.len() == arr_b.len()
+                    && arr_a
+                        .iter()
+                        .zip(arr_b)
+                        .all(|(va, vb)| self.equivalent(va, vb))
+            }
+            _ => self.leaf_equal(a, b),
+        }
+    }
+
+    fn has_leaf_rules(&self) -> bool {
+        !self.equal_sentinels.is_empty()
+            || self.ignore_whitespace
+            || self.case_insensitive_values
+            || self.string_edit_threshold.is_some()
+            || self.numbers_value_equal
+    }
+
+    fn leaf_equal(&self, a: &Value, b: &Value) -> bool {
+        match (a, b) {
+            (Value::String(x), Value::String(y)) => {
+                (self.is_sentinel(x) && self.is_sentinel(y))
+                    || (self.case_insensitive_values && x.eq_ignore_ascii_case(y))
+                    || (self.ignore_whitespace
+                        && whitespace_equal(x, y, self.case_insensitive_values))
+                    || self
+                        .string_edit_threshold
+                        .is_some_and(|max| self.minor_edit(x, y, max))
+            }
+            (Value::Number(x), Value::Number(y)) => self.numbers_value_equal && numbers_equal(x, y),
+            _ => false,
+        }
+    }
+
+    fn is_sentinel(&self, s: &str) -> bool {
+        self.equal_sentinels.iter().any(|sentinel| sentinel == s)
+    }
+
+    /// Whether `a` and `b` are at most `max` edits apart and both long enough for
+    /// `string_edit_min_len`.
+    fn minor_edit(&self, a: &str, b: &str, max: usize) -> bool {
+        let long_enough = |s: &str| s.chars().count() >= self.string_edit_min_len;
+        long_enough(a) && long_enough(b) && levenshtein(a, b) <= max
+    }
+}
+
+/// Whether two strings are equal once trimmed and with whitespace runs collapsed, and
+/// with ASCII case ignored when `ignore_case` is set.
+fn whitespace_equal(a: &str, b: &str, ignore_case: bool) -> bool {
+    let (mut a, mut b) = (a.split_whitespace(), b.split_whitespace());
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return true,
+            (Some(x), Some(y)) if x == y || (ignore_case && x.eq_ignore_ascii_case(y)) => {}
+            _ => return false,
+        }
+    }
+}
+
+/// Largest integer magnitude below which every integer is exactly representable as `f64`.
+const MAX_SAFE_INTEGER: u64 = 1 << 53;
+
+/// Whether two numbers have the same numeric value, comparing integers with floats only
+/// within `MAX_SAFE_INTEGER`.
+fn numbers_equal(a: &serde_json::Number, b: &serde_json::Number) -> bool {
+    let safe = |n: &serde_json::Number| {
+        n.as_u64()
+            .map(|u| u <= MAX_SAFE_INTEGER)
+            .or_else(|| n.as_i64().map(|i| i.unsigned_abs() <= MAX_SAFE_INTEGER))
+            .unwrap_or(true)
+    };
+    if a.is_f64() == b.is_f64() {
+        // Same representation: `==` on `Value` already decided.
+        return false;
+    }
+    safe(a) && safe(b) && a.as_f64() == b.as_f64()
+}
+
+/// Round every floating-point number in `value` to `digits` significant digits.
+///
+/// Integers, non-numbers and a `digits` of `0` are left unchanged. The result is the
+/// `f64` closest to the rounded decimal, so it serializes in its short form
+/// (`2.500000000000001` with three digits becomes `2.5`).
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::round_significant;
+///
+/// assert_eq!(round_significant(&json!({"x": 2.71828, "n": 12345}), 2), json!({"x": 2.7, "n": 12345}));
+/// ```
+pub fn round_significant(value: &Value, digits: u32) -> Value {
+    match value {
+        Value::Number(n) if digits > 0 && n.is_f64() => {
+            let Some(x) = n.as_f64() else {
+                return value.clone();
+            };
+            let precision = (digits - 1) as usize;
+            format!("{x:.precision$e}")
+                .parse::<f64>()
+                .ok()
+                .and_then(serde_json::Number::from_f64)
+                .map_or_else(|| value.clone(), Value::Number)
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| round_significant(item, digits))
+                .collect(),
+        ),
+        Value::Object(obj) => Value::Object(
+            obj.iter()
+                .map(|(k, v)| (k.clone(), round_significant(v, digits)))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
@@ -0,0 +1,109 @@
+//! Listing the changes between two documents in document order.
+
+use crate::{Change, DotCodec, PathCodec, diff};
+use serde_json::Value;
+
+/// Compute the changes between `before` and `after` like [`diff`], listed in the order
+/// their paths appear in the documents rather than sorted as strings.
+///
+/// Within each object, keys come in the order of `after`, followed by keys found only in
+/// `before` (removed ones) in the order of `before`. Object keys are in document order
+/// only with the `preserve_order` feature; without it serde_json keeps them sorted, so
+/// this only moves removed keys after their kept and added siblings.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::diff_ordered;
+///
+/// let changes = diff_ordered(&json!({"b": 1, "a": 1}), &json!({"b": 2, "a": 2, "c": 3}));
+/// let paths: Vec<&str> = changes.iter().map(|(path, _)| path.as_str()).collect();
+/// // With `preserve_order`; without it, `["a", "b", "c"]`.
+/// # #[cfg(feature = "preserve_order")]
+/// assert_eq!(paths, ["b", "a", "c"]);
+/// ```
+pub fn diff_ordered(before: &Value, after: &Value) -> Vec<(String, Change)> {
+    let mut changes: Vec<(Vec<usize>, String, Change)> = diff(before, after)
+        .into_inner()
+        .into_iter()
+        .map(|(path, change)| (position(before, after, &path), path, change))
+        .collect();
+    changes.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+    changes
+        .into_iter()
+        .map(|(_, path, change)| (path, change))
+        .collect()
+}
+
+/// The position of `path` in the documents: for each segment, its rank among the keys of
+/// its parent object, counting the keys of `after` first.
+fn position(before: &Value, after: &Value, path: &str) -> Vec<usize> {
+    let (mut old, mut new) = (Some(before), Some(after));
+    DotCodec
+        .decode(path)
+        .iter()
+        .map(|segment| {
+            let rank_in = |value: Option<&Value>| match value {
+                Some(Value::Object(obj)) => (obj.keys().position(|key| key == segment), obj.len()),
+                _ => (None, 0),
+            };
+            let ((new_rank, new_len), (old_rank, _)) = (rank_in(new), rank_in(old));
+            old = old.and_then(|value| value.get(segment));
+            new = new.and_then(|value| value.get(segment));
+            new_rank
+                .or_else(|| old_rank.map(|i| new_len + i))
+                .unwrap_or(usize::MAX)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn paths(changes: &[(String, Change)]) -> Vec<&str> {
+        changes.iter().map(|(path, _)| path.as_str()).collect()
+    }
+
+    #[test]
+    fn removed_keys_come_after_the_others() {
+        let before = json!({"a": 1, "b": {"x": 1, "y": 1}});
+        let after = json!({"b": {"y": 2, "z": 2}, "c": 3});
+        let changes = diff_ordered(&before, &after);
+        assert_eq!(paths(&changes), ["b.y", "b.z", "b.x", "c", "a"]);
+        assert_eq!(
+            changes.into_iter().collect::<crate::Delta>(),
+            diff(&before, &after)
+        );
+    }
+
+    #[cfg(feature = "preserve_order")]
+    #[test]
+    fn changes_follow_document_key_order() {
+        let before = json!({
+            "zeta": 1,
+            "alpha": {"second": 1, "first": 1},
+            "gone": true,
+            "mid": 1
+        });
+        let after = json!({
+            "zeta": 2,
+            "alpha": {"second": 2, "first": 2, "added": 0},
+            "mid": 2,
+            "new": "x"
+        });
+        let changes = diff_ordered(&before, &after);
+        assert_eq!(
+            paths(&changes),
+            [
+                "zeta",
+                "alpha.second",
+                "alpha.first",
+                "alpha.added",
+                "mid",
+                "new",
+                "gone"
+            ]
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! Parallel diffing of top-level keys (requires the `rayon` feature).
+
+use crate::path::join_path;
+use crate::{Change, Delta, DiffOptions, DotCodec, compare, diff};
+use rayon::prelude::*;
+use serde_json::Value;
+
+/// Compute the same delta as [`diff`], diffing the top-level keys of two objects in parallel
+/// on rayon's global thread pool.
+///
+/// The subtrees under different top-level keys are independent, so each key is diffed on
+/// its own and the partial deltas are merged; the result is identical to `diff`'s. This
+/// pays off for objects with many keys or large subtrees; for small documents the
+/// scheduling overhead outweighs the gain. Non-object roots are diffed serially.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff, diff_parallel};
+///
+/// let before = json!({"a": {"x": 1}, "b": [1], "c": true});
+/// let after = json!({"a": {"x": 2}, "b": [1], "d": null});
+/// assert_eq!(diff_parallel(&before, &after), diff(&before, &after));
+/// ```
+pub fn diff_parallel(before: &Value, after: &Value) -> Delta {
+    let (Value::Object(a), Value::Object(b)) = (before, after) else {
+        return diff(before, after);
+    };
+    let keys: Vec<&String> = a
+        .keys()
+        .chain(b.keys().filter(|key| !a.contains_key(*key)))
+        .collect();
+    let options = DiffOptions::default();
+    keys.into_par_iter()
+        .map(|key| {
+            let path = join_path("", key);
+            let mut delta = Delta::new();
+            match (a.get(key), b.get(key)) {
+                (Some(va), Some(vb)) => compare(&mut delta, &options, &DotCodec, path, va, vb),
+                (Some(va), None) => {
+                    delta.insert(path, Change::Remove(va.clone()));
+                }
+                (None, Some(vb)) => {
+                    delta.insert(path, Change::Add(vb.clone()));
+                }
+                (None, None) => {}
+            }
+            delta
+        })
+        .reduce(Delta::new, |mut merged, part| {
+            merged.extend(part);
+            merged
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Map, json};
+
+    #[test]
+    fn matches_serial_diff() {
+        let before: Value = (0..2_000)
+            .map(|i| {
+                (
+                    format!("k{i}"),
+                    json!({"id": i, "tags": ["a"], "on": i % 2 == 0}),
+                )
+            })
+            .collect::<Map<_, _>>()
+            .into();
+        let mut after = before.clone();
+        for i in (0..2_000).step_by(7) {
+            after[format!("k{i}")]["on"] = json!(i % 3 == 0);
+        }
+        after.as_object_mut().unwrap().remove("k5");
+        after["new"] = json!({"nested": [1, 2]});
+
+        let delta = diff_parallel(&before, &after);
+        assert!(!delta.is_empty());
+        assert_eq!(delta, diff(&before, &after));
+    }
+
+    #[test]
+    fn non_object_roots_fall_back_to_serial_diff() {
+        for (before, after) in [(json!([1]), json!([2])), (json!({"a": 1}), json!(3))] {
+            assert_eq!(diff_parallel(&before, &after), diff(&before, &after));
+        }
+    }
+}
@@ -0,0 +1,340 @@
+//! Applying RFC 6902 JSON Patch documents.
+
+use crate::path::parse_index;
+use crate::reorder::remove_key;
+use crate::{JsonDiffError, JsonPointerCodec, PathCodec};
+use serde_json::Value;
+
+/// Apply an RFC 6902 JSON Patch (an array of operation objects) to `target`.
+///
+/// Operations run in order and paths are RFC 6901 JSON Pointers (`/a/b/0`, with `~1` for
+/// `/` and `~0` for `~` inside keys), independent of this crate's dot-paths. All six
+/// operations are supported: `add` (a final `-` appends to an array), `remove`,
+/// `replace`, `move`, `copy` and `test`; removing the whole document (`""`) leaves `null`.
+/// The patch is atomic: `target` is never modified, and the first failing operation aborts
+/// with an error:
+///
+/// - `JsonDiffError::InvalidPatch` for a malformed patch or operation (unknown `op`,
+///   missing member, pointer not starting with `/`, or a `move` into its own child);
+/// - `JsonDiffError::PathNotFound` when a location (or its parent, for `add`) is missing;
+/// - `JsonDiffError::IndexOutOfBounds` for an array index past the end;
+/// - `JsonDiffError::StaleValue` when a `test` finds a different value.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::apply_json_patch;
+///
+/// let patch = json!([
+///     {"op": "test", "path": "/version", "value": 1},
+///     {"op": "replace", "path": "/version", "value": 2},
+///     {"op": "add", "path": "/tags/-", "value": "new"}
+/// ]);
+/// let patched = apply_json_patch(&json!({"version": 1, "tags": []}), &patch).unwrap();
+/// assert_eq!(patched, json!({"version": 2, "tags": ["new"]}));
+/// ```
+pub fn apply_json_patch(target: &Value, patch: &Value) -> Result<Value, JsonDiffError> {
+    let Value::Array(operations) = patch else {
+        return Err(invalid("a patch must be an array of operations"));
+    };
+    let mut doc = target.clone();
+    for operation in operations {
+        apply_operation(&mut doc, operation)?;
+    }
+    Ok(doc)
+}
+
+fn apply_operation(doc: &mut Value, operation: &Value) -> Result<(), JsonDiffError> {
+    let op = member(operation, "op")?
+        .as_str()
+        .ok_or_else(|| invalid("`op` must be a string"))?;
+    let path = pointer_member(operation, "path")?;
+    match op {
+        "add" => add(doc, path, member(operation, "value")?.clone()),
+        "remove" => remove(doc, path).map(drop),
+        "replace" => {
+            let slot = lookup_mut(doc, path)?;
+            *slot = member(operation, "value")?.clone();
+            Ok(())
+        }
+        "move" => {
+            let from = pointer_member(operation, "from")?;
+            if from == path {
+                return lookup_mut(doc, from).map(drop);
+            }
+            if path.starts_with(from) && path[from.len()..].starts_with('/') {
+                return Err(invalid(&format!("cannot move `{from}` into its own child")));
+            }
+            let value = remove(doc, from)?;
+            add(doc, path, value)
+        }
+        "copy" => {
+            let from = pointer_member(operation, "from")?;
+            let value = lookup_mut(doc, from)?.clone();
+            add(doc, path, value)
+        }
+        "test" => {
+            if *lookup_mut(doc, path)? == *member(operation, "value")? {
+                Ok(())
+            } else {
+                Err(JsonDiffError::StaleValue {
+                    path: path.to_string(),
+                })
+            }
+        }
+        other => Err(invalid(&format!("unknown op `{other}`"))),
+    }
+}
+
+fn member<'a>(operation: &'a Value, name: &str) -> Result<&'a Value, JsonDiffError> {
+    operation
+        .get(name)
+        .ok_or_else(|| invalid(&format!("operation is missing `{name}`")))
+}
+
+fn pointer_member<'a>(operation: &'a Value, name: &str) -> Result<&'a str, JsonDiffError> {
+    let pointer = member(operation, name)?
+        .as_str()
+        .ok_or_else(|| invalid(&format!("`{name}` must be a string")))?;
+    if pointer.is_empty() || pointer.starts_with('/') {
+        Ok(pointer)
+    } else {
+        Err(invalid(&format!("`{pointer}` is not a JSON Pointer")))
+    }
+}
+
+/// Split a JSON Pointer into its unescaped reference tokens.
+fn tokens(pointer: &str) -> Vec<String> {
+    JsonPointerCodec.decode(pointer)
+}
+
+/// Parse an array index token; RFC 6901 forbids leading zeros.
+fn array_index(token: &str) -> Option<usize> {
+    parse_index(token).filter(|_| token == "0" || !token.starts_with('0'))
+}
+
+fn lookup_mut<'a>(doc: &'a mut Value, pointer: &str) -> Result<&'a mut Value, JsonDiffError> {
+    let mut current = doc;
+    for token in tokens(pointer) {
+        current = match current {
+            Value::Object(obj) => obj.get_mut(&token),
+            Value::Array(items) => array_index(&token).and_then(|i| items.get_mut(i)),
+            _ => None,
+        }
+        .ok_or_else(|| not_found(pointer))?;
+    }
+    Ok(current)
+}
+
+/// The parent container of `pointer` and the final token.
+fn parent_mut<'a>(
+    doc: &'a mut Value,
+    pointer: &str,
+) -> Result<(&'a mut Value, String), JsonDiffError> {
+    let (parent, _) = pointer.rsplit_once('/').unwrap_or(("", pointer));
+    let last = tokens(pointer).pop().unwrap_or_default();
+    let parent = lookup_mut(doc, parent).map_err(|_| not_found(pointer))?;
+    Ok((parent, last))
+}
+
+fn add(doc: &mut Value, pointer: &str, value: Value) -> Result<(), JsonDiffError> {
+    if pointer.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    let (parent, last) = parent_mut(doc, pointer)?;
+    match parent {
+        Value::Object(obj) => {
+            obj.insert(last, value);
+            Ok(())
+        }
+        Value::Array(items) if last == "-" => {
+            items.push(value);
+            Ok(())
+        }
+        Value::Array(items) => {
+            let index = array_index(&last).ok_or_else(|| not_found(pointer))?;
+            if index > items.len() {
+                return Err(out_of_bounds(pointer, index, items.len()));
+            }
+            items.insert(index, value);
+            Ok(())
+        }
+        _ => Err(not_found(pointer)),
+    }
+}
+
+fn remove(doc: &mut Value, pointer: &str) -> Result<Value, JsonDiffError> {
+    if pointer.is_empty() {
+        return Ok(std::mem::take(doc));
+    }
+    let (parent, last) = parent_mut(doc, pointer)?;
+    match parent {
+        Value::Object(obj) => remove_key(obj, &last).ok_or_else(|| not_found(pointer)),
+        Value::Array(items) => {
+            let index = array_index(&last).ok_or_else(|| not_found(pointer))?;
+            if index >= items.len() {
+                return Err(out_of_bounds(pointer, index, items.len()));
+            }
+            Ok(items.remove(index))
+        }
+        _ => Err(not_found(pointer)),
+    }
+}
+
+fn invalid(message: &str) -> JsonDiffError {
+    JsonDiffError::InvalidPatch {
+        message: message.to_string(),
+    }
+}
+
+fn not_found(pointer: &str) -> JsonDiffError {
+    JsonDiffError::PathNotFound {
+        path: pointer.to_string(),
+    }
+}
+
+fn out_of_bounds(pointer: &str, index: usize, len: usize) -> JsonDiffError {
+    JsonDiffError::IndexOutOfBounds {
+        path: pointer.to_string(),
+        index,
+        len,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn patch(target: Value, operations: Value) -> Result<Value, JsonDiffError> {
+        apply_json_patch(&target, &operations)
+    }
+
+    #[test]
+    fn rfc6902_examples() {
+        // A.1 - A.3, A.5: add, remove and replace members and elements.
+        assert_eq!(
+            patch(
+                json!({"foo": "bar"}),
+                json!([{"op": "add", "path": "/baz", "value": "qux"}])
+            ),
+            Ok(json!({"baz": "qux", "foo": "bar"}))
+        );
+        assert_eq!(
+            patch(
+                json!({"foo": ["bar", "baz"]}),
+                json!([{"op": "add", "path": "/foo/1", "value": "qux"}])
+            ),
+            Ok(json!({"foo": ["bar", "qux", "baz"]}))
+        );
+        assert_eq!(
+            patch(
+                json!({"baz": "qux", "foo": "bar"}),
+                json!([{"op": "remove", "path": "/baz"}])
+            ),
+            Ok(json!({"foo": "bar"}))
+        );
+        assert_eq!(
+            patch(
+                json!({"baz": "qux", "foo": "bar"}),
+                json!([{"op": "replace", "path": "/baz", "value": "boo"}])
+            ),
+            Ok(json!({"baz": "boo", "foo": "bar"}))
+        );
+        // A.6, A.7: move a member and an element.
+        assert_eq!(
+            patch(
+                json!({"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}),
+                json!([{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}])
+            ),
+            Ok(json!({"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}))
+        );
+        assert_eq!(
+            patch(
+                json!({"foo": ["all", "grass", "cows", "eat"]}),
+                json!([{"op": "move", "from": "/foo/1", "path": "/foo/3"}])
+            ),
+            Ok(json!({"foo": ["all", "cows", "eat", "grass"]}))
+        );
+        // A.14: escaped keys; A.16: append with `-`.
+        assert_eq!(
+            patch(
+                json!({"/": 9, "~1": 10}),
+                json!([{"op": "test", "path": "/~01", "value": 10}])
+            ),
+            Ok(json!({"/": 9, "~1": 10}))
+        );
+        assert_eq!(
+            patch(
+                json!({"foo": ["bar"]}),
+                json!([{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}])
+            ),
+            Ok(json!({"foo": ["bar", ["abc", "def"]]}))
+        );
+    }
+
+    #[test]
+    fn failing_test_op_aborts_the_patch() {
+        // A.8 succeeds, A.9 fails.
+        let doc = json!({"baz": "qux", "foo": ["a", 2, "c"]});
+        assert_eq!(
+            patch(
+                doc.clone(),
+                json!([
+                    {"op": "test", "path": "/baz", "value": "qux"},
+                    {"op": "test", "path": "/foo/1", "value": 2}
+                ])
+            ),
+            Ok(doc.clone())
+        );
+        assert_eq!(
+            patch(
+                json!({"baz": "qux"}),
+                json!([
+                    {"op": "remove", "path": "/baz"},
+                    {"op": "test", "path": "/baz", "value": "bar"}
+                ])
+            ),
+            Err(not_found("/baz"))
+        );
+        assert_eq!(
+            patch(
+                json!({"baz": "qux"}),
+                json!([{"op": "test", "path": "/baz", "value": "bar"}])
+            ),
+            Err(JsonDiffError::StaleValue {
+                path: "/baz".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_operations_are_rejected() {
+        // A.12: adding to a nonexistent target.
+        assert_eq!(
+            patch(
+                json!({"foo": "bar"}),
+                json!([{"op": "add", "path": "/baz/bat", "value": "qux"}])
+            ),
+            Err(not_found("/baz/bat"))
+        );
+        assert_eq!(
+            patch(
+                json!({"a": [1]}),
+                json!([{"op": "add", "path": "/a/5", "value": 2}])
+            ),
+            Err(out_of_bounds("/a/5", 5, 1))
+        );
+        for operations in [
+            json!({"op": "add"}),
+            json!([{"op": "frobnicate", "path": "/a"}]),
+            json!([{"op": "add", "path": "a", "value": 1}]),
+            json!([{"op": "move", "from": "/a", "path": "/a/b"}]),
+        ] {
+            assert!(matches!(
+                patch(json!({"a": {}}), operations),
+                Err(JsonDiffError::InvalidPatch { .. })
+            ));
+        }
+    }
+}
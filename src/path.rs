@@ -0,0 +1,270 @@
+//! Navigating JSON values by dot-path.
+
+use crate::{DotCodec, PathCodec};
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// Look up the value at a dot-path, following object keys and array indices.
+///
+/// The empty path refers to `value` itself. A negative index counts from the end of the
+/// array, as when applying a delta (`-1` is the last element). Returns `None` if any
+/// segment is missing.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::get_path;
+///
+/// let doc = json!({"user": {"roles": ["admin", "editor"]}});
+/// assert_eq!(get_path(&doc, "user.roles.1"), Some(&json!("editor")));
+/// assert_eq!(get_path(&doc, "user.roles.-2"), Some(&json!("admin")));
+/// assert_eq!(get_path(&doc, "user.email"), None);
+/// ```
+pub fn get_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.is_empty() {
+        return Some(value);
+    }
+    path.split('.')
+        .try_fold(value, |current, segment| match current {
+            Value::Object(obj) => obj.get(segment),
+            Value::Array(items) => items.get(resolve_position(segment, items.len())?),
+            _ => None,
+        })
+}
+
+/// Mutable counterpart of [`get_path`].
+///
+/// Unlike applying a delta, this never creates intermediate containers: it returns
+/// `None` if any segment is missing.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::get_path_mut;
+///
+/// let mut doc = json!({"user": {"name": "Ann"}});
+/// *get_path_mut(&mut doc, "user.name").unwrap() = json!("Bob");
+/// assert_eq!(doc, json!({"user": {"name": "Bob"}}));
+/// assert!(get_path_mut(&mut doc, "user.email").is_none());
+/// ```
+pub fn get_path_mut<'a>(value: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    if path.is_empty() {
+        return Some(value);
+    }
+    path.split('.')
+        .try_fold(value, |current, segment| match current {
+            Value::Object(obj) => obj.get_mut(segment),
+            Value::Array(items) => {
+                let index = resolve_position(segment, items.len())?;
+                items.get_mut(index)
+            }
+            _ => None,
+        })
+}
+
+/// Split a dot-path into its segments, exactly as the crate does when applying it.
+///
+/// The empty path has no segments, and empty segments are kept (`a..b` has three), so
+/// `path_segments(path).join(".") == path` for every path. Array segments stay as written
+/// (`2`, `+2`, `-`); see [`path_segments_with`] for paths spelled by another codec.
+///
+/// ```rust
+/// use json_diff::path_segments;
+///
+/// assert_eq!(path_segments("user.roles.+1"), ["user", "roles", "+1"]);
+/// assert!(path_segments("").is_empty());
+/// ```
+pub fn path_segments(path: &str) -> Vec<String> {
+    DotCodec.decode(path)
+}
+
+/// Split a path spelled with `codec` into its segments, unescaping them.
+///
+/// ```rust
+/// use json_diff::{path_segments_with, EscapedDotCodec, JsonPointerCodec};
+///
+/// assert_eq!(path_segments_with(r"hosts.example\.com", &EscapedDotCodec), ["hosts", "example.com"]);
+/// assert_eq!(path_segments_with("/a~1b/0", &JsonPointerCodec), ["a/b", "0"]);
+/// ```
+pub fn path_segments_with(path: &str, codec: &dyn PathCodec) -> Vec<String> {
+    codec.decode(path)
+}
+
+/// Like [`get_path`], for a path already split into segments.
+pub(crate) fn get_segments<'a, S: AsRef<str>>(
+    value: &'a Value,
+    segments: &[S],
+) -> Option<&'a Value> {
+    segments
+        .iter()
+        .try_fold(value, |current, segment| match current {
+            Value::Object(obj) => obj.get(segment.as_ref()),
+            Value::Array(items) => items.get(resolve_position(segment.as_ref(), items.len())?),
+            _ => None,
+        })
+}
+
+/// Mutable counterpart of [`get_segments`].
+pub(crate) fn get_segments_mut<'a, S: AsRef<str>>(
+    value: &'a mut Value,
+    segments: &[S],
+) -> Option<&'a mut Value> {
+    segments
+        .iter()
+        .try_fold(value, |current, segment| match current {
+            Value::Object(obj) => obj.get_mut(segment.as_ref()),
+            Value::Array(items) => {
+                let index = resolve_position(segment.as_ref(), items.len())?;
+                items.get_mut(index)
+            }
+            _ => None,
+        })
+}
+
+/// Append `key` to `path`.
+pub(crate) fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+/// Whether `path` is `prefix` itself or lies underneath it.
+pub(crate) fn is_within(path: &str, prefix: &str) -> bool {
+    prefix.is_empty()
+        || path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// Whether `path` matches `pattern` segment by segment, where a `*` segment in the
+/// pattern matches any single segment.
+pub(crate) fn path_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern = pattern.split('.');
+    let mut path = path.split('.');
+    loop {
+        match (pattern.next(), path.next()) {
+            (None, None) => return true,
+            (Some(p), Some(s)) if p == "*" || p == s => {}
+            _ => return false,
+        }
+    }
+}
+
+/// Compare two paths segment by segment, ordering index-like segments (`2`, `+10`)
+/// numerically and other segments as strings, so `arr.2` comes before `arr.10`.
+pub(crate) fn natural_cmp<S: AsRef<str>>(a: &[S], b: &[S]) -> Ordering {
+    for (x, y) in a.iter().zip(b) {
+        let (x, y) = (x.as_ref(), y.as_ref());
+        let ord = match (parse_position(x), parse_position(y)) {
+            // An old index comes before the new index of the same value.
+            (Some(i), Some(j)) => i.cmp(&j).then(x.starts_with('+').cmp(&y.starts_with('+'))),
+            _ => x.cmp(y),
+        };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+/// Parse an array index segment (plain decimal digits only).
+pub(crate) fn parse_index(segment: &str) -> Option<usize> {
+    if segment.is_empty() || !segment.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    segment.parse().ok()
+}
+
+/// Parse an insertion segment (`+N`), which addresses index `N` of the new array.
+pub(crate) fn parse_insertion(segment: &str) -> Option<usize> {
+    parse_index(segment.strip_prefix('+')?)
+}
+
+/// Parse either kind of array segment to the index it addresses.
+pub(crate) fn parse_position(segment: &str) -> Option<usize> {
+    parse_index(segment).or_else(|| parse_insertion(segment))
+}
+
+/// Parse a segment counting back from the end of an array (`-N`, with `N` at least 1),
+/// returning `N`. Only hand-written deltas use these; `diff` never produces them.
+pub(crate) fn parse_from_end(segment: &str) -> Option<usize> {
+    parse_index(segment.strip_prefix('-')?).filter(|&n| n > 0)
+}
+
+/// Resolve an array segment (`N`, `+N` or `-N`) to an index of an array of length `len`.
+/// A `-N` reaching before the start of the array resolves to nothing.
+pub(crate) fn resolve_position(segment: &str, len: usize) -> Option<usize> {
+    parse_position(segment).or_else(|| len.checked_sub(parse_from_end(segment)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn get_path_walks_objects_and_arrays() {
+        let doc = json!({"a": {"b": [{"c": 1}, {"c": 2}]}, "0": "zero"});
+        assert_eq!(get_path(&doc, ""), Some(&doc));
+        assert_eq!(get_path(&doc, "a.b.1.c"), Some(&json!(2)));
+        assert_eq!(get_path(&doc, "0"), Some(&json!("zero")));
+        assert_eq!(get_path(&doc, "a.b.2"), None);
+        assert_eq!(get_path(&doc, "a.b.-1.c"), Some(&json!(2)));
+        assert_eq!(get_path(&doc, "a.b.-3"), None);
+        assert_eq!(get_path(&doc, "a.b.x"), None);
+    }
+
+    #[test]
+    fn get_path_mut_edits_in_place_without_creating() {
+        let mut doc = json!({"a": {"b": [{"c": 1}]}});
+        if let Some(leaf) = get_path_mut(&mut doc, "a.b.0.c") {
+            *leaf = json!(42);
+        }
+        assert_eq!(doc, json!({"a": {"b": [{"c": 42}]}}));
+
+        assert!(get_path_mut(&mut doc, "a.x.y").is_none());
+        assert!(get_path_mut(&mut doc, "a.b.3").is_none());
+        *get_path_mut(&mut doc, "a.b.-1.c").unwrap() = json!(42);
+        assert_eq!(doc, json!({"a": {"b": [{"c": 42}]}}));
+    }
+
+    #[test]
+    fn path_matches_single_segment_wildcards() {
+        assert!(path_matches("items.*.id", "items.3.id"));
+        assert!(path_matches("user.age", "user.age"));
+        assert!(!path_matches("items.*.id", "items.3.name"));
+        assert!(!path_matches("items.*", "items.3.id"));
+        assert!(!path_matches("items.*.id", "items.id"));
+    }
+
+    #[test]
+    fn is_within_respects_segment_boundaries() {
+        assert!(is_within("user.name", "user"));
+        assert!(is_within("user", "user"));
+        assert!(is_within("anything", ""));
+        assert!(!is_within("username", "user"));
+    }
+
+    #[test]
+    fn path_segments_split_like_the_codecs() {
+        assert_eq!(path_segments("a.b.0.+1.-"), ["a", "b", "0", "+1", "-"]);
+        assert_eq!(path_segments("a..b"), ["a", "", "b"]);
+        assert_eq!(path_segments("a..b").join("."), "a..b");
+        assert!(path_segments("").is_empty());
+
+        let escaped = r"config.example\.com.port";
+        assert_eq!(
+            path_segments_with(escaped, &crate::EscapedDotCodec),
+            ["config", "example.com", "port"]
+        );
+        assert_eq!(
+            path_segments_with("/a.b/c~1d/~0e/1", &crate::JsonPointerCodec),
+            ["a.b", "c/d", "~e", "1"]
+        );
+        assert!(path_segments_with("", &crate::JsonPointerCodec).is_empty());
+
+        let doc = json!({"a.b": [{"c/d": 1}]});
+        let segments = path_segments_with("/a.b/0/c~1d", &crate::JsonPointerCodec);
+        assert_eq!(get_segments(&doc, &segments), Some(&json!(1)));
+    }
+}
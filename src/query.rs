@@ -0,0 +1,423 @@
+//! Slicing, grouping and comparing deltas for reporting.
+
+use crate::{Change, Delta, diff};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Partition a delta by the first segment of each path.
+///
+/// All `user.*` changes end up under `"user"`, all `settings.*` under `"settings"`, and
+/// so on. A change to a top-level key itself (such as `"status"`) is grouped under that
+/// key. Paths are kept whole inside each group, so every group is a valid delta on its own.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff, group_by_top_level};
+///
+/// let delta = diff(
+///     &json!({"user": {"name": "Ann", "age": 30}, "status": "a"}),
+///     &json!({"user": {"name": "Bo", "age": 31}, "status": "b"}),
+/// );
+/// let groups = group_by_top_level(&delta);
+/// assert_eq!(groups["user"].len(), 2);
+/// assert!(groups["status"].contains_key("status"));
+/// ```
+pub fn group_by_top_level(delta: &Delta) -> BTreeMap<String, Delta> {
+    let mut groups: BTreeMap<String, Delta> = BTreeMap::new();
+    for (path, change) in delta {
+        let top = path.split('.').next().unwrap_or(path);
+        groups
+            .entry(top.to_string())
+            .or_default()
+            .insert(path.clone(), change.clone());
+    }
+    groups
+}
+
+/// The `Add`s of `delta`, as `(path, value)` pairs in path order.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{adds, diff};
+///
+/// let delta = diff(&json!({"a": 1}), &json!({"a": 2, "b": true}));
+/// assert_eq!(adds(&delta).collect::<Vec<_>>(), [("b", &json!(true))]);
+/// ```
+pub fn adds(delta: &Delta) -> impl Iterator<Item = (&str, &Value)> {
+    delta.iter().filter_map(|(path, change)| match change {
+        Change::Add(value) => Some((path.as_str(), value)),
+        _ => None,
+    })
+}
+
+/// The `Remove`s of `delta`, as `(path, value)` pairs in path order.
+pub fn removes(delta: &Delta) -> impl Iterator<Item = (&str, &Value)> {
+    delta.iter().filter_map(|(path, change)| match change {
+        Change::Remove(value) => Some((path.as_str(), value)),
+        _ => None,
+    })
+}
+
+/// The `Modify`s of `delta`, as `(path, old, new)` triples in path order.
+///
+/// Other changes that replace a value without recording both sides (`Set`,
+/// `NumberDelta`, `Opaque`) are not included.
+pub fn modifies(delta: &Delta) -> impl Iterator<Item = (&str, &Value, &Value)> {
+    delta.iter().filter_map(|(path, change)| match change {
+        Change::Modify { old, new } => Some((path.as_str(), old, new)),
+        _ => None,
+    })
+}
+
+/// Whether a path exists before and after a change, as reported by [`presence_changes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Presence {
+    /// The path was absent and now holds a value.
+    Appeared,
+    /// The path held a value and is now absent.
+    Disappeared,
+    /// The path exists on both sides, with a different value.
+    ValueChanged,
+    /// The path exists on both sides, with the same value (a watched `Unchanged` path).
+    Unchanged,
+}
+
+/// Classify each change of `delta` by whether its path exists before and after it, so
+/// that presence changes (a key appearing or disappearing) can be told apart from value
+/// changes of a key that exists throughout.
+///
+/// An `Add` or `InsertRange` appears and a `Remove` or `RemoveRange` disappears;
+/// `Unchanged` keeps its value, and every other change at a path (`Modify`, `Set`,
+/// `NumberDelta`, ...) changes the value of a path present on both sides. A `Move` or
+/// `Rename` yields two entries, `from` disappearing and then `to` appearing, and a `Copy`
+/// makes `to` appear.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff, presence_changes, Presence};
+///
+/// let delta = diff(&json!({"nick": null, "age": 30}), &json!({"age": 31, "email": "a@b"}));
+/// assert_eq!(
+///     presence_changes(&delta),
+///     [
+///         ("age".to_string(), Presence::ValueChanged),
+///         ("email".to_string(), Presence::Appeared),
+///         ("nick".to_string(), Presence::Disappeared),
+///     ]
+/// );
+/// ```
+pub fn presence_changes(delta: &Delta) -> Vec<(String, Presence)> {
+    let mut changes = Vec::new();
+    for (path, change) in delta {
+        match change {
+            Change::Add(_) | Change::InsertRange(_) => {
+                changes.push((path.clone(), Presence::Appeared))
+            }
+            Change::Remove(_) | Change::RemoveRange(_) => {
+                changes.push((path.clone(), Presence::Disappeared))
+            }
+            Change::Unchanged(_) => changes.push((path.clone(), Presence::Unchanged)),
+            Change::Move { from, to } | Change::Rename { from, to } => {
+                changes.push((from.clone(), Presence::Disappeared));
+                changes.push((to.clone(), Presence::Appeared));
+            }
+            Change::Copy { to, .. } => changes.push((to.clone(), Presence::Appeared)),
+            _ => changes.push((path.clone(), Presence::ValueChanged)),
+        }
+    }
+    changes
+}
+
+/// How a path's change differs between two deltas, as reported by [`diff_deltas`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeltaChange {
+    /// Only the second delta changes this path.
+    Added(Change),
+    /// Only the first delta changes this path.
+    Removed(Change),
+    /// Both deltas change this path, differently.
+    Modified { old: Change, new: Change },
+}
+
+/// Compare two deltas path by path, e.g. two revisions of a generated patch.
+///
+/// Paths that both deltas change in exactly the same way are left out.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff_deltas, DeltaBuilder, DeltaChange};
+///
+/// let first = DeltaBuilder::new().add("a", json!(1)).build();
+/// let second = DeltaBuilder::new().add("a", json!(2)).build();
+/// assert!(matches!(diff_deltas(&first, &second)["a"], DeltaChange::Modified { .. }));
+/// ```
+pub fn diff_deltas(a: &Delta, b: &Delta) -> BTreeMap<String, DeltaChange> {
+    let mut changes = BTreeMap::new();
+    for (path, old) in a.iter() {
+        match b.get(path) {
+            Some(new) if new == old => {}
+            Some(new) => {
+                changes.insert(
+                    path.clone(),
+                    DeltaChange::Modified {
+                        old: old.clone(),
+                        new: new.clone(),
+                    },
+                );
+            }
+            None => {
+                changes.insert(path.clone(), DeltaChange::Removed(old.clone()));
+            }
+        }
+    }
+    for (path, new) in b.iter() {
+        if !a.contains_key(path) {
+            changes.insert(path.clone(), DeltaChange::Added(new.clone()));
+        }
+    }
+    changes
+}
+
+/// The differences between two documents split by direction, as returned by
+/// [`symmetric_diff`].
+///
+/// Each collection is a delta keyed by path, so all three can be applied, inspected or
+/// reported like any other delta.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SymDiff {
+    /// Paths present only in the first document, as `Remove`s.
+    pub only_in_a: Delta,
+    /// Paths present only in the second document, as `Add`s.
+    pub only_in_b: Delta,
+    /// Paths present in both documents with different values, as `Modify`s.
+    pub different: Delta,
+}
+
+impl SymDiff {
+    /// Whether the two documents are equal.
+    pub fn is_empty(&self) -> bool {
+        self.only_in_a.is_empty() && self.only_in_b.is_empty() && self.different.is_empty()
+    }
+}
+
+/// Compute everything that differs between `a` and `b`, split by direction.
+///
+/// This is [`diff`] with its changes sorted into what only `a` has, what only `b` has and
+/// what both have but disagree on, which is the shape reconciliation views need. Array
+/// elements follow `diff`: a removed element appears under `only_in_a` at its index in `a`
+/// and an inserted one under `only_in_b` at its position in `b` (`+i`).
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::symmetric_diff;
+///
+/// let sym = symmetric_diff(
+///     &json!({"legacy": true, "name": "Ann"}),
+///     &json!({"name": "Bo", "owner": "ann"}),
+/// );
+/// assert!(sym.only_in_a.contains_key("legacy"));
+/// assert!(sym.only_in_b.contains_key("owner"));
+/// assert!(sym.different.contains_key("name"));
+/// ```
+pub fn symmetric_diff(a: &Value, b: &Value) -> SymDiff {
+    let mut sym = SymDiff::default();
+    for (path, change) in diff(a, b).into_inner() {
+        let side = match change {
+            Change::Remove(_) => &mut sym.only_in_a,
+            Change::Add(_) => &mut sym.only_in_b,
+            _ => &mut sym.different,
+        };
+        side.insert(path, change);
+    }
+    sym
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeltaBuilder, DiffOptions, apply, diff_with};
+    use serde_json::json;
+
+    #[test]
+    fn presence_changes_separate_existence_from_values() {
+        let before = json!({"a": null, "b": 1, "c": {"d": 1}, "old": 5, "w": true});
+        let after = json!({"a": 0, "b": 1, "c": {}, "e": null, "new": 5, "w": true});
+        let options = DiffOptions {
+            detect_renames: true,
+            watched: vec!["w".to_string()],
+            ..Default::default()
+        };
+        let delta = diff_with(&before, &after, &options);
+        assert_eq!(
+            presence_changes(&delta),
+            [
+                // `null` → `0` is a value change of a present key, not an appearance.
+                ("a".to_string(), Presence::ValueChanged),
+                ("c.d".to_string(), Presence::Disappeared),
+                ("e".to_string(), Presence::Appeared),
+                ("old".to_string(), Presence::Disappeared),
+                ("new".to_string(), Presence::Appeared),
+                ("w".to_string(), Presence::Unchanged),
+            ]
+        );
+    }
+
+    #[test]
+    fn groups_changes_by_first_segment() {
+        let before = json!({
+            "user": {"name": "Ann", "address": {"city": "Rio"}},
+            "settings": {"theme": "dark"},
+            "version": 1
+        });
+        let after = json!({
+            "user": {"name": "Ann B", "address": {"city": "Recife"}},
+            "settings": {"theme": "light", "lang": "pt"},
+            "version": 2,
+            "archived": true
+        });
+        let delta = diff(&before, &after);
+        let groups = group_by_top_level(&delta);
+
+        assert_eq!(
+            groups.keys().collect::<Vec<_>>(),
+            ["archived", "settings", "user", "version"]
+        );
+        assert_eq!(
+            groups["user"].keys().collect::<Vec<_>>(),
+            ["user.address.city", "user.name"]
+        );
+        assert_eq!(
+            groups["settings"].keys().collect::<Vec<_>>(),
+            ["settings.lang", "settings.theme"]
+        );
+        assert_eq!(
+            groups["archived"].get("archived"),
+            Some(&Change::Add(json!(true)))
+        );
+        assert_eq!(groups["version"].len(), 1);
+
+        let total: usize = groups.values().map(|group| group.len()).sum();
+        assert_eq!(total, delta.len());
+    }
+
+    #[test]
+    fn diff_deltas_reports_overlapping_and_disjoint_paths() {
+        let first = DeltaBuilder::new()
+            .modify("name", json!("a"), json!("b"))
+            .add("tags", json!(["x"]))
+            .remove("legacy", json!(true))
+            .build();
+        let second = DeltaBuilder::new()
+            .modify("name", json!("a"), json!("c"))
+            .add("tags", json!(["x"]))
+            .add("owner", json!("ann"))
+            .build();
+
+        let changes = diff_deltas(&first, &second);
+        assert_eq!(
+            changes.keys().collect::<Vec<_>>(),
+            ["legacy", "name", "owner"]
+        );
+        assert_eq!(
+            changes["name"],
+            DeltaChange::Modified {
+                old: Change::Modify {
+                    old: json!("a"),
+                    new: json!("b")
+                },
+                new: Change::Modify {
+                    old: json!("a"),
+                    new: json!("c")
+                },
+            }
+        );
+        assert_eq!(
+            changes["legacy"],
+            DeltaChange::Removed(Change::Remove(json!(true)))
+        );
+        assert_eq!(
+            changes["owner"],
+            DeltaChange::Added(Change::Add(json!("ann")))
+        );
+        assert!(diff_deltas(&first, &first).is_empty());
+    }
+
+    #[test]
+    fn kind_iterators_yield_only_their_kind() {
+        let delta = DeltaBuilder::new()
+            .add("b", json!(1))
+            .add("list.+0", json!("x"))
+            .remove("c", json!(null))
+            .modify("a", json!(1), json!(2))
+            .change("s", Change::Set(json!(3)))
+            .increment("n", 1)
+            .build();
+
+        assert_eq!(
+            adds(&delta).collect::<Vec<_>>(),
+            [("b", &json!(1)), ("list.+0", &json!("x"))]
+        );
+        assert_eq!(removes(&delta).collect::<Vec<_>>(), [("c", &json!(null))]);
+        assert_eq!(
+            modifies(&delta).collect::<Vec<_>>(),
+            [("a", &json!(1), &json!(2))]
+        );
+        assert_eq!(adds(&Delta::new()).count(), 0);
+    }
+
+    #[test]
+    fn symmetric_diff_splits_by_direction() {
+        let a = json!({
+            "legacy": true,
+            "name": "Ann",
+            "shared": {"x": 1, "only_a": 2},
+            "same": [1, 2]
+        });
+        let b = json!({
+            "name": "Bo",
+            "owner": "ann",
+            "shared": {"x": 3, "only_b": 4},
+            "same": [1, 2]
+        });
+        let sym = symmetric_diff(&a, &b);
+
+        assert_eq!(
+            sym.only_in_a.keys().collect::<Vec<_>>(),
+            ["legacy", "shared.only_a"]
+        );
+        assert_eq!(
+            sym.only_in_b.keys().collect::<Vec<_>>(),
+            ["owner", "shared.only_b"]
+        );
+        assert_eq!(
+            sym.different.keys().collect::<Vec<_>>(),
+            ["name", "shared.x"]
+        );
+        assert_eq!(sym.only_in_a["legacy"], Change::Remove(json!(true)));
+        assert_eq!(sym.only_in_b["owner"], Change::Add(json!("ann")));
+        assert_eq!(
+            sym.different["shared.x"],
+            Change::Modify {
+                old: json!(1),
+                new: json!(3)
+            }
+        );
+
+        let mut whole = sym.only_in_a.clone();
+        whole.extend(sym.only_in_b.clone());
+        whole.extend(sym.different.clone());
+        assert_eq!(whole, diff(&a, &b));
+        assert_eq!(apply(&a, &whole), b);
+    }
+
+    #[test]
+    fn symmetric_diff_of_equal_documents_is_empty() {
+        let doc = json!({"a": [1, {"b": null}]});
+        assert!(symmetric_diff(&doc, &doc).is_empty());
+
+        let sym = symmetric_diff(&json!({"a": 1}), &json!({"b": 1}));
+        assert!(sym.different.is_empty());
+        assert_eq!(sym.only_in_a.len(), 1);
+        assert_eq!(sym.only_in_b.len(), 1);
+    }
+}
@@ -0,0 +1,172 @@
+//! Object key order, for `Change::Reorder` (detected under the `preserve_order` feature).
+
+use serde_json::{Map, Value};
+
+/// Reorder the keys of `obj` so those listed in `keys` come first, in that order, followed
+/// by any others in their current order. Returns whether the order changed.
+///
+/// Without the `preserve_order` feature objects keep their keys sorted, so this never
+/// changes anything.
+pub(crate) fn reorder_keys(obj: &mut Map<String, Value>, keys: &[String]) -> bool {
+    let before: Vec<String> = obj.keys().cloned().collect();
+    let rank = |key: &str| keys.iter().position(|k| k == key).unwrap_or(keys.len());
+    let mut entries: Vec<(String, Value)> = std::mem::take(obj).into_iter().collect();
+    entries.sort_by_key(|(key, _)| rank(key));
+    obj.extend(entries);
+    !obj.keys().eq(before.iter())
+}
+
+/// Remove `key` from `obj`, keeping the remaining keys in their order. Under the
+/// `preserve_order` feature `Map::remove` swaps the last key into the gap, which would
+/// reorder the object.
+pub(crate) fn remove_key(obj: &mut Map<String, Value>, key: &str) -> Option<Value> {
+    #[cfg(feature = "preserve_order")]
+    return obj.shift_remove(key);
+    #[cfg(not(feature = "preserve_order"))]
+    obj.remove(key)
+}
+
+#[cfg(feature = "preserve_order")]
+pub(crate) use detect::{key_order_change, record_reorders};
+
+#[cfg(feature = "preserve_order")]
+mod detect {
+    use crate::{Change, ChangeSink, DiffOptions, PathCodec};
+    use serde_json::{Map, Value};
+
+    /// The `Reorder` turning the key order of `a` into that of `b`, if they hold the same
+    /// keys in a different order.
+    pub(crate) fn key_order_change(
+        a: &Map<String, Value>,
+        b: &Map<String, Value>,
+    ) -> Option<Change> {
+        let same_keys = a.len() == b.len() && a.keys().all(|key| b.contains_key(key));
+        (same_keys && !a.keys().eq(b.keys())).then(|| Change::Reorder {
+            keys: b.keys().cloned().collect(),
+            previous: a.keys().cloned().collect(),
+        })
+    }
+
+    /// Record a `Reorder` for every object within `a` and `b`, two equal values, whose
+    /// keys are ordered differently.
+    pub(crate) fn record_reorders(
+        delta: &mut dyn ChangeSink,
+        options: &DiffOptions,
+        codec: &dyn PathCodec,
+        path: &str,
+        a: &Value,
+        b: &Value,
+    ) {
+        if options.should_skip(path, a, codec) {
+            return;
+        }
+        match (a, b) {
+            (Value::Object(obj_a), Value::Object(obj_b)) => {
+                if let Some(change) = key_order_change(obj_a, obj_b) {
+                    delta.record(path.to_string(), change);
+                }
+                for (key, va) in obj_a {
+                    if let Some(vb) = obj_b.get(key) {
+                        record_reorders(delta, options, codec, &codec.join(path, key), va, vb);
+                    }
+                }
+            }
+            (Value::Array(items_a), Value::Array(items_b)) => {
+                for (i, (va, vb)) in items_a.iter().zip(items_b).enumerate() {
+                    record_reorders(
+                        delta,
+                        options,
+                        codec,
+                        &codec.join(path, &i.to_string()),
+                        va,
+                        vb,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(all(test, feature = "preserve_order"))]
+mod tests {
+    use crate::{Change, DiffOptions, apply, diff, diff_with, revert, try_apply};
+    use serde_json::{Value, json};
+
+    fn options() -> DiffOptions {
+        DiffOptions {
+            detect_key_reorder: true,
+            ..Default::default()
+        }
+    }
+
+    fn keys(value: &Value) -> Vec<&str> {
+        value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect()
+    }
+
+    #[test]
+    fn pure_reorder_is_reported_and_applied() {
+        let before = json!({"a": 1, "b": 2, "c": {"x": 1, "y": 2}});
+        let after = json!({"c": {"y": 2, "x": 1}, "a": 1, "b": 2});
+        assert_eq!(before, after);
+        assert!(diff(&before, &after).is_empty());
+
+        let delta = diff_with(&before, &after, &options());
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["", "c"]);
+        assert_eq!(
+            delta[""],
+            Change::Reorder {
+                keys: vec!["c".into(), "a".into(), "b".into()],
+                previous: vec!["a".into(), "b".into(), "c".into()],
+            }
+        );
+
+        let applied = apply(&before, &delta);
+        assert_eq!(keys(&applied), ["c", "a", "b"]);
+        assert_eq!(keys(&applied["c"]), ["y", "x"]);
+        assert_eq!(
+            serde_json::to_string(&applied).unwrap(),
+            serde_json::to_string(&after).unwrap()
+        );
+        let reverted = revert(&applied, &delta);
+        assert_eq!(
+            serde_json::to_string(&reverted).unwrap(),
+            serde_json::to_string(&before).unwrap()
+        );
+    }
+
+    #[test]
+    fn reorders_combine_with_value_changes() {
+        let before = json!({"a": 1, "b": 2});
+        let after = json!({"b": 3, "a": 1});
+        let delta = diff_with(&before, &after, &options());
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["", "b"]);
+        let applied = try_apply(&before, &delta).unwrap();
+        assert_eq!(serde_json::to_string(&applied).unwrap(), r#"{"b":3,"a":1}"#);
+
+        // Removing a key leaves the others in their order.
+        let before = json!({"a": 1, "b": 2, "c": 3, "d": 4});
+        let after = json!({"a": 1, "c": 3, "d": 4});
+        let delta = diff_with(&before, &after, &options());
+        assert_eq!(delta.keys().collect::<Vec<_>>(), ["b"]);
+        let applied = apply(&before, &delta);
+        assert_eq!(
+            serde_json::to_string(&applied).unwrap(),
+            r#"{"a":1,"c":3,"d":4}"#
+        );
+        assert!(diff_with(&applied, &after, &options()).is_empty());
+
+        // Different key sets are reported as adds and removes only.
+        let delta = diff_with(
+            &json!({"a": 1, "b": 2}),
+            &json!({"c": 3, "a": 1}),
+            &options(),
+        );
+        assert!(!delta.contains_key(""));
+    }
+}
@@ -0,0 +1,111 @@
+//! Checking that a document has the structure a delta expects.
+
+use crate::path::resolve_position;
+use crate::{Change, Delta, DotCodec, PathCodec};
+use serde_json::Value;
+
+/// List the paths of `delta` whose changes do not fit the structure of `value`.
+///
+/// Every path is walked from the root down to the parent of its last segment. A change is
+/// reported when that walk has to descend through a scalar (including `null`), or into an
+/// array with a segment that is not one of its indices: applying it would overwrite that
+/// value with an object instead of editing inside it. Missing object keys are not a
+/// mismatch, since applying creates them. `Move`, `Copy` and `Rename` are checked at
+/// both `from` and `to`; `Unchanged` entries never descend and always fit.
+///
+/// This only checks parent types, not values; use [`try_apply`](crate::try_apply) to
+/// find changes whose targets or old values do not match.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{check_shape, DeltaBuilder};
+///
+/// let delta = DeltaBuilder::new()
+///     .modify("user.name", json!("Ann"), json!("Bo"))
+///     .add("settings.theme", json!("dark"))
+///     .build();
+/// assert!(check_shape(&json!({"user": {"name": "Ann"}}), &delta).is_empty());
+/// assert_eq!(check_shape(&json!({"user": "Ann"}), &delta), ["user.name"]);
+/// ```
+pub fn check_shape(value: &Value, delta: &Delta) -> Vec<String> {
+    delta
+        .iter()
+        .filter(|(path, change)| match change {
+            Change::Unchanged(_) => false,
+            _ => match change.endpoints() {
+                Some((from, to)) => !fits(value, from) || !fits(value, to),
+                None => !fits(value, path),
+            },
+        })
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
+/// Whether every parent along `path` is a container the path can descend into.
+pub(crate) fn fits(root: &Value, path: &str) -> bool {
+    let segments = DotCodec.decode(path);
+    let Some((_, parents)) = segments.split_last() else {
+        return true;
+    };
+    let mut current = root;
+    for segment in parents {
+        let next = match current {
+            Value::Object(obj) => obj.get(segment),
+            Value::Array(items) => match resolve_position(segment, items.len()) {
+                Some(i) if i < items.len() => items.get(i),
+                _ => return false,
+            },
+            _ => return false,
+        };
+        match next {
+            Some(next) => current = next,
+            None => return true,
+        }
+    }
+    matches!(current, Value::Object(_) | Value::Array(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DeltaBuilder;
+    use serde_json::json;
+
+    #[test]
+    fn matching_shapes_report_nothing() {
+        let doc = json!({"a": {"b": [{"c": 1}, {"c": 2}]}, "n": null});
+        let delta = DeltaBuilder::new()
+            .modify("a.b.1.c", json!(2), json!(3))
+            .add("a.b.+0", json!({"c": 0}))
+            .remove("a.b.-1", json!({"c": 2}))
+            .add("a.new.deep.key", json!(true))
+            .modify("n", json!(null), json!(1))
+            .modify("", json!(null), json!(1))
+            .build();
+        assert!(check_shape(&doc, &delta).is_empty());
+    }
+
+    #[test]
+    fn mismatched_intermediate_types_are_reported() {
+        let doc = json!({"a": {"b": 5}, "list": [1, 2], "n": null, "obj": {"x": 1}});
+        let mut delta = DeltaBuilder::new()
+            .modify("a.b.c", json!(1), json!(2))
+            .add("list.name.x", json!(1))
+            .add("list.7.x", json!(1))
+            .add("n.k", json!(1))
+            .modify("obj.x", json!(1), json!(2))
+            .change("ok", Change::Unchanged(json!(1)))
+            .build();
+        delta.insert(
+            "obj.y".to_string(),
+            Change::Move {
+                from: "a.b.inner".to_string(),
+                to: "obj.y".to_string(),
+            },
+        );
+        assert_eq!(
+            check_shape(&doc, &delta),
+            ["a.b.c", "list.7.x", "list.name.x", "n.k", "obj.y"]
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! Measuring how far apart two documents are.
+
+use crate::diff;
+use crate::flatten::flatten;
+use serde_json::Value;
+
+/// The number of changes `diff` reports between `a` and `b`; `0` means equal.
+pub fn distance(a: &Value, b: &Value) -> usize {
+    diff(a, b).len()
+}
+
+/// How alike `a` and `b` are, from `0.0` (nothing in common) to `1.0` (equal).
+///
+/// Both values are flattened to their leaves (see [`leaf_paths`](crate::leaf_paths)) and
+/// compared with the Dice coefficient: twice the number of leaves present and equal on
+/// both sides, divided by the total number of leaves. Scalars and arrays are single
+/// leaves, so unequal ones score `0.0`.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::similarity;
+///
+/// let a = json!({"street": "Main", "city": "Rio", "zip": "1"});
+/// let b = json!({"street": "Main", "city": "Rio", "zip": "2"});
+/// assert!((similarity(&a, &b) - 2.0 / 3.0).abs() < 1e-9);
+/// ```
+pub fn similarity(a: &Value, b: &Value) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    let leaves_a = flatten(a);
+    let leaves_b = flatten(b);
+    let shared = leaves_a
+        .iter()
+        .filter(|(path, value)| leaves_b.get(*path) == Some(value))
+        .count();
+    (2 * shared) as f64 / (leaves_a.len() + leaves_b.len()) as f64
+}
+
+/// Levenshtein distance between two strings, counted in `char`s.
+pub(crate) fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn similarity_ranges_from_disjoint_to_equal() {
+        let a = json!({"a": 1, "b": {"c": 2, "d": 3}});
+        assert_eq!(similarity(&a, &a.clone()), 1.0);
+        assert_eq!(similarity(&a, &json!({"x": 1})), 0.0);
+        assert_eq!(similarity(&json!(1), &json!(2)), 0.0);
+        assert_eq!(
+            similarity(&a, &json!({"a": 1, "b": {"c": 2, "d": 4}})),
+            2.0 / 3.0
+        );
+        assert_eq!(distance(&a, &json!({"a": 1, "b": {"c": 2, "d": 4}})), 1);
+        assert_eq!(distance(&a, &a), 0);
+    }
+
+    #[test]
+    fn levenshtein_counts_edits() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("café", "cafe"), 1);
+        assert_eq!(levenshtein("same", "same"), 0);
+    }
+}
@@ -0,0 +1,198 @@
+//! Canonical, line-per-change text form of a `Delta` for golden/snapshot tests.
+
+use crate::{Change, Delta};
+use serde_json::Value;
+use std::fmt::Write;
+
+/// Render a `Delta` as canonical text, one change per line.
+///
+/// Each line has the form `<path> <op> <values>`, where `<path>` is the JSON-quoted path,
+/// `<op>` is one of `add`, `remove`, `modify`, `set`, `unchanged`, `move`, `copy`,
+/// `rename`, `number_delta`, `array_ops`, `reorder`, `remove_range`, `insert_range` or
+/// `opaque`, and `<values>` are the affected values in canonical JSON (object keys sorted,
+/// no whitespace, floats in shortest round-trip form). `modify` lists the old value
+/// followed by the new one; `move`, `copy` and `rename` list the quoted `from` and `to`
+/// paths; `number_delta` lists the amount added; `array_ops` lists the edit script as one
+/// array; `reorder` lists the new key order followed by the previous one; the range ops
+/// list their elements as one array; `opaque` lists the quoted old and new digests. Lines
+/// follow the delta's path order.
+///
+/// Unlike `Debug` or `serde_json` output, the format does not depend on map iteration order
+/// or serializer settings, so it is byte-stable and safe to commit as a golden file.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff, to_snapshot};
+///
+/// let delta = diff(&json!({"a": 1, "b": "x"}), &json!({"a": 2, "c": [true]}));
+/// assert_eq!(
+///     to_snapshot(&delta),
+///     "\"a\" modify 1 2\n\"b\" remove \"x\"\n\"c\" add [true]\n"
+/// );
+/// ```
+pub fn to_snapshot(delta: &Delta) -> String {
+    let mut out = String::new();
+    for (path, change) in delta {
+        write_string(&mut out, path);
+        match change {
+            Change::Add(v) => {
+                out.push_str(" add ");
+                write_value(&mut out, v);
+            }
+            Change::Remove(v) => {
+                out.push_str(" remove ");
+                write_value(&mut out, v);
+            }
+            Change::Modify { old, new } => {
+                out.push_str(" modify ");
+                write_value(&mut out, old);
+                out.push(' ');
+                write_value(&mut out, new);
+            }
+            Change::Set(v) => {
+                out.push_str(" set ");
+                write_value(&mut out, v);
+            }
+            Change::Unchanged(v) => {
+                out.push_str(" unchanged ");
+                write_value(&mut out, v);
+            }
+            Change::Move { from, to } => {
+                out.push_str(" move ");
+                write_string(&mut out, from);
+                out.push(' ');
+                write_string(&mut out, to);
+            }
+            Change::Copy { from, to } => {
+                out.push_str(" copy ");
+                write_string(&mut out, from);
+                out.push(' ');
+                write_string(&mut out, to);
+            }
+            Change::Rename { from, to } => {
+                out.push_str(" rename ");
+                write_string(&mut out, from);
+                out.push(' ');
+                write_string(&mut out, to);
+            }
+            Change::NumberDelta { by } => {
+                out.push_str(" number_delta ");
+                write_value(&mut out, &Value::Number(by.clone()));
+            }
+            Change::ArrayOps(ops) => {
+                out.push_str(" array_ops ");
+                write_value(&mut out, &serde_json::to_value(ops).unwrap_or_default());
+            }
+            Change::Reorder { keys, previous } => {
+                out.push_str(" reorder ");
+                write_value(&mut out, &Value::from(keys.clone()));
+                out.push(' ');
+                write_value(&mut out, &Value::from(previous.clone()));
+            }
+            Change::RemoveRange(values) => {
+                out.push_str(" remove_range ");
+                write_value(&mut out, &Value::Array(values.clone()));
+            }
+            Change::InsertRange(values) => {
+                out.push_str(" insert_range ");
+                write_value(&mut out, &Value::Array(values.clone()));
+            }
+            Change::Opaque { old, new } => {
+                out.push_str(" opaque ");
+                write_string(&mut out, old);
+                out.push(' ');
+                write_string(&mut out, new);
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                write!(out, "{i}").unwrap();
+            } else if let Some(u) = n.as_u64() {
+                write!(out, "{u}").unwrap();
+            } else if let Some(f) = n.as_f64() {
+                write!(out, "{f:?}").unwrap();
+            }
+        }
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item);
+            }
+            out.push(']');
+        }
+        Value::Object(obj) => {
+            let mut keys: Vec<&String> = obj.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, key);
+                out.push(':');
+                write_value(out, &obj[key]);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push_str(&serde_json::to_string(s).expect("strings always serialize"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff;
+    use serde_json::json;
+
+    #[test]
+    fn snapshot_is_byte_stable() {
+        let before = json!({"user": {"name": "Ann", "age": 30}, "tags": ["a"], "ratio": 0.5});
+        let after = json!({"user": {"name": "Ann", "age": 31.0}, "tags": ["a", "b"], "meta": {"z": 1, "a": null}});
+
+        let first = to_snapshot(&diff(&before, &after));
+        let second = to_snapshot(&diff(&before, &after));
+        assert_eq!(first, second);
+        assert_eq!(
+            first,
+            concat!(
+                "\"meta\" add {\"a\":null,\"z\":1}\n",
+                "\"ratio\" remove 0.5\n",
+                "\"tags\" modify [\"a\"] [\"a\",\"b\"]\n",
+                "\"user.age\" modify 30 31.0\n",
+            )
+        );
+    }
+
+    #[test]
+    fn snapshot_independent_of_insertion_order() {
+        let mut forward = Delta::new();
+        forward.insert("b".to_string(), Change::Add(json!({"y": 2, "x": 1})));
+        forward.insert("a".to_string(), Change::Remove(json!("line\n\"quoted\"")));
+
+        let mut backward = Delta::new();
+        backward.insert("a".to_string(), Change::Remove(json!("line\n\"quoted\"")));
+        backward.insert("b".to_string(), Change::Add(json!({"x": 1, "y": 2})));
+
+        assert_eq!(to_snapshot(&forward), to_snapshot(&backward));
+        assert_eq!(
+            to_snapshot(&forward),
+            "\"a\" remove \"line\\n\\\"quoted\\\"\"\n\"b\" add {\"x\":1,\"y\":2}\n"
+        );
+    }
+}
@@ -0,0 +1,253 @@
+//! Source positions of the values a diff changes.
+
+use crate::path::join_path;
+use crate::{Change, Delta, DotCodec, JsonDiffError, PathCodec, diff};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Where a value sits in a JSON source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// Byte offset of the first byte of the value.
+    pub start: usize,
+    /// Byte offset just past the value, so `&src[start..end]` is its text.
+    pub end: usize,
+    /// Line of `start`, counting from 1.
+    pub line: usize,
+    /// Column of `start` in characters, counting from 1.
+    pub column: usize,
+}
+
+/// The source span of each change of a delta, keyed by path.
+pub type Spans = BTreeMap<String, Span>;
+
+/// Parse and diff two JSON source texts, also returning where each changed value sits in
+/// the source.
+///
+/// For an `Add`, which has no value in `before_src`, the span is that of the added value
+/// in `after_src`; for every other change it is the span of the value at the path in
+/// `before_src`, the one the change replaces or removes. Spans cover the value itself,
+/// from its first byte to its last (the quotes of a string, the brackets of an object or
+/// array), without the key or surrounding whitespace.
+///
+/// Fails with `JsonDiffError::Serialization` if either input is not valid JSON.
+///
+/// ```rust
+/// use json_diff::diff_with_spans;
+///
+/// let before = "{\n  \"name\": \"svc\",\n  \"port\": 80\n}";
+/// let after = "{\n  \"name\": \"svc\",\n  \"port\": 8080\n}";
+/// let (delta, spans) = diff_with_spans(before, after).unwrap();
+/// let port = spans["port"];
+/// assert_eq!(&before[port.start..port.end], "80");
+/// assert_eq!((port.line, port.column), (3, 11));
+/// assert_eq!(delta.len(), 1);
+/// ```
+pub fn diff_with_spans(before_src: &str, after_src: &str) -> Result<(Delta, Spans), JsonDiffError> {
+    let before: Value = serde_json::from_str(before_src)?;
+    let after: Value = serde_json::from_str(after_src)?;
+    let delta = diff(&before, &after);
+    let (old, new) = (Locator::locate(before_src), Locator::locate(after_src));
+    let spans = delta
+        .iter()
+        .filter_map(|(path, change)| {
+            let span = match change {
+                Change::Add(_) => new.span(&inserted_at(path)),
+                _ => old.span(path),
+            };
+            span.map(|span| (path.clone(), span))
+        })
+        .collect();
+    Ok((delta, spans))
+}
+
+/// `path` with array insertion segments (`+N`) turned into the index the element ends
+/// up at.
+fn inserted_at(path: &str) -> String {
+    let segments = DotCodec.decode(path);
+    segments.iter().fold(String::new(), |path, segment| {
+        let segment = segment
+            .strip_prefix('+')
+            .filter(|index| index.parse::<usize>().is_ok())
+            .unwrap_or(segment);
+        join_path(&path, segment)
+    })
+}
+
+/// A pass over JSON source text recording the byte range of every value by path. The
+/// text must already be known to be valid JSON.
+struct Locator<'a> {
+    src: &'a str,
+    pos: usize,
+    ranges: HashMap<String, (usize, usize)>,
+}
+
+impl<'a> Locator<'a> {
+    fn locate(src: &'a str) -> Located<'a> {
+        let mut locator = Locator {
+            src,
+            pos: 0,
+            ranges: HashMap::new(),
+        };
+        locator.value(String::new());
+        let line_starts = std::iter::once(0)
+            .chain(src.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Located {
+            src,
+            ranges: locator.ranges,
+            line_starts,
+        }
+    }
+
+    fn peek(&self) -> u8 {
+        self.src.as_bytes().get(self.pos).copied().unwrap_or(b'}')
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self, path: String) {
+        self.skip_whitespace();
+        let start = self.pos;
+        match self.peek() {
+            b'{' => {
+                self.pos += 1;
+                self.skip_whitespace();
+                while self.peek() != b'}' {
+                    self.skip_whitespace();
+                    let key = self.string();
+                    self.skip_whitespace();
+                    self.pos += 1; // ':'
+                    self.value(join_path(&path, &key));
+                    self.skip_whitespace();
+                    if self.peek() == b',' {
+                        self.pos += 1;
+                    }
+                }
+                self.pos += 1;
+            }
+            b'[' => {
+                self.pos += 1;
+                self.skip_whitespace();
+                let mut index = 0;
+                while self.peek() != b']' {
+                    self.value(join_path(&path, &index.to_string()));
+                    index += 1;
+                    self.skip_whitespace();
+                    if self.peek() == b',' {
+                        self.pos += 1;
+                    }
+                }
+                self.pos += 1;
+            }
+            b'"' => {
+                self.string();
+            }
+            _ => {
+                while !matches!(self.peek(), b',' | b']' | b'}')
+                    && !self.peek().is_ascii_whitespace()
+                {
+                    self.pos += 1;
+                }
+            }
+        }
+        self.ranges.insert(path, (start, self.pos));
+    }
+
+    /// Read a string token, returning its unescaped contents.
+    fn string(&mut self) -> String {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.peek() {
+                b'\\' => self.pos += 2,
+                b'"' => break,
+                _ => self.pos += 1,
+            }
+        }
+        self.pos += 1;
+        serde_json::from_str(&self.src[start..self.pos]).unwrap_or_default()
+    }
+}
+
+/// The value ranges found by a `Locator`, with what is needed to turn them into spans.
+struct Located<'a> {
+    src: &'a str,
+    ranges: HashMap<String, (usize, usize)>,
+    line_starts: Vec<usize>,
+}
+
+impl Located<'_> {
+    fn span(&self, path: &str) -> Option<Span> {
+        let &(start, end) = self.ranges.get(path)?;
+        let line = self
+            .line_starts
+            .partition_point(|&line_start| line_start <= start);
+        let line_start = self.line_starts[line - 1];
+        Some(Span {
+            start,
+            end,
+            line,
+            column: self.src[line_start..start].chars().count() + 1,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_point_at_the_changed_values() {
+        let before = r#"{
+  "name": "svc",
+  "port": 80,
+  "db": {"host": "x", "pool": [1, 2]},
+  "caf\u00e9": "old",
+  "gone": {"a": null}
+}"#;
+        let after = r#"{
+  "name": "svc",
+  "port": 81,
+  "db": {"host": "ÿ", "pool": [1, 2]},
+  "café": "new",
+  "tls": true
+}"#;
+        let (delta, spans) = diff_with_spans(before, after).unwrap();
+        assert_eq!(
+            spans.keys().collect::<Vec<_>>(),
+            delta.keys().collect::<Vec<_>>()
+        );
+        let text = |path: &str, src: &'static str| {
+            let span = spans[path];
+            (&src[span.start..span.end], span.line, span.column)
+        };
+        assert_eq!(text("port", before), ("80", 3, 11));
+        assert_eq!(text("db.host", before), ("\"x\"", 4, 18));
+        assert_eq!(text("café", before), ("\"old\"", 5, 16));
+        assert_eq!(text("gone", before), ("{\"a\": null}", 6, 11));
+        assert_eq!(text("tls", after), ("true", 6, 10));
+    }
+
+    #[test]
+    fn spans_cover_array_elements_and_reject_invalid_json() {
+        let located = Locator::locate("[1, [true, \"a]\"], {}]");
+        let text = |path: &str| {
+            let span = located.span(path).unwrap();
+            &located.src[span.start..span.end]
+        };
+        assert_eq!(text(""), "[1, [true, \"a]\"], {}]");
+        assert_eq!(text("1.1"), "\"a]\"");
+        assert_eq!(text("2"), "{}");
+        assert_eq!(inserted_at("list.+2.name"), "list.2.name");
+
+        assert!(matches!(
+            diff_with_spans("{", "{}"),
+            Err(JsonDiffError::Serialization { .. })
+        ));
+    }
+}
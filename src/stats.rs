@@ -0,0 +1,278 @@
+//! Cheap size and shape metrics for deltas.
+
+use crate::{ArrayOp, Change, Delta};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Counts of the changes in a delta, by kind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeltaStats {
+    /// `Add` and `InsertRange` changes.
+    pub adds: usize,
+    /// `Remove` and `RemoveRange` changes.
+    pub removes: usize,
+    /// `Modify`, `Set`, `NumberDelta`, `Opaque`, `ArrayOps` and `Reorder` changes: edits
+    /// of a value at a path that exists throughout.
+    pub modifies: usize,
+    /// `Move`, `Copy` and `Rename` changes.
+    pub relocations: usize,
+    /// `Unchanged` records of watched paths.
+    pub unchanged: usize,
+}
+
+impl DeltaStats {
+    /// Number of actual changes (everything except `unchanged`).
+    pub fn total(&self) -> usize {
+        self.adds + self.removes + self.modifies + self.relocations
+    }
+
+    fn record(&mut self, change: &Change) {
+        let counter = match change {
+            Change::Add(_) | Change::InsertRange(_) => &mut self.adds,
+            Change::Remove(_) | Change::RemoveRange(_) => &mut self.removes,
+            Change::Modify { .. }
+            | Change::Set(_)
+            | Change::NumberDelta { .. }
+            | Change::Opaque { .. }
+            | Change::ArrayOps(_)
+            | Change::Reorder { .. } => &mut self.modifies,
+            Change::Move { .. } | Change::Copy { .. } | Change::Rename { .. } => {
+                &mut self.relocations
+            }
+            Change::Unchanged(_) => &mut self.unchanged,
+        };
+        *counter += 1;
+    }
+}
+
+/// Count the changes in `delta` by kind.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff, stats};
+///
+/// let totals = stats(&diff(&json!({"a": 1, "b": 2}), &json!({"a": 3, "c": 4})));
+/// assert_eq!((totals.adds, totals.removes, totals.modifies), (1, 1, 1));
+/// ```
+pub fn stats(delta: &Delta) -> DeltaStats {
+    let mut totals = DeltaStats::default();
+    for change in delta.values() {
+        totals.record(change);
+    }
+    totals
+}
+
+/// Count the changes in `delta` per top-level key, in one pass.
+///
+/// Sections are formed as in [`group_by_top_level`](crate::group_by_top_level): a change
+/// belongs to the first segment of its path.
+pub fn stats_by_section(delta: &Delta) -> BTreeMap<String, DeltaStats> {
+    let mut sections: BTreeMap<String, DeltaStats> = BTreeMap::new();
+    for (path, change) in delta {
+        let top = path.split('.').next().unwrap_or(path);
+        match sections.get_mut(top) {
+            Some(section) => section.record(change),
+            None => {
+                let mut section = DeltaStats::default();
+                section.record(change);
+                sections.insert(top.to_string(), section);
+            }
+        }
+    }
+    sections
+}
+
+/// Estimate the size in bytes of `delta` serialized as compact JSON, without serializing it.
+///
+/// Each entry contributes its quoted path, the enum tag wrapping its change (e.g.
+/// `{"Modify":{"old":..,"new":..}}`), and the estimated size of the values or paths it
+/// carries. Values are sized recursively: strings by byte length plus quotes (escapes are
+/// not counted), numbers by digit count, with finite floats assumed to take 18 bytes, and
+/// containers by their elements plus brackets and separators. The result is meant for
+/// capacity planning and is typically within a few percent of the real size.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff, estimated_size};
+///
+/// let delta = diff(&json!({"a": 1}), &json!({"a": 2, "b": "text"}));
+/// let exact = serde_json::to_vec(&delta).unwrap().len();
+/// assert!(estimated_size(&delta).abs_diff(exact) <= exact / 4);
+/// ```
+pub fn estimated_size(delta: &Delta) -> usize {
+    let entries: usize = delta
+        .iter()
+        .map(|(path, change)| quoted_len(path) + 1 + change_size(change))
+        .sum();
+    // Braces plus a comma between entries.
+    entries + 2 + delta.len().saturating_sub(1)
+}
+
+fn change_size(change: &Change) -> usize {
+    // `{"Tag":` ... `}`
+    let tagged = |tag: &str, inner: usize| tag.len() + 5 + inner;
+    match change {
+        Change::Add(v) => tagged("Add", value_size(v)),
+        Change::Remove(v) => tagged("Remove", value_size(v)),
+        Change::Set(v) => tagged("Set", value_size(v)),
+        Change::Unchanged(v) => tagged("Unchanged", value_size(v)),
+        // `{"old":` ... `,"new":` ... `}`
+        Change::Modify { old, new } => tagged("Modify", 15 + value_size(old) + value_size(new)),
+        // `{"from":` ... `,"to":` ... `}`
+        Change::Move { from, to } => tagged("Move", 14 + quoted_len(from) + quoted_len(to)),
+        Change::Copy { from, to } => tagged("Copy", 14 + quoted_len(from) + quoted_len(to)),
+        Change::Rename { from, to } => tagged("Rename", 14 + quoted_len(from) + quoted_len(to)),
+        // `{"by":` ... `}`
+        Change::NumberDelta { by } => tagged("NumberDelta", 7 + value_size(&by.clone().into())),
+        Change::Opaque { old, new } => tagged("Opaque", 15 + quoted_len(old) + quoted_len(new)),
+        // `[` ... `]`
+        Change::ArrayOps(ops) => tagged(
+            "ArrayOps",
+            2 + ops.iter().map(array_op_size).sum::<usize>() + ops.len().saturating_sub(1),
+        ),
+        // `{"keys":[` ... `],"previous":[` ... `]}`
+        Change::Reorder { keys, previous } => {
+            tagged("Reorder", 25 + keys_size(keys) + keys_size(previous))
+        }
+        Change::RemoveRange(values) => tagged("RemoveRange", values_size(values)),
+        Change::InsertRange(values) => tagged("InsertRange", values_size(values)),
+    }
+}
+
+/// A list of values: `[` ... `]` with the commas between them.
+fn values_size(values: &[Value]) -> usize {
+    2 + values.iter().map(value_size).sum::<usize>() + values.len().saturating_sub(1)
+}
+
+/// The quoted keys of a list, with the commas between them.
+fn keys_size(keys: &[String]) -> usize {
+    keys.iter().map(|key| quoted_len(key)).sum::<usize>() + keys.len().saturating_sub(1)
+}
+
+fn array_op_size(op: &ArrayOp) -> usize {
+    // `{"Tag":{"index":` ... `,"value":` ... `}}`
+    let tagged = |tag: &str, inner: usize| tag.len() + 15 + digits(op.index() as u64) + inner;
+    match op {
+        ArrayOp::Insert { value, .. } => tagged("Insert", 9 + value_size(value)),
+        ArrayOp::Delete { value, .. } => tagged("Delete", 9 + value_size(value)),
+        ArrayOp::Update { change, .. } => tagged("Update", 10 + change_size(change)),
+    }
+}
+
+pub(crate) fn value_size(value: &Value) -> usize {
+    match value {
+        Value::Null => 4,
+        Value::Bool(true) => 4,
+        Value::Bool(false) => 5,
+        Value::Number(n) => {
+            if let Some(u) = n.as_u64() {
+                digits(u)
+            } else if let Some(i) = n.as_i64() {
+                1 + digits(i.unsigned_abs())
+            } else {
+                18
+            }
+        }
+        Value::String(s) => quoted_len(s),
+        Value::Array(items) => {
+            2 + items.iter().map(value_size).sum::<usize>() + items.len().saturating_sub(1)
+        }
+        Value::Object(obj) => {
+            2 + obj
+                .iter()
+                .map(|(k, v)| quoted_len(k) + 1 + value_size(v))
+                .sum::<usize>()
+                + obj.len().saturating_sub(1)
+        }
+    }
+}
+
+fn quoted_len(s: &str) -> usize {
+    s.len() + 2
+}
+
+fn digits(mut n: u64) -> usize {
+    let mut count = 1;
+    while n >= 10 {
+        n /= 10;
+        count += 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff;
+    use serde_json::json;
+
+    #[test]
+    fn estimate_is_close_to_serialized_size() {
+        let before = json!({
+            "user": {"name": "Ann", "age": 30, "roles": ["admin", "dev"]},
+            "balance": -1250,
+            "ratio": 0.25,
+            "flags": {"beta": false}
+        });
+        let after = json!({
+            "user": {"name": "Annabel", "age": 31, "roles": ["admin"]},
+            "balance": 4000,
+            "flags": {"beta": true, "new": null},
+            "notes": [{"id": 1, "text": "hello"}]
+        });
+        let delta = diff(&before, &after);
+
+        let exact = serde_json::to_vec(&delta).unwrap().len();
+        let estimate = estimated_size(&delta);
+        assert!(
+            estimate.abs_diff(exact) <= exact / 10,
+            "estimate {estimate} too far from {exact}"
+        );
+    }
+
+    #[test]
+    fn counts_changes_per_section() {
+        let before = json!({
+            "user": {"name": "Ann", "age": 30, "nick": "a"},
+            "settings": {"theme": "dark"},
+            "version": 1
+        });
+        let after = json!({
+            "user": {"name": "Bo", "age": 30, "email": "b@x"},
+            "settings": {"theme": "light"},
+            "version": 1,
+            "archived": true
+        });
+        let delta = diff(&before, &after);
+        let sections = stats_by_section(&delta);
+
+        assert_eq!(
+            sections["user"],
+            DeltaStats {
+                adds: 1,
+                removes: 1,
+                modifies: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(sections["settings"].modifies, 1);
+        assert_eq!(sections["archived"].adds, 1);
+        assert!(!sections.contains_key("version"));
+
+        let totals = stats(&delta);
+        assert_eq!(totals.total(), 5);
+        assert_eq!(
+            sections.values().map(DeltaStats::total).sum::<usize>(),
+            totals.total()
+        );
+    }
+
+    #[test]
+    fn empty_delta_is_two_bytes() {
+        let delta = Delta::new();
+        assert_eq!(
+            estimated_size(&delta),
+            serde_json::to_vec(&delta).unwrap().len()
+        );
+    }
+}
@@ -0,0 +1,223 @@
+//! Applying deltas to documents streamed from a reader.
+
+use crate::{Change, Delta, JsonDiffError, apply_in_place, group_by_top_level};
+use serde::de::{self, DeserializeSeed, MapAccess, Visitor};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{Read, Write};
+
+/// Apply `delta` to a JSON document read from `reader`, writing the result to `writer`.
+///
+/// The base document must be an object. It is read one top-level entry at a time: each
+/// entry is parsed, edited with the changes under its key (with `apply`'s lenient rules)
+/// and written out before the next one is read, so memory use is bounded by the largest
+/// single top-level value rather than by the whole document. Entries keep their input
+/// order; keys added by the delta are written after them, in path order. Output is compact
+/// JSON. Both ends are unbuffered, so wrap files and sockets in `BufReader`/`BufWriter`.
+///
+/// Because entries are processed independently, a change may not reach across them:
+/// changes to the root (the empty path), and `Move`, `Copy` or `Rename` changes whose
+/// endpoints sit under different top-level keys, are rejected with
+/// `JsonDiffError::InvalidPatch` before anything is read. Parse and I/O failures (including
+/// a non-object root) are reported as `JsonDiffError::Serialization`; output written up to
+/// that point is left in `writer`.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{apply_streaming, DeltaBuilder};
+///
+/// let base = br#"{"users": [1, 2], "meta": {"version": 1}}"#;
+/// let delta = DeltaBuilder::new().modify("meta.version", json!(1), json!(2)).build();
+///
+/// let mut out = Vec::new();
+/// apply_streaming(&base[..], &mut out, &delta).unwrap();
+/// assert_eq!(out, br#"{"users":[1,2],"meta":{"version":2}}"#);
+/// ```
+pub fn apply_streaming<R: Read, W: Write>(
+    reader: R,
+    mut writer: W,
+    delta: &Delta,
+) -> Result<(), JsonDiffError> {
+    for (path, change) in delta {
+        if path.is_empty() {
+            return Err(invalid("cannot change the root while streaming"));
+        }
+        if let Change::Move { from, to } | Change::Copy { from, to } | Change::Rename { from, to } =
+            change
+            && top_level(from) != top_level(to)
+        {
+            return Err(invalid(&format!(
+                "`{from}` and `{to}` are in different top-level entries"
+            )));
+        }
+    }
+
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let entries = Entries {
+        writer: &mut writer,
+        groups: group_by_top_level(delta),
+    };
+    entries.deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(())
+}
+
+fn top_level(path: &str) -> &str {
+    path.split('.').next().unwrap_or(path)
+}
+
+fn invalid(message: &str) -> JsonDiffError {
+    JsonDiffError::InvalidPatch {
+        message: format!("streaming apply: {message}"),
+    }
+}
+
+/// Streams the entries of the root object from the deserializer to `writer`.
+struct Entries<'a, W> {
+    writer: &'a mut W,
+    groups: BTreeMap<String, Delta>,
+}
+
+impl<'de, W: Write> DeserializeSeed<'de> for Entries<'_, W> {
+    type Value = ();
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, W: Write> Visitor<'de> for Entries<'_, W> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a JSON object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<(), A::Error> {
+        let mut first = true;
+        write(self.writer, b"{").map_err(de::Error::custom)?;
+        while let Some(key) = map.next_key::<String>()? {
+            let value: Value = map.next_value()?;
+            let group = self.groups.remove(&key);
+            write_entry(self.writer, key, Some(value), group.as_ref(), &mut first)
+                .map_err(de::Error::custom)?;
+        }
+        for (key, group) in std::mem::take(&mut self.groups) {
+            write_entry(self.writer, key, None, Some(&group), &mut first)
+                .map_err(de::Error::custom)?;
+        }
+        write(self.writer, b"}").map_err(de::Error::custom)
+    }
+}
+
+/// Apply `group` to the entry `key` (absent when `value` is `None`) and write whatever
+/// entries result, separated from earlier ones by commas.
+fn write_entry<W: Write>(
+    writer: &mut W,
+    key: String,
+    value: Option<Value>,
+    group: Option<&Delta>,
+    first: &mut bool,
+) -> serde_json::Result<()> {
+    let mut entry = Map::new();
+    if let Some(value) = value {
+        entry.insert(key, value);
+    }
+    let mut entry = Value::Object(entry);
+    if let Some(group) = group {
+        apply_in_place(&mut entry, group);
+    }
+    let Value::Object(entry) = entry else {
+        // Only a change to the root could replace the entry object, and those are rejected.
+        return Ok(());
+    };
+    for (key, value) in entry {
+        if !std::mem::take(first) {
+            write(writer, b",")?;
+        }
+        serde_json::to_writer(&mut *writer, &key)?;
+        write(writer, b":")?;
+        serde_json::to_writer(&mut *writer, &value)?;
+    }
+    Ok(())
+}
+
+fn write<W: Write>(writer: &mut W, bytes: &[u8]) -> serde_json::Result<()> {
+    writer.write_all(bytes).map_err(serde_json::Error::io)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeltaBuilder, apply};
+    use serde_json::json;
+
+    fn stream(base: &str, delta: &Delta) -> Result<String, JsonDiffError> {
+        let mut out = Vec::new();
+        apply_streaming(base.as_bytes(), &mut out, delta)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn streams_a_large_base_with_a_few_edits() {
+        let base: Value = (0..5_000)
+            .map(|i| (format!("k{i}"), json!({"id": i, "tags": ["a", "b"]})))
+            .collect::<Map<_, _>>()
+            .into();
+        let delta = DeltaBuilder::new()
+            .modify("k10.id", json!(10), json!(-10))
+            .remove("k4999", json!({"id": 4999, "tags": ["a", "b"]}))
+            .add("k20.extra", json!(true))
+            .add("zz_new", json!([1]))
+            .build();
+
+        let out = stream(&base.to_string(), &delta).unwrap();
+        let streamed: Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(streamed, apply(&base, &delta));
+    }
+
+    #[test]
+    fn keeps_input_order_and_appends_new_keys() {
+        let delta = DeltaBuilder::new()
+            .add("c.x", json!(1))
+            .modify("z", json!(1), json!(2))
+            .build();
+        assert_eq!(
+            stream(r#"{"z": 1, "a": {"b": 2}}"#, &delta).unwrap(),
+            r#"{"z":2,"a":{"b":2},"c":{"x":1}}"#
+        );
+    }
+
+    #[test]
+    fn rejects_cross_entry_changes() {
+        let mut delta = Delta::new();
+        delta.insert(
+            "b.x".to_string(),
+            Change::Move {
+                from: "a.x".to_string(),
+                to: "b.x".to_string(),
+            },
+        );
+        assert!(matches!(
+            stream(r#"{"a": {"x": 1}, "b": {}}"#, &delta),
+            Err(JsonDiffError::InvalidPatch { .. })
+        ));
+
+        let root = DeltaBuilder::new().modify("", json!({}), json!([])).build();
+        assert!(matches!(
+            stream("{}", &root),
+            Err(JsonDiffError::InvalidPatch { .. })
+        ));
+    }
+
+    #[test]
+    fn reports_non_object_and_malformed_input() {
+        for base in ["[1, 2]", r#"{"a": 1"#, r#"{"a": 1} x"#] {
+            assert!(matches!(
+                stream(base, &Delta::new()),
+                Err(JsonDiffError::Serialization { .. })
+            ));
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! Applying hand-written deltas whose paths contain wildcards.
+
+use crate::path::join_path;
+use crate::{Change, Delta, apply_in_place};
+use serde_json::Value;
+
+/// Wildcard segment matching every key of an object or every element of an array.
+const WILDCARD: &str = "*";
+
+/// Apply a delta whose paths may contain `*` segments, expanding each against the document.
+///
+/// A `*` segment stands for every key of an object, or every index of an array, found at
+/// that point of the document, so `orders.*.status` targets the `status` of each order.
+/// Segments after a wildcard need not exist: `orders.*.archived_at` with `Change::Add`
+/// adds the key to every order. A wildcard over a missing value or a scalar matches
+/// nothing. Changes are expanded in path order, each against the document as the
+/// previous ones left it, and applied leniently, as with `apply`. `Move`, `Copy` and
+/// `Rename` changes are applied as written, without expansion.
+///
+/// Wildcards are meant for hand-written bulk edits; deltas computed by `diff` never
+/// contain them, and plain `apply` treats `*` as an ordinary key.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{apply_template, Change, Delta};
+///
+/// let mut delta = Delta::new();
+/// delta.insert("orders.*.status".to_string(), Change::Add(json!("archived")));
+/// let orders = json!({"orders": {"a": {"status": "open"}, "b": {"status": "paid"}}});
+/// assert_eq!(
+///     apply_template(&orders, &delta),
+///     json!({"orders": {"a": {"status": "archived"}, "b": {"status": "archived"}}})
+/// );
+/// ```
+pub fn apply_template(original: &Value, delta: &Delta) -> Value {
+    let mut result = original.clone();
+    for (pattern, change) in delta {
+        let expanded: Delta = match change {
+            Change::Move { .. } | Change::Copy { .. } | Change::Rename { .. } => {
+                [(pattern.clone(), change.clone())].into_iter().collect()
+            }
+            _ => expand(&result, pattern)
+                .into_iter()
+                .map(|path| (path, change.clone()))
+                .collect(),
+        };
+        apply_in_place(&mut result, &expanded);
+    }
+    result
+}
+
+/// The concrete paths of `value` matched by `pattern`.
+fn expand(value: &Value, pattern: &str) -> Vec<String> {
+    if pattern.is_empty() {
+        return vec![String::new()];
+    }
+    let mut matches = Vec::new();
+    let segments: Vec<&str> = pattern.split('.').collect();
+    expand_into(Some(value), String::new(), &segments, &mut matches);
+    matches
+}
+
+fn expand_into(value: Option<&Value>, path: String, segments: &[&str], out: &mut Vec<String>) {
+    let Some((&segment, rest)) = segments.split_first() else {
+        out.push(path);
+        return;
+    };
+    if segment != WILDCARD {
+        let child = match value {
+            Some(Value::Object(obj)) => obj.get(segment),
+            Some(Value::Array(items)) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        };
+        expand_into(child, join_path(&path, segment), rest, out);
+        return;
+    }
+    match value {
+        Some(Value::Object(obj)) => {
+            for (key, child) in obj {
+                expand_into(Some(child), join_path(&path, key), rest, out);
+            }
+        }
+        Some(Value::Array(items)) => {
+            for (i, child) in items.iter().enumerate() {
+                expand_into(Some(child), join_path(&path, &i.to_string()), rest, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::apply;
+    use serde_json::json;
+
+    fn template(path: &str, change: Change) -> Delta {
+        [(path.to_string(), change)].into_iter().collect()
+    }
+
+    #[test]
+    fn expands_over_object_of_objects() {
+        let before = json!({"users": {"ann": {"active": true}, "bo": {"active": true, "x": 1}}});
+        let delta = template("users.*.active", Change::Add(json!(false)));
+        assert_eq!(
+            apply_template(&before, &delta),
+            json!({"users": {"ann": {"active": false}, "bo": {"active": false, "x": 1}}})
+        );
+        // Plain `apply` reads `*` as a key.
+        assert_eq!(
+            apply(&before, &delta)["users"]["*"],
+            json!({"active": false})
+        );
+    }
+
+    #[test]
+    fn expands_over_arrays() {
+        let before = json!({"orders": [{"status": "open"}, {"status": "paid", "tmp": 1}]});
+        let set = template("orders.*.status", Change::Add(json!("archived")));
+        assert_eq!(
+            apply_template(&before, &set),
+            json!({"orders": [{"status": "archived"}, {"status": "archived", "tmp": 1}]})
+        );
+
+        let remove = template("orders.*", Change::Remove(Value::Null));
+        assert_eq!(apply_template(&before, &remove), json!({"orders": []}));
+    }
+
+    #[test]
+    fn wildcards_over_scalars_match_nothing() {
+        let before = json!({"a": 1, "b": {"c": 2}});
+        let delta = template("a.*.x", Change::Add(json!(true)));
+        assert_eq!(apply_template(&before, &delta), before);
+    }
+}
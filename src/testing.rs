@@ -0,0 +1,135 @@
+//! Assertions for tests of code that diffs and applies documents (`testing` feature).
+
+use crate::{Change, Delta, diff, revert, try_apply, try_revert};
+use serde_json::Value;
+
+/// Assert that `diff(before, after)` replays exactly: applying it to `before` gives
+/// `after`, reverting it on `after` gives `before`, and so does applying its inverse.
+///
+/// The strict functions are used, so a delta that only round-trips leniently fails too.
+/// When the documents have object keys containing `.`, which dot-paths cannot address,
+/// the panic message says so.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::assert_round_trip;
+///
+/// assert_round_trip(&json!({"a": 1, "b": [1]}), &json!({"a": 2, "c": null}));
+/// ```
+#[track_caller]
+pub fn assert_round_trip(before: &Value, after: &Value) {
+    let delta = diff(before, after);
+    let hint = match dotted_key(before).or_else(|| dotted_key(after)) {
+        Some(key) => format!(
+            "\nnote: the key `{key}` contains `.`, which dot-paths read as nesting; \
+             diff with `EscapedDotCodec` or `JsonPointerCodec` instead"
+        ),
+        None => String::new(),
+    };
+    let forward = try_apply(before, &delta);
+    assert!(
+        forward.as_ref() == Ok(after),
+        "applying the delta does not reproduce `after`\n  delta: {delta:?}\n result: {forward:?}{hint}"
+    );
+    let backward = try_revert(after, &delta);
+    assert!(
+        backward.as_ref() == Ok(before),
+        "reverting the delta does not restore `before`\n  delta: {delta:?}\n result: {backward:?}{hint}"
+    );
+    assert_eq!(
+        delta.invert(after).apply(after),
+        revert(after, &delta),
+        "the inverted delta does not undo the delta{hint}"
+    );
+}
+
+/// Assert that `delta` holds exactly the `expected` changes, given as `(path, change)`
+/// pairs in any order.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{assert_delta_eq, diff, Change};
+///
+/// let delta = diff(&json!({"a": 1}), &json!({"a": 2, "b": true}));
+/// assert_delta_eq(
+///     &delta,
+///     [
+///         ("b", Change::Add(json!(true))),
+///         ("a", Change::Modify { old: json!(1), new: json!(2) }),
+///     ],
+/// );
+/// ```
+#[track_caller]
+pub fn assert_delta_eq<'a>(delta: &Delta, expected: impl IntoIterator<Item = (&'a str, Change)>) {
+    let expected: Delta = expected
+        .into_iter()
+        .map(|(path, change)| (path.to_string(), change))
+        .collect();
+    assert_eq!(
+        *delta, expected,
+        "the delta does not hold the expected changes"
+    );
+}
+
+/// The first object key within `value` that contains a `.`.
+fn dotted_key(value: &Value) -> Option<&str> {
+    match value {
+        Value::Object(obj) => obj.iter().find_map(|(key, child)| {
+            if key.contains('.') {
+                Some(key.as_str())
+            } else {
+                dotted_key(child)
+            }
+        }),
+        Value::Array(items) => items.iter().find_map(dotted_key),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn round_trips_pass_for_ordinary_documents() {
+        assert_round_trip(&json!({}), &json!({"a": {"b": [1, 2]}}));
+        assert_round_trip(&json!({"a": 1, "list": [1, 2]}), &json!([true]));
+        assert_round_trip(&json!(null), &json!(null));
+    }
+
+    #[test]
+    #[should_panic(expected = "the key `example.com` contains `.`")]
+    fn dotted_keys_are_pointed_out() {
+        assert_round_trip(
+            &json!({"hosts": {"example.com": 1}}),
+            &json!({"hosts": {"example.com": 2}}),
+        );
+    }
+
+    #[test]
+    fn delta_equality_ignores_order() {
+        let delta = diff(&json!({"a": 1, "b": 1}), &json!({"b": 2, "c": 3}));
+        assert_delta_eq(
+            &delta,
+            [
+                ("c", Change::Add(json!(3))),
+                ("a", Change::Remove(json!(1))),
+                (
+                    "b",
+                    Change::Modify {
+                        old: json!(1),
+                        new: json!(2),
+                    },
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "the delta does not hold the expected changes")]
+    fn delta_mismatches_panic() {
+        let delta = diff(&json!({"a": 1}), &json!({"a": 2}));
+        assert_delta_eq(&delta, [("a", Change::Set(json!(2)))]);
+    }
+}
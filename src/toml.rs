@@ -0,0 +1,102 @@
+//! TOML interop (requires the `toml` feature).
+
+use crate::{Delta, JsonDiffError, diff};
+use serde_json::{Map, Value};
+
+/// Parse two TOML documents into JSON values and diff them.
+///
+/// TOML maps onto JSON with a few caveats:
+///
+/// - datetimes (offset, local, date-only and time-only) become strings in their TOML
+///   form, e.g. `"1979-05-27T07:32:00Z"`;
+/// - `nan` and `inf` floats have no JSON representation and become `null`;
+/// - tables become objects, so key order and comments are not preserved.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff_toml, Change};
+///
+/// let delta = diff_toml("[server]\nport = 80\n", "[server]\nport = 8080\n").unwrap();
+/// assert_eq!(delta["server.port"], Change::Modify { old: json!(80), new: json!(8080) });
+/// ```
+pub fn diff_toml(before: &str, after: &str) -> Result<Delta, JsonDiffError> {
+    Ok(diff(&parse(before)?, &parse(after)?))
+}
+
+fn parse(source: &str) -> Result<Value, JsonDiffError> {
+    let table: toml::Table = source.parse()?;
+    Ok(to_json(toml::Value::Table(table)))
+}
+
+fn to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => serde_json::Number::from_f64(f).map_or(Value::Null, Value::Number),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(dt) => Value::String(dt.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(k, v)| (k, to_json(v)))
+                .collect::<Map<_, _>>(),
+        ),
+    }
+}
+
+impl From<toml::de::Error> for JsonDiffError {
+    fn from(error: toml::de::Error) -> Self {
+        JsonDiffError::Serialization {
+            message: error.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn matches_the_equivalent_json_diff() {
+        let before = r#"
+title = "app"
+released = 1979-05-27T07:32:00Z
+
+[database]
+ports = [8000, 8001]
+enabled = true
+"#;
+        let after = r#"
+title = "app"
+released = 1980-01-01T00:00:00Z
+
+[database]
+ports = [8000]
+enabled = true
+max_conn = 10
+"#;
+        let delta = diff_toml(before, after).unwrap();
+        let expected = diff(
+            &json!({
+                "title": "app",
+                "released": "1979-05-27T07:32:00Z",
+                "database": {"ports": [8000, 8001], "enabled": true}
+            }),
+            &json!({
+                "title": "app",
+                "released": "1980-01-01T00:00:00Z",
+                "database": {"ports": [8000], "enabled": true, "max_conn": 10}
+            }),
+        );
+        assert_eq!(delta, expected);
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        let error = diff_toml("a = ", "a = 1").unwrap_err();
+        assert!(matches!(error, JsonDiffError::Serialization { .. }));
+        assert_eq!(to_json(toml::Value::Float(f64::NAN)), Value::Null);
+    }
+}
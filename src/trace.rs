@@ -0,0 +1,137 @@
+//! `tracing` events for applied changes (the `tracing` feature).
+//!
+//! Every change applied or reverted by this crate goes through [`applied`], which emits
+//! one event with target `json_diff::apply` when the feature is enabled and compiles to
+//! nothing otherwise.
+
+use crate::{Change, JsonDiffError};
+
+/// Target of the events, for filtering (e.g. `RUST_LOG=json_diff::apply=trace`).
+#[cfg(feature = "tracing")]
+pub(crate) const TARGET: &str = "json_diff::apply";
+
+/// Report the outcome of applying `change` at `path`.
+///
+/// A `DEBUG` event carries `path`, `op` (the change kind, e.g. `Modify`) and either
+/// `changed` (whether the document was altered) or `error`. When `TRACE` is enabled for
+/// the target, the event is emitted at `TRACE` instead and also carries `old`, `new` and
+/// `from`: the old and new values as compact JSON and the source path of a relocation,
+/// each empty when the change has none.
+#[cfg(feature = "tracing")]
+pub(crate) fn applied(path: &str, change: &Change, result: &Result<bool, JsonDiffError>) {
+    use tracing::{Level, debug, enabled, trace};
+
+    let op = tracing::field::debug(change.kind());
+    if enabled!(target: TARGET, Level::TRACE) {
+        let json =
+            |value: Option<&serde_json::Value>| value.map_or(String::new(), |v| v.to_string());
+        let old = json(change.old_value());
+        let new = json(change.new_value());
+        let from = change.endpoints().map_or("", |(from, _)| from);
+        match result {
+            Ok(changed) => {
+                trace!(target: TARGET, path, op, changed, old, new, from, "applied change")
+            }
+            Err(error) => {
+                trace!(target: TARGET, path, op, %error, old, new, from, "failed to apply change")
+            }
+        }
+    } else {
+        match result {
+            Ok(changed) => debug!(target: TARGET, path, op, changed, "applied change"),
+            Err(error) => debug!(target: TARGET, path, op, %error, "failed to apply change"),
+        }
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+#[inline(always)]
+pub(crate) fn applied(_path: &str, _change: &Change, _result: &Result<bool, JsonDiffError>) {}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use crate::{DeltaBuilder, apply, try_apply};
+    use serde_json::json;
+    use std::fmt::Debug;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::subscriber::with_default;
+    use tracing::{Event, Metadata, Subscriber};
+
+    /// Records the fields of every event as `name=value` lines.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        trace: bool,
+        events: Arc<Mutex<Vec<String>>>,
+    }
+
+    struct Fields(String);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            self.0.push_str(&format!(" {}={value:?}", field.name()));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.push_str(&format!(" {}={value}", field.name()));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+            self.trace || *metadata.level() <= tracing::Level::DEBUG
+        }
+        fn new_span(&self, _: &Attributes<'_>) -> Id {
+            Id::from_u64(1)
+        }
+        fn record(&self, _: &Id, _: &Record<'_>) {}
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields(format!(
+                "{} {}",
+                event.metadata().target(),
+                event.metadata().level()
+            ));
+            event.record(&mut fields);
+            self.events.lock().unwrap().push(fields.0);
+        }
+        fn enter(&self, _: &Id) {}
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[test]
+    fn emits_an_event_per_change() {
+        let recorder = Recorder::default();
+        let delta = DeltaBuilder::new()
+            .modify("a", json!(1), json!(2))
+            .add("b", json!(true))
+            .build();
+        with_default(recorder.clone(), || apply(&json!({"a": 1}), &delta));
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            [
+                "json_diff::apply DEBUG message=applied change path=a op=Modify changed=true",
+                "json_diff::apply DEBUG message=applied change path=b op=Add changed=true",
+            ]
+        );
+    }
+
+    #[test]
+    fn trace_level_adds_values_and_errors() {
+        let recorder = Recorder {
+            trace: true,
+            ..Default::default()
+        };
+        let delta = DeltaBuilder::new().modify("a", json!(1), json!(2)).build();
+        let result = with_default(recorder.clone(), || try_apply(&json!({}), &delta));
+        assert!(result.is_err());
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            [
+                "json_diff::apply TRACE message=failed to apply change path=a op=Modify \
+                 error=path `a` not found old=1 new=2 from="
+            ]
+        );
+    }
+}
@@ -0,0 +1,460 @@
+//! Rewriting deltas without reference to a document.
+
+use crate::number;
+use crate::path::{join_path, parse_index, parse_insertion, parse_position};
+use crate::{ArrayOp, Change, Delta};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Rewrite every path of `delta` through `f`, dropping entries for which it returns `None`.
+///
+/// The `from`/`to` paths inside `Move`, `Copy` and `Rename` changes are rewritten too; if
+/// `f` drops either of them, the whole entry is dropped. Useful for migrating a delta to a
+/// new document shape or moving it under a different prefix.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff, map_paths};
+///
+/// let delta = diff(&json!({"name": "Ann"}), &json!({"name": "Bo"}));
+/// let moved = map_paths(&delta, |path| Some(format!("profile.{path}")));
+/// assert!(moved.contains_key("profile.name"));
+/// ```
+pub fn map_paths<F: Fn(&str) -> Option<String>>(delta: &Delta, f: F) -> Delta {
+    delta
+        .iter()
+        .filter_map(|(path, change)| {
+            let change = match change {
+                Change::Move { from, to } => Change::Move {
+                    from: f(from)?,
+                    to: f(to)?,
+                },
+                Change::Copy { from, to } => Change::Copy {
+                    from: f(from)?,
+                    to: f(to)?,
+                },
+                Change::Rename { from, to } => Change::Rename {
+                    from: f(from)?,
+                    to: f(to)?,
+                },
+                other => other.clone(),
+            };
+            Some((f(path)?, change))
+        })
+        .collect()
+}
+
+/// Return a copy of `delta` without entries that have no effect when applied.
+///
+/// Dropped are `Modify`s whose `old` and `new` are equal, `NumberDelta`s of zero, empty
+/// `ArrayOps` scripts and element ranges, `Reorder`s that keep the previous order,
+/// `Move`s, `Copy`s and `Rename`s onto their own source, and pairs of a `Remove` at old
+/// index `i` and an `Add` of the same value at new position `+i` in the same array that
+/// cancel out (as many elements of that array are removed before index `i` as are
+/// inserted before it, so the kept element lands back at `i`). `diff` never produces such
+/// entries, but hand-written and composed deltas can. `Unchanged` entries are kept, since
+/// they record that a path was checked.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{compact, DeltaBuilder};
+///
+/// let delta = DeltaBuilder::new()
+///     .modify("a", json!(1), json!(1))
+///     .modify("b", json!(1), json!(2))
+///     .build();
+/// assert_eq!(compact(&delta).keys().collect::<Vec<_>>(), ["b"]);
+/// ```
+pub fn compact(delta: &Delta) -> Delta {
+    let mut removes: BTreeMap<&str, BTreeMap<usize, (&str, &Value)>> = BTreeMap::new();
+    let mut adds: BTreeMap<&str, BTreeMap<usize, (&str, &Value)>> = BTreeMap::new();
+    for (path, change) in delta {
+        let (parent, last) = path.rsplit_once('.').unwrap_or(("", path));
+        match change {
+            Change::Remove(value) => {
+                if let Some(i) = parse_index(last) {
+                    removes.entry(parent).or_default().insert(i, (path, value));
+                }
+            }
+            Change::Add(value) => {
+                if let Some(i) = parse_position(last) {
+                    adds.entry(parent).or_default().insert(i, (path, value));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut cancelled = Vec::new();
+    for (parent, removed) in &removes {
+        let Some(added) = adds.get(parent) else {
+            continue;
+        };
+        for (&i, &(remove_path, old)) in removed {
+            if let Some(&(add_path, new)) = added.get(&i)
+                && old == new
+                && removed.range(..i).count() == added.range(..i).count()
+            {
+                cancelled.push(remove_path);
+                cancelled.push(add_path);
+            }
+        }
+    }
+
+    delta
+        .iter()
+        .filter(|(path, change)| {
+            let no_op = match change {
+                Change::Modify { old, new } => old == new,
+                Change::NumberDelta { by } => number::is_zero(by),
+                Change::ArrayOps(ops) => ops.is_empty(),
+                Change::RemoveRange(values) | Change::InsertRange(values) => values.is_empty(),
+                Change::Reorder { keys, previous } => keys == previous,
+                Change::Move { from, to }
+                | Change::Copy { from, to }
+                | Change::Rename { from, to } => from == to,
+                _ => false,
+            };
+            !no_op && !cancelled.contains(&path.as_str())
+        })
+        .map(|(path, change)| (path.clone(), change.clone()))
+        .collect()
+}
+
+/// Return a copy of `delta` with each run of element removals at consecutive indices of
+/// one array merged into a single `Change::RemoveRange`, and each run of insertions at
+/// consecutive positions (`+N`) into a single `Change::InsertRange`.
+///
+/// A range is keyed by the first path of its run (`tags.3` for removals of `tags.3` to
+/// `tags.7`) and holds the values in index order; single removals and insertions are kept
+/// as they are. The result applies and reverts exactly like `delta`, with each range
+/// spliced in one step, and is much smaller for bulk truncations or appends. With no
+/// document to look at, a numeric last segment is taken to be an array index, so only
+/// use this on deltas of arrays, not of objects with integer keys.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{apply, coalesce_ranges, diff_with, Change, DiffOptions};
+///
+/// let options = DiffOptions { set_arrays: vec!["ids".to_string()], ..Default::default() };
+/// let before = json!({"ids": [1, 2, 3, 4, 5]});
+/// let delta = diff_with(&before, &json!({"ids": [1]}), &options);
+/// assert_eq!(delta.len(), 4);
+/// let ranges = coalesce_ranges(&delta);
+/// assert_eq!(ranges["ids.1"], Change::RemoveRange(vec![json!(2), json!(3), json!(4), json!(5)]));
+/// assert_eq!(apply(&before, &ranges), json!({"ids": [1]}));
+/// ```
+pub fn coalesce_ranges(delta: &Delta) -> Delta {
+    type Runs<'a> = BTreeMap<(&'a str, bool), BTreeMap<usize, (&'a String, &'a Value)>>;
+    let mut runs: Runs = BTreeMap::new();
+    for (path, change) in delta {
+        let (parent, last) = path.rsplit_once('.').unwrap_or(("", path));
+        let (inserting, index, value) = match change {
+            Change::Remove(value) => (false, parse_index(last), value),
+            Change::Add(value) => (true, parse_insertion(last), value),
+            _ => continue,
+        };
+        if let Some(index) = index {
+            runs.entry((parent, inserting))
+                .or_default()
+                .insert(index, (path, value));
+        }
+    }
+
+    let mut coalesced = delta.clone();
+    for ((_, inserting), elements) in runs {
+        let mut elements = elements.into_iter().peekable();
+        while let Some((start, (first, value))) = elements.next() {
+            let mut values = vec![value.clone()];
+            while let Some((_, (path, value))) =
+                elements.next_if(|&(index, _)| index == start + values.len())
+            {
+                coalesced.remove(path.as_str());
+                values.push(value.clone());
+            }
+            if values.len() > 1 {
+                let range = if inserting {
+                    Change::InsertRange(values)
+                } else {
+                    Change::RemoveRange(values)
+                };
+                coalesced.insert(first.clone(), range);
+            }
+        }
+    }
+    coalesced
+}
+
+/// Placeholder written over redacted values.
+const REDACTED: &str = "***";
+
+/// Return a copy of `delta` with the values at sensitive paths replaced by `"***"`.
+///
+/// `is_sensitive` is asked about the path of every change and, within the values a change
+/// carries, about the path of every nested key and element, so a secret stays hidden even
+/// when an enclosing object was added or removed as a whole. Paths and the kind of each
+/// change are preserved, which keeps the result useful for logs and audit trails; it is no
+/// longer meant to be applied. `Move`, `Copy` and `Rename` carry no values and are kept
+/// as they are, as are `NumberDelta` amounts; the digests of an `Opaque` at a sensitive
+/// path are replaced too. The steps of an `ArrayOps` script are redacted as if each
+/// addressed index `i` were the path `array.i`, and so are the elements of a range.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff, redact, Change};
+///
+/// let delta = diff(
+///     &json!({"user": "ann", "password": "hunter2"}),
+///     &json!({"user": "bo", "password": "correct horse"}),
+/// );
+/// let safe = redact(&delta, |path| path.ends_with("password"));
+/// assert_eq!(safe["password"], Change::Modify { old: json!("***"), new: json!("***") });
+/// assert_eq!(safe["user"], delta["user"]);
+/// ```
+pub fn redact<F: Fn(&str) -> bool>(delta: &Delta, is_sensitive: F) -> Delta {
+    delta
+        .iter()
+        .map(|(path, change)| (path.clone(), redact_change(change, path, &is_sensitive)))
+        .collect()
+}
+
+fn redact_change<F: Fn(&str) -> bool>(change: &Change, path: &str, is_sensitive: &F) -> Change {
+    let hide = |value: &Value| redact_value(value, path, is_sensitive);
+    match change {
+        Change::Add(v) => Change::Add(hide(v)),
+        Change::Remove(v) => Change::Remove(hide(v)),
+        Change::Set(v) => Change::Set(hide(v)),
+        Change::Unchanged(v) => Change::Unchanged(hide(v)),
+        Change::Modify { old, new } => Change::Modify {
+            old: hide(old),
+            new: hide(new),
+        },
+        Change::Opaque { .. } if is_sensitive(path) => Change::Opaque {
+            old: REDACTED.to_string(),
+            new: REDACTED.to_string(),
+        },
+        Change::ArrayOps(ops) => Change::ArrayOps(
+            ops.iter()
+                .map(|op| {
+                    let path = join_path(path, &op.index().to_string());
+                    match op {
+                        ArrayOp::Insert { index, value } => ArrayOp::Insert {
+                            index: *index,
+                            value: redact_value(value, &path, is_sensitive),
+                        },
+                        ArrayOp::Delete { index, value } => ArrayOp::Delete {
+                            index: *index,
+                            value: redact_value(value, &path, is_sensitive),
+                        },
+                        ArrayOp::Update { index, change } => ArrayOp::Update {
+                            index: *index,
+                            change: Box::new(redact_change(change, &path, is_sensitive)),
+                        },
+                    }
+                })
+                .collect(),
+        ),
+        Change::RemoveRange(values) => {
+            Change::RemoveRange(redact_range(values, path, is_sensitive))
+        }
+        Change::InsertRange(values) => {
+            Change::InsertRange(redact_range(values, path, is_sensitive))
+        }
+        other => other.clone(),
+    }
+}
+
+/// Redact the elements of a range keyed at `path`, each at the path of its own index.
+fn redact_range<F: Fn(&str) -> bool>(values: &[Value], path: &str, is_sensitive: &F) -> Vec<Value> {
+    let (parent, last) = path.rsplit_once('.').unwrap_or(("", path));
+    let start = parse_position(last).unwrap_or_default();
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            redact_value(
+                value,
+                &join_path(parent, &(start + i).to_string()),
+                is_sensitive,
+            )
+        })
+        .collect()
+}
+
+fn redact_value<F: Fn(&str) -> bool>(value: &Value, path: &str, is_sensitive: &F) -> Value {
+    if is_sensitive(path) {
+        return Value::from(REDACTED);
+    }
+    match value {
+        Value::Object(obj) => obj
+            .iter()
+            .map(|(key, v)| {
+                (
+                    key.clone(),
+                    redact_value(v, &join_path(path, key), is_sensitive),
+                )
+            })
+            .collect(),
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, v)| redact_value(v, &join_path(path, &i.to_string()), is_sensitive))
+            .collect(),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DeltaBuilder, apply, diff};
+    use serde_json::json;
+
+    #[test]
+    fn remapped_delta_applies_to_migrated_shape() {
+        let before = json!({"user_name": "Ann", "user_age": 30, "internal": 1});
+        let after = json!({"user_name": "Bo", "user_age": 31, "internal": 2});
+        let delta = diff(&before, &after);
+
+        let migrated = map_paths(&delta, |path| match path {
+            "internal" => None,
+            other => other
+                .strip_prefix("user_")
+                .map(|field| format!("user.{field}")),
+        });
+        assert_eq!(
+            migrated.keys().collect::<Vec<_>>(),
+            ["user.age", "user.name"]
+        );
+
+        let migrated_before = json!({"user": {"name": "Ann", "age": 30}});
+        assert_eq!(
+            apply(&migrated_before, &migrated),
+            json!({"user": {"name": "Bo", "age": 31}})
+        );
+    }
+
+    #[test]
+    fn relocations_are_rewritten_or_dropped() {
+        let mut delta = Delta::new();
+        delta.insert(
+            "b".to_string(),
+            Change::Move {
+                from: "a".to_string(),
+                to: "b".to_string(),
+            },
+        );
+        let prefixed = map_paths(&delta, |path| Some(format!("x.{path}")));
+        assert_eq!(
+            prefixed["x.b"],
+            Change::Move {
+                from: "x.a".to_string(),
+                to: "x.b".to_string(),
+            }
+        );
+        let dropped = map_paths(&delta, |path| (path != "a").then(|| path.to_string()));
+        assert!(dropped.is_empty());
+    }
+
+    #[test]
+    fn compact_drops_no_op_modifies() {
+        let before = json!({"a": 1, "b": 2});
+        let delta = DeltaBuilder::new()
+            .modify("a", json!(1), json!(1))
+            .modify("b", json!(2), json!(3))
+            .build();
+        let compacted = compact(&delta);
+        assert_eq!(compacted.keys().collect::<Vec<_>>(), ["b"]);
+        assert_eq!(apply(&before, &compacted), apply(&before, &delta));
+    }
+
+    #[test]
+    fn compact_cancels_matching_remove_and_add() {
+        let before = json!({"tags": ["a", "b", "c"]});
+        let delta = DeltaBuilder::new()
+            .remove("tags.1", json!("b"))
+            .add("tags.+1", json!("b"))
+            .remove("tags.2", json!("c"))
+            .build();
+        let compacted = compact(&delta);
+        assert_eq!(compacted.keys().collect::<Vec<_>>(), ["tags.2"]);
+        assert_eq!(apply(&before, &compacted), apply(&before, &delta));
+
+        // Here the insertion lands after a removed element, so the pair is not a no-op.
+        let shifted = DeltaBuilder::new()
+            .remove("tags.0", json!("a"))
+            .remove("tags.1", json!("b"))
+            .add("tags.+1", json!("b"))
+            .build();
+        assert_eq!(compact(&shifted), shifted);
+    }
+
+    #[test]
+    fn redact_hides_sensitive_values_at_any_depth() {
+        let before = json!({"db": {"host": "a", "password": "old"}, "tokens": ["t1"]});
+        let after = json!({
+            "db": {"host": "b", "password": "new"},
+            "admin": {"name": "root", "password": "pw"}
+        });
+        let delta = diff(&before, &after);
+        let safe = redact(&delta, |path| {
+            path.ends_with(".password") || path == "tokens"
+        });
+
+        assert_eq!(
+            safe["db.password"],
+            Change::Modify {
+                old: json!("***"),
+                new: json!("***")
+            }
+        );
+        assert_eq!(
+            safe["admin"],
+            Change::Add(json!({"name": "root", "password": "***"}))
+        );
+        assert_eq!(safe["tokens"], Change::Remove(json!("***")));
+        assert_eq!(safe["db.host"], delta["db.host"]);
+        assert_eq!(
+            safe.keys().collect::<Vec<_>>(),
+            delta.keys().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn coalesced_ranges_apply_like_their_elements() {
+        let options = crate::DiffOptions {
+            set_arrays: vec!["tags".to_string(), "ids".to_string()],
+            ..Default::default()
+        };
+        let before = json!({
+            "tags": ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"],
+            "ids": [1, 2],
+            "n": 1
+        });
+        let after = json!({
+            "tags": ["a", "b", "c", "i", "j"],
+            "ids": [1, 7, 8, 9, 2, 5],
+            "n": 2
+        });
+        let delta = crate::diff_with(&before, &after, &options);
+        let ranges = coalesce_ranges(&delta);
+        assert_eq!(
+            ranges.keys().collect::<Vec<_>>(),
+            ["ids.+1", "ids.+5", "n", "tags.3"]
+        );
+        assert_eq!(
+            ranges["tags.3"],
+            Change::RemoveRange(["d", "e", "f", "g", "h"].map(Value::from).to_vec())
+        );
+        assert_eq!(
+            ranges["ids.+1"],
+            Change::InsertRange(vec![json!(7), json!(8), json!(9)])
+        );
+        assert_eq!(ranges["ids.+5"], delta["ids.+5"]);
+
+        assert_eq!(crate::try_apply(&before, &ranges), Ok(after.clone()));
+        assert_eq!(crate::try_revert(&after, &ranges), Ok(before.clone()));
+        assert_eq!(ranges.invert(&after).apply(&after), before);
+        assert_eq!(coalesce_ranges(&ranges), ranges);
+    }
+}
@@ -0,0 +1,181 @@
+//! Git-style textual diffs of JSON documents.
+
+use crate::path::{is_within, join_path};
+use crate::{Delta, diff, normalize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// Render the differences between two documents as a unified-diff-like text.
+///
+/// Both documents are pretty-printed in a canonical form (keys sorted, two-space indent)
+/// and merged into one listing: each line starts with `-` when it belongs to `before`
+/// only, `+` when it belongs to `after` only, and a space otherwise. Which lines differ is
+/// decided by the paths of `diff(before, after)`, not by comparing text: objects on a
+/// changed path are opened and their keys listed one by one, while any other changed
+/// value (such as a whole array) is printed as removed and then added. Unchanged parts
+/// are printed in full as context; there are no hunk headers.
+///
+/// Trailing commas follow each side's own layout, so a context line may carry a comma
+/// that only one side needs.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::unified_diff;
+///
+/// let text = unified_diff(&json!({"a": 1, "b": true}), &json!({"a": 2, "b": true}));
+/// assert_eq!(text, " {\n-  \"a\": 1,\n+  \"a\": 2,\n   \"b\": true\n }\n");
+/// ```
+pub fn unified_diff(before: &Value, after: &Value) -> String {
+    let delta = diff(before, after);
+    let mut out = String::new();
+    Renderer {
+        delta: &delta,
+        out: &mut out,
+    }
+    .entry("", None, Some(before), Some(after), 0, (false, false));
+    out
+}
+
+struct Renderer<'a> {
+    delta: &'a Delta,
+    out: &'a mut String,
+}
+
+impl Renderer<'_> {
+    /// Render the entry at `path` (labelled `key` inside an object), present on either or
+    /// both sides. `commas` says whether the entry is followed by a sibling in `before`
+    /// and in `after`.
+    fn entry(
+        &mut self,
+        path: &str,
+        key: Option<&str>,
+        before: Option<&Value>,
+        after: Option<&Value>,
+        depth: usize,
+        commas: (bool, bool),
+    ) {
+        match (before, after) {
+            (Some(_), Some(b)) if !self.touched(path) => self.value(' ', key, b, depth, commas.1),
+            (Some(Value::Object(a)), Some(Value::Object(b))) => {
+                self.line(' ', depth, &format!("{}{{", label(key)));
+                let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+                let last_a = a.keys().max();
+                let last_b = b.keys().max();
+                for k in keys {
+                    let commas = (
+                        a.contains_key(k) && Some(k) != last_a,
+                        b.contains_key(k) && Some(k) != last_b,
+                    );
+                    self.entry(
+                        &join_path(path, k),
+                        Some(k),
+                        a.get(k),
+                        b.get(k),
+                        depth + 1,
+                        commas,
+                    );
+                }
+                self.line(' ', depth, &format!("}}{}", comma(commas.1)));
+            }
+            (a, b) => {
+                if let Some(a) = a {
+                    self.value('-', key, a, depth, commas.0);
+                }
+                if let Some(b) = b {
+                    self.value('+', key, b, depth, commas.1);
+                }
+            }
+        }
+    }
+
+    /// Whether any change of the delta lies at or below `path`.
+    fn touched(&self, path: &str) -> bool {
+        self.delta
+            .range(path.to_string()..)
+            .take_while(|(p, _)| p.starts_with(path))
+            .any(|(p, _)| is_within(p, path))
+    }
+
+    /// Print `value` in full, every line marked with `sign`.
+    fn value(&mut self, sign: char, key: Option<&str>, value: &Value, depth: usize, comma: bool) {
+        let pretty =
+            serde_json::to_string_pretty(&normalize(value)).expect("values always serialize");
+        let lines: Vec<&str> = pretty.lines().collect();
+        for (i, text) in lines.iter().enumerate() {
+            let prefix = if i == 0 { label(key) } else { String::new() };
+            let suffix = if i + 1 == lines.len() { comma } else { false };
+            self.line(
+                sign,
+                depth,
+                &format!("{prefix}{text}{}", self::comma(suffix)),
+            );
+        }
+    }
+
+    fn line(&mut self, sign: char, depth: usize, text: &str) {
+        self.out.push(sign);
+        self.out.push_str(&"  ".repeat(depth));
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+}
+
+/// The `"key": ` prefix of an object entry.
+fn label(key: Option<&str>) -> String {
+    key.map_or_else(String::new, |k| format!("{}: ", Value::from(k)))
+}
+
+fn comma(present: bool) -> &'static str {
+    if present { "," } else { "" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn marks_changed_lines() {
+        let before = json!({"name": "app", "deps": {"serde": "1.0", "old": "0.1"}, "tags": [1]});
+        let after = json!({"name": "app", "deps": {"serde": "1.1"}, "tags": [1, 2], "new": null});
+        let text = unified_diff(&before, &after);
+        let expected = r#" {
+   "deps": {
+-    "old": "0.1",
+-    "serde": "1.0"
++    "serde": "1.1"
+   },
+   "name": "app",
++  "new": null,
+-  "tags": [
+-    1
+-  ]
++  "tags": [
++    1,
++    2
++  ]
+ }
+"#;
+        assert_eq!(text, expected);
+        assert!(text.lines().any(|line| line == "-    \"serde\": \"1.0\""));
+        assert!(text.lines().any(|line| line == "+  \"new\": null,"));
+    }
+
+    #[test]
+    fn equal_documents_are_all_context() {
+        let value = json!({"a": [1, {"b": 2}]});
+        let text = unified_diff(&value, &value);
+        assert!(text.lines().all(|line| line.starts_with(' ')));
+        let context: String = text
+            .lines()
+            .map(|line| &line[1..])
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(context, serde_json::to_string_pretty(&value).unwrap());
+    }
+
+    #[test]
+    fn root_replacement_is_removed_then_added() {
+        assert_eq!(unified_diff(&json!(1), &json!("x")), "-1\n+\"x\"\n");
+    }
+}
@@ -0,0 +1,109 @@
+//! Walking a `Delta` with a visitor.
+
+use crate::{ArrayOp, Change, Delta};
+use serde_json::{Number, Value};
+
+/// Callbacks for each kind of change, driven by [`walk`].
+///
+/// Every method has a no-op default, so implementors override only the kinds they care
+/// about, and new kinds of change get a new defaulted method instead of breaking them.
+/// `path` is the key of the change in the delta; relocations (keyed by `to`) receive
+/// their `from` and `to` paths instead.
+pub trait DeltaVisitor {
+    fn visit_add(&mut self, _path: &str, _value: &Value) {}
+    fn visit_remove(&mut self, _path: &str, _value: &Value) {}
+    fn visit_modify(&mut self, _path: &str, _old: &Value, _new: &Value) {}
+    fn visit_set(&mut self, _path: &str, _value: &Value) {}
+    fn visit_unchanged(&mut self, _path: &str, _value: &Value) {}
+    fn visit_move(&mut self, _from: &str, _to: &str) {}
+    fn visit_copy(&mut self, _from: &str, _to: &str) {}
+    fn visit_rename(&mut self, _from: &str, _to: &str) {}
+    fn visit_number_delta(&mut self, _path: &str, _by: &Number) {}
+    fn visit_opaque(&mut self, _path: &str, _old_digest: &str, _new_digest: &str) {}
+    fn visit_array_ops(&mut self, _path: &str, _ops: &[ArrayOp]) {}
+    fn visit_reorder(&mut self, _path: &str, _keys: &[String]) {}
+    fn visit_remove_range(&mut self, _path: &str, _values: &[Value]) {}
+    fn visit_insert_range(&mut self, _path: &str, _values: &[Value]) {}
+}
+
+/// Call the matching `visitor` method for every change of `delta`, in path order.
+///
+/// ```rust
+/// use serde_json::{json, Value};
+/// use json_diff::{diff, walk, DeltaVisitor};
+///
+/// struct Added(Vec<String>);
+///
+/// impl DeltaVisitor for Added {
+///     fn visit_add(&mut self, path: &str, _value: &Value) {
+///         self.0.push(path.to_string());
+///     }
+/// }
+///
+/// let mut added = Added(Vec::new());
+/// walk(&diff(&json!({"a": 1}), &json!({"a": 2, "b": 3})), &mut added);
+/// assert_eq!(added.0, ["b"]);
+/// ```
+pub fn walk<V: DeltaVisitor + ?Sized>(delta: &Delta, visitor: &mut V) {
+    for (path, change) in delta {
+        match change {
+            Change::Add(value) => visitor.visit_add(path, value),
+            Change::Remove(value) => visitor.visit_remove(path, value),
+            Change::Modify { old, new } => visitor.visit_modify(path, old, new),
+            Change::Set(value) => visitor.visit_set(path, value),
+            Change::Unchanged(value) => visitor.visit_unchanged(path, value),
+            Change::Move { from, to } => visitor.visit_move(from, to),
+            Change::Copy { from, to } => visitor.visit_copy(from, to),
+            Change::Rename { from, to } => visitor.visit_rename(from, to),
+            Change::NumberDelta { by } => visitor.visit_number_delta(path, by),
+            Change::Opaque { old, new } => visitor.visit_opaque(path, old, new),
+            Change::ArrayOps(ops) => visitor.visit_array_ops(path, ops),
+            Change::Reorder { keys, .. } => visitor.visit_reorder(path, keys),
+            Change::RemoveRange(values) => visitor.visit_remove_range(path, values),
+            Change::InsertRange(values) => visitor.visit_insert_range(path, values),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DeltaBuilder;
+    use serde_json::json;
+
+    /// Summarizes adds, removes and modifies, ignoring everything else.
+    #[derive(Default)]
+    struct Summary(String);
+
+    impl DeltaVisitor for Summary {
+        fn visit_add(&mut self, path: &str, value: &Value) {
+            self.0.push_str(&format!("+{path}={value};"));
+        }
+        fn visit_remove(&mut self, path: &str, _value: &Value) {
+            self.0.push_str(&format!("-{path};"));
+        }
+        fn visit_modify(&mut self, path: &str, old: &Value, new: &Value) {
+            self.0.push_str(&format!("~{path}:{old}->{new};"));
+        }
+    }
+
+    #[test]
+    fn visitor_accumulates_a_summary() {
+        let mut delta = DeltaBuilder::new()
+            .add("b", json!(true))
+            .remove("a", json!(1))
+            .modify("c.d", json!("x"), json!("y"))
+            .build();
+        delta.insert(
+            "e".to_string(),
+            Change::Move {
+                from: "z".to_string(),
+                to: "e".to_string(),
+            },
+        );
+
+        let mut summary = Summary::default();
+        walk(&delta, &mut summary);
+        assert_eq!(summary.0, r#"-a;+b=true;~c.d:"x"->"y";"#);
+    }
+}
@@ -0,0 +1,80 @@
+//! YAML interop (requires the `yaml` feature).
+
+use crate::{Delta, JsonDiffError, apply, diff};
+use serde_json::Value;
+
+/// Parse two YAML documents into JSON values and diff them.
+///
+/// Parsing goes through `serde_yaml`, so the JSON data model applies: mapping keys
+/// become strings, and tags, anchors and comments are not preserved.
+///
+/// ```rust
+/// use serde_json::json;
+/// use json_diff::{diff_yaml, Change};
+///
+/// let delta = diff_yaml("replicas: 2\nimage: app:1\n", "replicas: 3\nimage: app:1\n").unwrap();
+/// assert_eq!(delta["replicas"], Change::Modify { old: json!(2), new: json!(3) });
+/// ```
+pub fn diff_yaml(before: &str, after: &str) -> Result<Delta, JsonDiffError> {
+    Ok(diff(&parse(before)?, &parse(after)?))
+}
+
+/// Apply `delta` to a YAML document and serialize the result back to YAML.
+pub fn apply_yaml(original: &str, delta: &Delta) -> Result<String, JsonDiffError> {
+    Ok(serde_yaml::to_string(&apply(&parse(original)?, delta))?)
+}
+
+fn parse(yaml: &str) -> Result<Value, JsonDiffError> {
+    Ok(serde_yaml::from_str(yaml)?)
+}
+
+impl From<serde_yaml::Error> for JsonDiffError {
+    fn from(error: serde_yaml::Error) -> Self {
+        JsonDiffError::Serialization {
+            message: error.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Change;
+    use serde_json::json;
+
+    const BEFORE: &str = "\
+service:
+  name: api
+  ports: [80, 443]
+debug: true
+";
+    const AFTER: &str = "\
+service:
+  name: api
+  ports: [80]
+  replicas: 2
+";
+
+    #[test]
+    fn diffs_yaml_documents() {
+        let delta = diff_yaml(BEFORE, AFTER).unwrap();
+        assert_eq!(delta.len(), 3);
+        assert_eq!(delta["debug"], Change::Remove(json!(true)));
+        assert_eq!(delta["service.replicas"], Change::Add(json!(2)));
+    }
+
+    #[test]
+    fn applies_and_reserializes_yaml() {
+        let delta = diff_yaml(BEFORE, AFTER).unwrap();
+        let applied = apply_yaml(BEFORE, &delta).unwrap();
+        let reparsed: Value = serde_yaml::from_str(&applied).unwrap();
+        let expected: Value = serde_yaml::from_str(AFTER).unwrap();
+        assert_eq!(reparsed, expected);
+    }
+
+    #[test]
+    fn reports_parse_errors() {
+        let error = diff_yaml("a: [1", "a: 1").unwrap_err();
+        assert!(matches!(error, JsonDiffError::Serialization { .. }));
+    }
+}